@@ -0,0 +1,137 @@
+pub const USAGE: &str = "Usage: rsl <command> <files...> [options]
+Files ending with .rslir contain macro instructions (like emit-ir prints them).
+
+Commands:
+    build       Compile the files into an executable
+    emit-asm    Print the generated assembly
+    emit-ir     Print the generated macro instructions
+    check       Only look for errors in the code
+
+Options:
+    -o <path>           Where to write the output to (stdout for emit-asm/emit-ir by default)
+    --target <arch>     The architecture to generate code for (defaults to the host)
+    -O<level>           The optimization level (0-3)
+    --verify-ir         Check the macro instructions before using them (always done by debug builds)
+    -v, --verbose       Explain what's happening
+    -q, --quiet         Don't show the output of external tools
+    -h, --help          Show this message";
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    Build,
+    EmitAsm,
+    EmitIr,
+    Check,
+    Help,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Verbosity {
+    Quiet,
+    Normal,
+    Verbose,
+}
+
+/// Everything the command line arguments specify
+#[derive(Clone, Debug, PartialEq)]
+pub struct Options {
+    pub command: Command,
+    pub files: Vec<String>,
+    pub output: Option<String>,
+    pub target: Option<String>,         // The name of the architecture (see Arch::name)
+    pub optimization_level: u8,
+    pub verbosity: Verbosity,
+    pub verify_ir: bool,
+}
+
+/// Parse the arguments (without the name of the executable).
+/// Returns a message explaining the problem if they're malformed.
+pub fn parse_arguments(arguments: &[String]) -> Result<Options, String> {
+    let mut command: Option<Command> = None;
+    let mut options = Options { command: Command::Help, files: Vec::new(), output: None, target: None, optimization_level: 0, verbosity: Verbosity::Normal, verify_ir: false };
+
+    let mut arguments = arguments.iter();
+
+    while let Some(argument) = arguments.next() {
+        match argument.as_str() {
+            "-h" | "--help" => return Ok(Options { command: Command::Help, ..options }),
+            "-v" | "--verbose" => options.verbosity = Verbosity::Verbose,
+            "-q" | "--quiet" => options.verbosity = Verbosity::Quiet,
+            "--verify-ir" => options.verify_ir = true,
+            "-o" => {
+                let Some(output) = arguments.next() else { return Err("-o must be followed by a path.".to_string()); };
+                options.output = Some(output.clone());
+            }
+            "--target" => {
+                let Some(target) = arguments.next() else { return Err("--target must be followed by the name of an architecture.".to_string()); };
+                options.target = Some(target.clone());
+            }
+            _ if argument.starts_with("--target=") => {
+                options.target = Some(argument["--target=".len()..].to_string());
+            }
+            _ if argument.starts_with("-O") => {
+                let level = &argument[2..];
+
+                match level.parse::<u8>() {
+                    Ok(level) if level <= 3 => options.optimization_level = level,
+                    _ => return Err(format!("Unknown optimization level \"{}\" (expected 0-3).", level)),
+                }
+            }
+            _ if argument.starts_with('-') => return Err(format!("Unknown option \"{}\".", argument)),
+            _ if command.is_none() => {
+                command = Some(match argument.as_str() {
+                    "build" => Command::Build,
+                    "emit-asm" => Command::EmitAsm,
+                    "emit-ir" => Command::EmitIr,
+                    "check" => Command::Check,
+                    "help" => Command::Help,
+                    _ => return Err(format!("Unknown command \"{}\".", argument)),
+                });
+            }
+            _ => options.files.push(argument.clone()),
+        }
+    }
+
+    let Some(command) = command else { return Err("No command given.".to_string()); };
+
+    if command != Command::Help && options.files.is_empty() {
+        return Err("No files given.".to_string());
+    }
+
+    Ok(Options { command, ..options })
+}
+
+#[cfg(test)]
+mod tests{
+    use crate::cli::arguments::{parse_arguments, Command, Verbosity};
+
+    fn parse(arguments: &str) -> Result<crate::cli::arguments::Options, String> {
+        parse_arguments(&arguments.split_whitespace().map(|x| x.to_string()).collect::<Vec<String>>())
+    }
+
+    #[test]
+    fn test_parse_arguments(){
+        let options = parse("build main.rsl -o app --target aarch64-mac-os -O2 util.rsl -v").unwrap();
+
+        assert_eq!(options.command, Command::Build);
+        assert_eq!(options.files, vec!["main.rsl".to_string(), "util.rsl".to_string()]);
+        assert_eq!(options.output, Some("app".to_string()));
+        assert_eq!(options.target, Some("aarch64-mac-os".to_string()));
+        assert_eq!(options.optimization_level, 2);
+        assert_eq!(options.verbosity, Verbosity::Verbose);
+
+        assert_eq!(parse("emit-ir --target=x main.rsl").unwrap().target, Some("x".to_string()));
+        assert!(parse("emit-ir main.rsl --verify-ir").unwrap().verify_ir);
+        assert_eq!(parse("check main.rsl --help").unwrap().command, Command::Help);
+    }
+
+    #[test]
+    fn test_bad_arguments(){
+        assert_eq!(parse(""), Err("No command given.".to_string()));
+        assert_eq!(parse("check"), Err("No files given.".to_string()));
+        assert_eq!(parse("run main.rsl"), Err("Unknown command \"run\".".to_string()));
+        assert_eq!(parse("build main.rsl -O9"), Err("Unknown optimization level \"9\" (expected 0-3).".to_string()));
+        assert_eq!(parse("build main.rsl -o"), Err("-o must be followed by a path.".to_string()));
+        assert_eq!(parse("build main.rsl --fast"), Err("Unknown option \"--fast\".".to_string()));
+    }
+}
@@ -0,0 +1,209 @@
+use std::fs;
+use std::path::Path;
+use std::process::{Command as Process, Stdio};
+use crate::cli::arguments::{Command, Options, Verbosity, USAGE};
+use crate::compiler::diagnostic::Diagnostic;
+use crate::compiler::low_level::arch::arch::{all_architectures, architecture_named, host_architecture, Arch};
+use crate::compiler::low_level::ir_text::{parse_ir, print_functions};
+use crate::compiler::low_level::macro_function::MacroFunction;
+use crate::compiler::low_level::verifier::verify_functions;
+use crate::compiler::lowering::lowering::Lowering;
+use crate::compiler::parser::ast::Module;
+use crate::compiler::parser::parser::parse_source;
+use crate::util::exit::{exit, exit_with_diagnostics, ExitCode};
+
+/// The extension of files that contain macro instructions instead of code
+const IR_EXTENSION: &str = ".rslir";
+
+/// Run the command the options describe
+pub fn run(options: Options) {
+    if options.command == Command::Help {
+        println!("{}", USAGE);
+        return;
+    }
+
+    // Pick the architecture first, so a typo in it is reported before any work is done
+    let arch = match options.command {
+        Command::EmitAsm | Command::Build => Some(select_architecture(&options)),
+        _ => None,
+    };
+
+    if let Some(arch) = &arch && options.command == Command::Build && !arch.is_native() {
+        exit(format!("Executables can't be built for {}, use emit-asm instead.", arch.name()), ExitCode::BadArgument);
+    }
+
+    let (modules, ir_functions) = parse_files(&options);
+
+    // Hand-written instructions are the user's responsibility, the lowered ones the compiler's
+    let exit_code = if ir_functions.is_empty() { ExitCode::Internal } else { ExitCode::BadCode };
+
+    let mut functions = lower(&options, &modules);
+    functions.extend(ir_functions);
+
+    if options.verify_ir || cfg!(debug_assertions) {
+        verify(&options, &functions, exit_code);
+    }
+
+    match options.command {
+        Command::Check => {
+            log(&options, format!("No errors found in {} file(s).", options.files.len()));
+        }
+        Command::EmitIr => {
+            write_output(&options, print_functions(&functions));
+        }
+        Command::EmitAsm => {
+            let assembly = generate_assembly(&options, arch.unwrap().as_ref(), functions);
+            write_output(&options, assembly);
+        }
+        Command::Build => {
+            let arch = arch.unwrap();
+            let assembly = generate_assembly(&options, arch.as_ref(), functions);
+            build(&options, arch.as_ref(), assembly);
+        }
+        Command::Help => {}
+    }
+}
+
+fn log(options: &Options, message: String) {
+    if options.verbosity == Verbosity::Verbose {
+        eprintln!("{}", message);
+    }
+}
+
+fn select_architecture(options: &Options) -> Box<dyn Arch> {
+    let Some(target) = &options.target else {
+        let Some(arch) = host_architecture() else {
+            exit("Code can't be generated for this machine, please pick a target using --target.".to_string(), ExitCode::BadArgument);
+        };
+        return arch;
+    };
+
+    let Some(arch) = architecture_named(target) else {
+        let names = all_architectures().iter().map(|x| x.name()).collect::<Vec<String>>().join(", ");
+        exit(format!("Unknown target \"{}\" (available targets: {}).", target, names), ExitCode::BadArgument);
+    };
+
+    arch
+}
+
+/// Parse all the files and report the errors of all of them at once.
+/// Files ending with .rslir contain macro instructions (see emit-ir), their functions are returned separately.
+fn parse_files(options: &Options) -> (Vec<Module>, Vec<MacroFunction>) {
+    let mut modules = Vec::new();
+    let mut functions = Vec::new();
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+
+    for file in &options.files {
+        log(options, format!("Parsing {}", file));
+
+        let source = match fs::read_to_string(file) {
+            Ok(source) => source,
+            Err(error) => exit(format!("Couldn't read \"{}\": {}", file, error), ExitCode::FileAccess),
+        };
+
+        if file.ends_with(IR_EXTENSION) {
+            let (file_functions, file_diagnostics) = parse_ir(file.clone(), &source);
+
+            functions.extend(file_functions);
+            diagnostics.extend(file_diagnostics);
+            continue;
+        }
+
+        let (module, file_diagnostics) = parse_source(file.clone(), &source);
+
+        modules.push(module);
+        diagnostics.extend(file_diagnostics);
+    }
+
+    if !diagnostics.is_empty() {
+        exit_with_diagnostics(&diagnostics, ExitCode::BadCode);
+    }
+
+    (modules, functions)
+}
+
+fn lower(options: &Options, modules: &[Module]) -> Vec<MacroFunction> {
+    // The app is named after the output (or the first file) unless the code names it itself
+    let app_path = options.output.clone().unwrap_or(options.files[0].clone());
+    let app_name = Path::new(&app_path).file_stem().map(|x| x.to_string_lossy().to_string()).unwrap_or(app_path);
+
+    let (functions, diagnostics) = Lowering::new(modules, app_name).lower_program();
+
+    if !diagnostics.is_empty() {
+        exit_with_diagnostics(&diagnostics, ExitCode::BadCode);
+    }
+
+    log(options, format!("Lowered {} function(s)", functions.len()));
+
+    functions
+}
+
+fn verify(options: &Options, functions: &[MacroFunction], exit_code: ExitCode) {
+    let errors = verify_functions(functions);
+
+    if !errors.is_empty() {
+        let message = errors.iter().map(|x| x.to_string()).collect::<Vec<String>>().join("\n");
+        exit(format!("The macro instructions aren't valid:\n{}", message), exit_code);
+    }
+
+    log(options, format!("Verified {} function(s)", functions.len()));
+}
+
+fn generate_assembly(options: &Options, arch: &dyn Arch, functions: Vec<MacroFunction>) -> String {
+    log(options, format!("Generating assembly for {} ({} bit)", arch.name(), arch.architecture_bits()));
+
+    if !functions.iter().any(|x| x.name == Lowering::ENTRY_POINT_NAME) {
+        exit("The program doesn't have a main function.".to_string(), ExitCode::BadCode);
+    }
+
+    arch.generate_assembly(functions)
+}
+
+/// Write the output to the file given using -o or to stdout if there is none
+fn write_output(options: &Options, output: String) {
+    match &options.output {
+        Some(path) => {
+            if let Err(error) = fs::write(path, output) {
+                exit(format!("Couldn't write \"{}\": {}", path, error), ExitCode::FileAccess);
+            }
+        }
+        None => print!("{}", output),
+    }
+}
+
+/// Assemble and link the assembly (or compile the C code) using the system's C compiler
+fn build(options: &Options, arch: &dyn Arch, assembly: String) {
+    let output = options.output.clone().unwrap_or_else(|| {
+        Path::new(&options.files[0]).file_stem().map(|x| x.to_string_lossy().to_string()).unwrap_or("a.out".to_string())
+    });
+
+    let assembly_path = format!("{}.{}", output, arch.file_extension());
+    if let Err(error) = fs::write(&assembly_path, assembly) {
+        exit(format!("Couldn't write \"{}\": {}", assembly_path, error), ExitCode::FileAccess);
+    }
+
+    let mut process = Process::new("cc");
+    process.arg(format!("-O{}", options.optimization_level)).arg("-o").arg(&output).arg(&assembly_path);
+
+    if options.verbosity == Verbosity::Quiet {
+        process.stdout(Stdio::null()).stderr(Stdio::null());
+    }
+
+    log(options, format!("Running {:?}", process));
+
+    let status = match process.status() {
+        Ok(status) => status,
+        Err(error) => exit(format!("Couldn't run cc: {}", error), ExitCode::ExternalTool),
+    };
+
+    // The assembly is only kept around when it might be interesting
+    if options.verbosity != Verbosity::Verbose {
+        _ = fs::remove_file(&assembly_path);
+    }
+
+    if !status.success() {
+        exit(format!("cc failed ({}).", status), ExitCode::ExternalTool);
+    }
+
+    log(options, format!("Built {}", output));
+}
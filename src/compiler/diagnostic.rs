@@ -0,0 +1,22 @@
+use std::fmt::{Display, Formatter};
+use crate::compiler::lexer::span::Span;
+
+/// An error in the code that's being compiled.
+/// Those are collected so a single run can report as many of them as possible.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub span: Span,
+}
+
+impl Diagnostic {
+    pub fn new(message: String, span: Span) -> Diagnostic {
+        Diagnostic { message, span }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.span, self.message)
+    }
+}
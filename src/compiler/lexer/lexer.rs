@@ -0,0 +1,411 @@
+use crate::compiler::diagnostic::Diagnostic;
+use crate::compiler::lexer::span::Span;
+use crate::compiler::lexer::token::{Delimiter, Keyword, Operator, Token, TokenKind};
+
+/// Turns the text of a single .rsl file into tokens
+pub struct Lexer {
+    file: String,
+    characters: Vec<char>,
+    position: usize,        // Index into characters
+    line: usize,
+    column: usize,
+
+    tokens: Vec<Token>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Lexer {
+    pub fn new(file: String, source: &str) -> Lexer {
+        Lexer { file, characters: source.chars().collect(), position: 0, line: 1, column: 1, tokens: Vec::new(), diagnostics: Vec::new() }
+    }
+
+    /// Split the source into tokens.
+    /// Errors don't stop the lexer, the broken part is skipped and reported so all errors of a file can be shown at once.
+    /// The tokens always end with an EndOfFile token.
+    pub fn tokenize(mut self) -> (Vec<Token>, Vec<Diagnostic>) {
+        while let Some(character) = self.peek(0) {
+            let span = self.span();
+
+            if character.is_whitespace() {
+                self.advance();
+                continue;
+            }
+
+            // Comments
+            if character == '/' && self.peek(1) == Some('/') {
+                while self.peek(0).is_some_and(|x| x != '\n') { self.advance(); }
+                continue;
+            }
+
+            if character == '/' && self.peek(1) == Some('*') {
+                self.skip_block_comment(span);
+                continue;
+            }
+
+            if character.is_alphabetic() || character == '_' {
+                self.lex_word(span);
+                continue;
+            }
+
+            if character.is_ascii_digit() {
+                self.lex_number(span);
+                continue;
+            }
+
+            if character == '"' {
+                self.lex_string(span);
+                continue;
+            }
+
+            if character == '\'' {
+                self.lex_char(span);
+                continue;
+            }
+
+            if let Some(delimiter) = Delimiter::ALL.iter().find(|&x| x.character() == character) {
+                self.advance();
+                self.tokens.push(Token::new(TokenKind::Delimiter(*delimiter), span));
+                continue;
+            }
+
+            if let Some(operator) = Operator::ALL.iter().find(|&x| self.starts_with(x.text())) {
+                for _ in 0..operator.text().chars().count() { self.advance(); }
+                self.tokens.push(Token::new(TokenKind::Operator(*operator), span));
+                continue;
+            }
+
+            self.advance();
+            self.diagnostics.push(Diagnostic::new(format!("Unexpected character '{}'.", character), span));
+        }
+
+        let span = self.span();
+        self.tokens.push(Token::new(TokenKind::EndOfFile, span));
+
+        (self.tokens, self.diagnostics)
+    }
+
+    fn span(&self) -> Span {
+        Span::new(self.file.clone(), self.line, self.column)
+    }
+
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.characters.get(self.position + offset).copied()
+    }
+
+    fn starts_with(&self, text: &str) -> bool {
+        text.chars().enumerate().all(|(i, x)| self.peek(i) == Some(x))
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let character = self.peek(0)?;
+        self.position += 1;
+
+        if character == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+
+        Some(character)
+    }
+
+    /// Skip a /* */ comment, which may contain other block comments.
+    fn skip_block_comment(&mut self, span: Span) {
+        // The spans of all the comments that are still open, so an unterminated one can be pointed at
+        let mut open_comments: Vec<Span> = Vec::new();
+
+        while self.peek(0).is_some() {
+            if self.starts_with("/*") {
+                open_comments.push(self.span());
+                self.advance();
+                self.advance();
+                continue;
+            }
+
+            if self.starts_with("*/") {
+                self.advance();
+                self.advance();
+                open_comments.pop();
+
+                if open_comments.is_empty() { return; }
+                continue;
+            }
+
+            self.advance();
+        }
+
+        let span = open_comments.last().cloned().unwrap_or(span);
+        self.diagnostics.push(Diagnostic::new("Unterminated block comment.".to_string(), span));
+    }
+
+    /// Lex an identifier or a keyword
+    fn lex_word(&mut self, span: Span) {
+        let mut word = String::new();
+
+        while let Some(character) = self.peek(0) {
+            if !character.is_alphanumeric() && character != '_' { break; }
+
+            word.push(character);
+            self.advance();
+        }
+
+        let kind = match Keyword::from_text(&word) {
+            Some(keyword) => TokenKind::Keyword(keyword),
+            None => TokenKind::Identifier(word),
+        };
+
+        self.tokens.push(Token::new(kind, span));
+    }
+
+    /// Lex integer literals (decimal, 0x, 0o and 0b) and floating point literals (decimal only).
+    /// Underscores may be used to separate digits.
+    fn lex_number(&mut self, span: Span) {
+        let radix = match (self.peek(0), self.peek(1)) {
+            (Some('0'), Some('x')) => 16,
+            (Some('0'), Some('o')) => 8,
+            (Some('0'), Some('b')) => 2,
+            _ => 10,
+        };
+
+        if radix != 10 {
+            self.advance();
+            self.advance();
+        }
+
+        let mut digits = String::new();
+        let mut is_float = false;
+
+        while let Some(character) = self.peek(0) {
+            if character == '_' {
+                self.advance();
+                continue;
+            }
+
+            // A dot only belongs to the number if a digit follows, otherwise it might be a method call or a range
+            if radix == 10 && character == '.' && !is_float && self.peek(1).is_some_and(|x| x.is_ascii_digit()) {
+                is_float = true;
+                digits.push(character);
+                self.advance();
+                continue;
+            }
+
+            if radix == 10 && (character == 'e' || character == 'E') {
+                let sign_length = if matches!(self.peek(1), Some('+') | Some('-')) { 1 } else { 0 };
+
+                if self.peek(1 + sign_length).is_some_and(|x| x.is_ascii_digit()) {
+                    is_float = true;
+                    for _ in 0..1 + sign_length {
+                        digits.push(self.advance().unwrap());
+                    }
+                    continue;
+                }
+            }
+
+            // Letters are consumed as well, so e.g. "12ab" is reported as a single bad literal
+            if !character.is_ascii_alphanumeric() { break; }
+
+            digits.push(character);
+            self.advance();
+        }
+
+        let kind = if is_float {
+            match digits.parse::<f64>() {
+                Ok(value) => TokenKind::FloatLiteral(value),
+                Err(_) => {
+                    self.diagnostics.push(Diagnostic::new(format!("Invalid floating point literal \"{}\".", digits), span));
+                    return;
+                }
+            }
+        } else {
+            match u64::from_str_radix(&digits, radix) {
+                Ok(value) => TokenKind::IntegerLiteral(value),
+                Err(error) => {
+                    let message = match error.kind() {
+                        std::num::IntErrorKind::PosOverflow => format!("Integer literal \"{}\" doesn't fit in 64 bits.", digits),
+                        _ => format!("Invalid integer literal \"{}\".", digits),
+                    };
+                    self.diagnostics.push(Diagnostic::new(message, span));
+                    return;
+                }
+            }
+        };
+
+        self.tokens.push(Token::new(kind, span));
+    }
+
+    fn lex_string(&mut self, span: Span) {
+        // Skip the opening quote
+        self.advance();
+
+        let mut text = String::new();
+
+        loop {
+            match self.peek(0) {
+                None | Some('\n') => {
+                    self.diagnostics.push(Diagnostic::new("Unterminated string literal.".to_string(), span));
+                    return;
+                }
+                Some('"') => {
+                    self.advance();
+                    break;
+                }
+                Some('\\') => {
+                    if let Some(character) = self.lex_escape_sequence() {
+                        text.push(character);
+                    }
+                }
+                Some(character) => {
+                    text.push(character);
+                    self.advance();
+                }
+            }
+        }
+
+        self.tokens.push(Token::new(TokenKind::StringLiteral(text), span));
+    }
+
+    fn lex_char(&mut self, span: Span) {
+        // Skip the opening quote
+        self.advance();
+
+        let character = match self.peek(0) {
+            None | Some('\n') | Some('\'') => {
+                self.diagnostics.push(Diagnostic::new("Empty or unterminated character literal.".to_string(), span));
+                if self.peek(0) == Some('\'') { self.advance(); }
+                return;
+            }
+            Some('\\') => self.lex_escape_sequence(),
+            Some(character) => {
+                self.advance();
+                Some(character)
+            }
+        };
+
+        if self.peek(0) != Some('\'') {
+            self.diagnostics.push(Diagnostic::new("Character literals must contain exactly one character.".to_string(), span));
+
+            // Skip the rest of the broken literal
+            while self.peek(0).is_some_and(|x| x != '\'' && x != '\n') { self.advance(); }
+            if self.peek(0) == Some('\'') { self.advance(); }
+            return;
+        }
+        self.advance();
+
+        if let Some(character) = character {
+            self.tokens.push(Token::new(TokenKind::CharLiteral(character), span));
+        }
+    }
+
+    /// Lex a backslash and whatever follows it, returns None (and reports it) if the sequence is invalid
+    fn lex_escape_sequence(&mut self) -> Option<char> {
+        let span = self.span();
+
+        // Skip the backslash
+        self.advance();
+
+        let character = self.advance();
+
+        let escaped = match character {
+            Some('n') => '\n',
+            Some('t') => '\t',
+            Some('r') => '\r',
+            Some('0') => '\0',
+            Some('\\') => '\\',
+            Some('"') => '"',
+            Some('\'') => '\'',
+            Some('x') => {
+                let mut digits = String::new();
+                while digits.len() < 2 && self.peek(0).is_some_and(|x| x.is_ascii_hexdigit()) {
+                    digits.push(self.advance().unwrap());
+                }
+
+                match u8::from_str_radix(&digits, 16) {
+                    Ok(value) if digits.len() == 2 && value < 0x80 => value as char,
+                    _ => {
+                        self.diagnostics.push(Diagnostic::new("\\x must be followed by two hex digits (at most 7f).".to_string(), span));
+                        return None;
+                    }
+                }
+            }
+            _ => {
+                let text = character.map(|x| x.to_string()).unwrap_or_default();
+                self.diagnostics.push(Diagnostic::new(format!("Unknown escape sequence \"\\{}\".", text), span));
+                return None;
+            }
+        };
+
+        Some(escaped)
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use crate::compiler::lexer::lexer::Lexer;
+    use crate::compiler::lexer::span::Span;
+    use crate::compiler::lexer::token::{Delimiter, Keyword, Operator, TokenKind};
+
+    fn kinds(source: &str) -> Vec<TokenKind> {
+        let (tokens, diagnostics) = Lexer::new("main.rsl".to_string(), source).tokenize();
+        assert_eq!(diagnostics, vec![]);
+
+        tokens.into_iter().map(|x| x.kind).collect()
+    }
+
+    #[test]
+    fn test_tokenize(){
+        assert_eq!(kinds("var myVar: Int = 0x1F + 2.5e3; // the rest is ignored"), vec![
+            TokenKind::Keyword(Keyword::Var),
+            TokenKind::Identifier("myVar".to_string()),
+            TokenKind::Delimiter(Delimiter::Colon),
+            TokenKind::Identifier("Int".to_string()),
+            TokenKind::Operator(Operator::Assign),
+            TokenKind::IntegerLiteral(31),
+            TokenKind::Operator(Operator::Plus),
+            TokenKind::FloatLiteral(2500.0),
+            TokenKind::Delimiter(Delimiter::Semicolon),
+            TokenKind::EndOfFile,
+        ]);
+
+        assert_eq!(kinds("a <<= b->c...'\\n'\"say \\\"hi\\\"\\x21\""), vec![
+            TokenKind::Identifier("a".to_string()),
+            TokenKind::Operator(Operator::ShiftLeftAssign),
+            TokenKind::Identifier("b".to_string()),
+            TokenKind::Operator(Operator::Arrow),
+            TokenKind::Identifier("c".to_string()),
+            TokenKind::Operator(Operator::Ellipsis),
+            TokenKind::CharLiteral('\n'),
+            TokenKind::StringLiteral("say \"hi\"!".to_string()),
+            TokenKind::EndOfFile,
+        ]);
+    }
+
+    #[test]
+    fn test_nested_block_comments_and_spans(){
+        let (tokens, diagnostics) = Lexer::new("main.rsl".to_string(), "/* outer /* inner */ still outer */\n  loop1").tokenize();
+
+        assert_eq!(diagnostics, vec![]);
+        assert_eq!(tokens[0].kind, TokenKind::Identifier("loop1".to_string()));
+        assert_eq!(tokens[0].span, Span::new("main.rsl".to_string(), 2, 3));
+    }
+
+    #[test]
+    fn test_errors_are_collected(){
+        let (tokens, diagnostics) = Lexer::new("main.rsl".to_string(), "a $ 99999999999999999999\n\"open\nb /* /* */").tokenize();
+
+        let messages: Vec<String> = diagnostics.iter().map(|x| x.to_string()).collect();
+        assert_eq!(messages, vec![
+            "main.rsl:1:3: Unexpected character '$'.".to_string(),
+            "main.rsl:1:5: Integer literal \"99999999999999999999\" doesn't fit in 64 bits.".to_string(),
+            "main.rsl:2:1: Unterminated string literal.".to_string(),
+            "main.rsl:3:3: Unterminated block comment.".to_string(),
+        ]);
+
+        // Lexing continues after errors
+        assert_eq!(tokens.iter().map(|x| x.kind.clone()).collect::<Vec<_>>(), vec![
+            TokenKind::Identifier("a".to_string()),
+            TokenKind::Identifier("b".to_string()),
+            TokenKind::EndOfFile,
+        ]);
+    }
+}
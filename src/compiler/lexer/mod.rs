@@ -0,0 +1,4 @@
+#[allow(clippy::module_inception)]
+pub mod lexer;
+pub mod token;
+pub mod span;
\ No newline at end of file
@@ -0,0 +1,21 @@
+use std::fmt::{Display, Formatter};
+
+/// The place in a source file something (usually a token) starts at
+#[derive(Clone, Debug, PartialEq)]
+pub struct Span {
+    pub file: String,       // The path of the file as it's been passed to the compiler (e.g. main.rsl)
+    pub line: usize,        // Starts at 1
+    pub column: usize,      // Starts at 1, counted in characters (not bytes)
+}
+
+impl Span {
+    pub fn new(file: String, line: usize, column: usize) -> Span {
+        Span { file, line, column }
+    }
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
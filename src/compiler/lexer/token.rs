@@ -0,0 +1,231 @@
+use std::fmt::{Display, Formatter};
+use crate::compiler::lexer::span::Span;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Span,         // Where the token starts
+}
+
+impl Token {
+    pub fn new(kind: TokenKind, span: Span) -> Token {
+        Token { kind, span }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TokenKind {
+    Identifier(String),
+    Keyword(Keyword),
+
+    IntegerLiteral(u64),
+    FloatLiteral(f64),
+    StringLiteral(String),      // Escape sequences are resolved already
+    CharLiteral(char),
+
+    Operator(Operator),
+    Delimiter(Delimiter),
+
+    EndOfFile,
+}
+
+impl Display for TokenKind {
+    /// Describe the token for error messages
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenKind::Identifier(name) => write!(f, "identifier \"{}\"", name),
+            TokenKind::Keyword(keyword) => write!(f, "\"{}\"", keyword.text()),
+            TokenKind::IntegerLiteral(value) => write!(f, "integer literal {}", value),
+            TokenKind::FloatLiteral(value) => write!(f, "floating point literal {}", value),
+            TokenKind::StringLiteral(_) => write!(f, "string literal"),
+            TokenKind::CharLiteral(_) => write!(f, "character literal"),
+            TokenKind::Operator(operator) => write!(f, "\"{}\"", operator.text()),
+            TokenKind::Delimiter(delimiter) => write!(f, "\"{}\"", delimiter.character()),
+            TokenKind::EndOfFile => write!(f, "end of file"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Keyword {
+    Module,
+    Import,
+    Type,
+    Fun,
+    Extern,
+    Var,
+    Let,
+    If,
+    Else,
+    Loop,
+    While,
+    Break,
+    Continue,
+    Return,
+    True,
+    False,
+    As,
+}
+
+impl Keyword {
+    pub const ALL: [Keyword; 17] = [
+        Keyword::Module, Keyword::Import, Keyword::Type, Keyword::Fun, Keyword::Extern, Keyword::Var, Keyword::Let,
+        Keyword::If, Keyword::Else, Keyword::Loop, Keyword::While, Keyword::Break, Keyword::Continue, Keyword::Return,
+        Keyword::True, Keyword::False, Keyword::As,
+    ];
+
+    /// The way the keyword is spelled in the source code
+    pub fn text(&self) -> &'static str {
+        match self {
+            Keyword::Module => "module",
+            Keyword::Import => "import",
+            Keyword::Type => "type",
+            Keyword::Fun => "fun",
+            Keyword::Extern => "extern",
+            Keyword::Var => "var",
+            Keyword::Let => "let",
+            Keyword::If => "if",
+            Keyword::Else => "else",
+            Keyword::Loop => "loop",
+            Keyword::While => "while",
+            Keyword::Break => "break",
+            Keyword::Continue => "continue",
+            Keyword::Return => "return",
+            Keyword::True => "true",
+            Keyword::False => "false",
+            Keyword::As => "as",
+        }
+    }
+
+    pub fn from_text(text: &str) -> Option<Keyword> {
+        Self::ALL.iter().find(|&x| x.text() == text).copied()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operator {
+    Plus,                   // +
+    Minus,                  // -
+    Star,                   // *
+    Slash,                  // /
+    Percent,                // %
+    Ampersand,              // &
+    Pipe,                   // |
+    Caret,                  // ^
+    Tilde,                  // ~
+    Exclamation,            // !
+    ShiftLeft,              // <<
+    ShiftRight,             // >>
+    AndAnd,                 // &&
+    OrOr,                   // ||
+
+    Equal,                  // ==
+    NotEqual,               // !=
+    Less,                   // <
+    LessEqual,              // <=
+    Greater,                // >
+    GreaterEqual,           // >=
+
+    Assign,                 // =
+    PlusAssign,             // +=
+    MinusAssign,            // -=
+    StarAssign,             // *=
+    SlashAssign,            // /=
+    PercentAssign,          // %=
+    AmpersandAssign,        // &=
+    PipeAssign,             // |=
+    CaretAssign,            // ^=
+    ShiftLeftAssign,        // <<=
+    ShiftRightAssign,       // >>=
+
+    Arrow,                  // ->
+    Dot,                    // .
+    Ellipsis,               // ...
+}
+
+impl Operator {
+    /// All operators, longer ones first so the lexer always matches as much as possible
+    pub const ALL: [Operator; 34] = [
+        Operator::Ellipsis, Operator::ShiftLeftAssign, Operator::ShiftRightAssign,
+        Operator::ShiftLeft, Operator::ShiftRight, Operator::AndAnd, Operator::OrOr,
+        Operator::Equal, Operator::NotEqual, Operator::LessEqual, Operator::GreaterEqual,
+        Operator::PlusAssign, Operator::MinusAssign, Operator::StarAssign, Operator::SlashAssign, Operator::PercentAssign,
+        Operator::AmpersandAssign, Operator::PipeAssign, Operator::CaretAssign, Operator::Arrow,
+        Operator::Plus, Operator::Minus, Operator::Star, Operator::Slash, Operator::Percent,
+        Operator::Ampersand, Operator::Pipe, Operator::Caret, Operator::Tilde, Operator::Exclamation,
+        Operator::Less, Operator::Greater, Operator::Assign, Operator::Dot,
+    ];
+
+    pub fn text(&self) -> &'static str {
+        match self {
+            Operator::Plus => "+",
+            Operator::Minus => "-",
+            Operator::Star => "*",
+            Operator::Slash => "/",
+            Operator::Percent => "%",
+            Operator::Ampersand => "&",
+            Operator::Pipe => "|",
+            Operator::Caret => "^",
+            Operator::Tilde => "~",
+            Operator::Exclamation => "!",
+            Operator::ShiftLeft => "<<",
+            Operator::ShiftRight => ">>",
+            Operator::AndAnd => "&&",
+            Operator::OrOr => "||",
+            Operator::Equal => "==",
+            Operator::NotEqual => "!=",
+            Operator::Less => "<",
+            Operator::LessEqual => "<=",
+            Operator::Greater => ">",
+            Operator::GreaterEqual => ">=",
+            Operator::Assign => "=",
+            Operator::PlusAssign => "+=",
+            Operator::MinusAssign => "-=",
+            Operator::StarAssign => "*=",
+            Operator::SlashAssign => "/=",
+            Operator::PercentAssign => "%=",
+            Operator::AmpersandAssign => "&=",
+            Operator::PipeAssign => "|=",
+            Operator::CaretAssign => "^=",
+            Operator::ShiftLeftAssign => "<<=",
+            Operator::ShiftRightAssign => ">>=",
+            Operator::Arrow => "->",
+            Operator::Dot => ".",
+            Operator::Ellipsis => "...",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Delimiter {
+    OpenParenthesis,        // (
+    CloseParenthesis,       // )
+    OpenBrace,              // {
+    CloseBrace,             // }
+    OpenBracket,            // [
+    CloseBracket,           // ]
+    Comma,                  // ,
+    Semicolon,              // ;
+    Colon,                  // :
+}
+
+impl Delimiter {
+    pub const ALL: [Delimiter; 9] = [
+        Delimiter::OpenParenthesis, Delimiter::CloseParenthesis, Delimiter::OpenBrace, Delimiter::CloseBrace,
+        Delimiter::OpenBracket, Delimiter::CloseBracket, Delimiter::Comma, Delimiter::Semicolon, Delimiter::Colon,
+    ];
+
+    pub fn character(&self) -> char {
+        match self {
+            Delimiter::OpenParenthesis => '(',
+            Delimiter::CloseParenthesis => ')',
+            Delimiter::OpenBrace => '{',
+            Delimiter::CloseBrace => '}',
+            Delimiter::OpenBracket => '[',
+            Delimiter::CloseBracket => ']',
+            Delimiter::Comma => ',',
+            Delimiter::Semicolon => ';',
+            Delimiter::Colon => ':',
+        }
+    }
+}
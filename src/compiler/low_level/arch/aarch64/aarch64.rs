@@ -0,0 +1,790 @@
+use std::string::ToString;
+use crate::compiler::low_level::arch::arch::{Arch, NativeArch, RegisterOrImmediate};
+use crate::compiler::low_level::arch::function_gen::{allocate_stack_slot, generate_constants, generate_function};
+use crate::compiler::low_level::macro_function::MacroFunction;
+use crate::compiler::low_level::operation::{Operation, UnaryOperation};
+use crate::compiler::low_level::arch::register::*;
+use crate::compiler::low_level::variable::ValueType;
+use crate::util::exit::{exit, ExitCode};
+
+/// The operating systems aarch64 code can be generated for.
+/// They share the instruction set and (mostly) the calling convention (AAPCS64),
+/// everything that differs between them is answered here.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum AArch64Os {
+    MacOs,
+    Linux,
+}
+
+impl AArch64Os {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AArch64Os::MacOs => "aarch64-mac-os",
+            AArch64Os::Linux => "aarch64-linux",
+        }
+    }
+
+    /// Mach-O symbols of C functions start with an underscore, ELF symbols don't
+    pub fn symbol_prefix(&self) -> &'static str {
+        match self {
+            AArch64Os::MacOs => "_",
+            AArch64Os::Linux => "",
+        }
+    }
+
+    /// Labels starting with these characters don't end up in the symbol table
+    pub fn local_label_prefix(&self) -> &'static str {
+        match self {
+            AArch64Os::MacOs => "L",
+            AArch64Os::Linux => ".L",
+        }
+    }
+
+    /// Whether x18 (the platform register) can be used like any other temporary register.
+    /// Apple reserves it, so it must never be touched there.
+    pub fn platform_register_usable(&self) -> bool {
+        match self {
+            AArch64Os::MacOs => false,
+            AArch64Os::Linux => true,
+        }
+    }
+}
+
+pub struct AArch64 {
+    pub os: AArch64Os,
+    pub registers: Vec<Register>
+}
+
+impl AArch64 {
+    pub const BITS_COUNT: u8 = 64;
+
+    /// The register that's used whenever an instruction needs an extra register
+    const SCRATCH_REGISTER: &'static str = "x8";
+
+    pub fn new(os: AArch64Os) -> Self {
+        let platform_register = match os.platform_register_usable() {
+            true => Register::new("x18".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::GeneralPurpose]),
+            false => Register::new("x18".to_string(), 64, RegisterSaver::OS, vec![RegisterTag::NoModify]),
+        };
+
+        AArch64 {
+            os,
+            registers: vec![
+                Register::new("x0".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::Argument(0), RegisterTag::ReturnValue, RegisterTag::GeneralPurpose]),
+                Register::new("x1".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::Argument(1), RegisterTag::GeneralPurpose]),
+                Register::new("x2".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::Argument(2), RegisterTag::GeneralPurpose]),
+                Register::new("x3".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::Argument(3), RegisterTag::GeneralPurpose]),
+                Register::new("x4".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::Argument(4), RegisterTag::GeneralPurpose]),
+                Register::new("x5".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::Argument(5), RegisterTag::GeneralPurpose]),
+                Register::new("x6".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::Argument(6), RegisterTag::GeneralPurpose]),
+                Register::new("x7".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::Argument(7), RegisterTag::GeneralPurpose]),
+                Register::new("x8".to_string(), 64, RegisterSaver::None, vec![RegisterTag::Scratch]),
+                Register::new("x9".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::GeneralPurpose]),
+                Register::new("x10".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::GeneralPurpose]),
+                Register::new("x11".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::GeneralPurpose]),
+                Register::new("x12".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::GeneralPurpose]),
+                Register::new("x13".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::GeneralPurpose]),
+                Register::new("x14".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::GeneralPurpose]),
+                Register::new("x15".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::GeneralPurpose]),
+                Register::new("x16".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::GeneralPurpose]),
+                Register::new("x17".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::GeneralPurpose]),
+                platform_register,
+                Register::new("x19".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]),
+                Register::new("x20".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]),
+                Register::new("x21".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]),
+                Register::new("x22".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]),
+                Register::new("x23".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]),
+                Register::new("x24".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]),
+                Register::new("x25".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]),
+                Register::new("x26".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]),
+                Register::new("x27".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]),
+                Register::new("x28".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]),
+                Register::new("x29".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::FramePointer]),
+                Register::new("x30".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::LinkRegister]),
+                Register::new("sp".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::StackPointer]),
+                // The floating point and SIMD registers, they're named by the size that's accessed (d0 or s0 for v0)
+                Register::new("v0".to_string(), 128, RegisterSaver::Caller, vec![RegisterTag::Argument(0), RegisterTag::ReturnValue, RegisterTag::GeneralPurpose]).with_class(RegisterClass::Float),
+                Register::new("v1".to_string(), 128, RegisterSaver::Caller, vec![RegisterTag::Argument(1), RegisterTag::GeneralPurpose]).with_class(RegisterClass::Float),
+                Register::new("v2".to_string(), 128, RegisterSaver::Caller, vec![RegisterTag::Argument(2), RegisterTag::GeneralPurpose]).with_class(RegisterClass::Float),
+                Register::new("v3".to_string(), 128, RegisterSaver::Caller, vec![RegisterTag::Argument(3), RegisterTag::GeneralPurpose]).with_class(RegisterClass::Float),
+                Register::new("v4".to_string(), 128, RegisterSaver::Caller, vec![RegisterTag::Argument(4), RegisterTag::GeneralPurpose]).with_class(RegisterClass::Float),
+                Register::new("v5".to_string(), 128, RegisterSaver::Caller, vec![RegisterTag::Argument(5), RegisterTag::GeneralPurpose]).with_class(RegisterClass::Float),
+                Register::new("v6".to_string(), 128, RegisterSaver::Caller, vec![RegisterTag::Argument(6), RegisterTag::GeneralPurpose]).with_class(RegisterClass::Float),
+                Register::new("v7".to_string(), 128, RegisterSaver::Caller, vec![RegisterTag::Argument(7), RegisterTag::GeneralPurpose]).with_class(RegisterClass::Float),
+                // Only the lower 64 bits (d8-d15) are callee-saved, which is all that's ever kept in them
+                Register::new("v8".to_string(), 128, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]).with_class(RegisterClass::Float),
+                Register::new("v9".to_string(), 128, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]).with_class(RegisterClass::Float),
+                Register::new("v10".to_string(), 128, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]).with_class(RegisterClass::Float),
+                Register::new("v11".to_string(), 128, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]).with_class(RegisterClass::Float),
+                Register::new("v12".to_string(), 128, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]).with_class(RegisterClass::Float),
+                Register::new("v13".to_string(), 128, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]).with_class(RegisterClass::Float),
+                Register::new("v14".to_string(), 128, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]).with_class(RegisterClass::Float),
+                Register::new("v15".to_string(), 128, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]).with_class(RegisterClass::Float),
+                Register::new("v16".to_string(), 128, RegisterSaver::Caller, vec![RegisterTag::GeneralPurpose]).with_class(RegisterClass::Float),
+                Register::new("v17".to_string(), 128, RegisterSaver::Caller, vec![RegisterTag::GeneralPurpose]).with_class(RegisterClass::Float),
+                Register::new("v18".to_string(), 128, RegisterSaver::Caller, vec![RegisterTag::GeneralPurpose]).with_class(RegisterClass::Float),
+                Register::new("v19".to_string(), 128, RegisterSaver::Caller, vec![RegisterTag::GeneralPurpose]).with_class(RegisterClass::Float),
+                Register::new("v20".to_string(), 128, RegisterSaver::Caller, vec![RegisterTag::GeneralPurpose]).with_class(RegisterClass::Float),
+                Register::new("v21".to_string(), 128, RegisterSaver::Caller, vec![RegisterTag::GeneralPurpose]).with_class(RegisterClass::Float),
+                Register::new("v22".to_string(), 128, RegisterSaver::Caller, vec![RegisterTag::GeneralPurpose]).with_class(RegisterClass::Float),
+                Register::new("v23".to_string(), 128, RegisterSaver::Caller, vec![RegisterTag::GeneralPurpose]).with_class(RegisterClass::Float),
+                Register::new("v24".to_string(), 128, RegisterSaver::Caller, vec![RegisterTag::GeneralPurpose]).with_class(RegisterClass::Float),
+                Register::new("v25".to_string(), 128, RegisterSaver::Caller, vec![RegisterTag::GeneralPurpose]).with_class(RegisterClass::Float),
+                Register::new("v26".to_string(), 128, RegisterSaver::Caller, vec![RegisterTag::GeneralPurpose]).with_class(RegisterClass::Float),
+                Register::new("v27".to_string(), 128, RegisterSaver::Caller, vec![RegisterTag::GeneralPurpose]).with_class(RegisterClass::Float),
+                Register::new("v28".to_string(), 128, RegisterSaver::Caller, vec![RegisterTag::GeneralPurpose]).with_class(RegisterClass::Float),
+                Register::new("v29".to_string(), 128, RegisterSaver::Caller, vec![RegisterTag::GeneralPurpose]).with_class(RegisterClass::Float),
+                Register::new("v30".to_string(), 128, RegisterSaver::Caller, vec![RegisterTag::GeneralPurpose]).with_class(RegisterClass::Float),
+                Register::new("v31".to_string(), 128, RegisterSaver::Caller, vec![RegisterTag::GeneralPurpose]).with_class(RegisterClass::Float)
+            ]
+        }
+    }
+
+    /// Generate the code that moves the stack pointer by the given amount of bytes (with "sub" or "add").
+    /// Immediates only have 12 bits, so larger amounts are split into a part that's shifted by 12 bits and the rest.
+    fn adjust_stack_pointer(instruction: &str, bytes: usize) -> String {
+        if bytes >= 1 << 24 {
+            exit(format!("The stack frame is {}B large, which is more than the supported 16MiB.", bytes), ExitCode::Internal);
+        }
+
+        let mut code = String::new();
+
+        if bytes >> 12 > 0 {
+            code += format!("{}\tsp, sp, #{}, lsl #12\n", instruction, bytes >> 12).as_str();
+        }
+
+        if bytes & 0xfff > 0 {
+            code += format!("{}\tsp, sp, #{}\n", instruction, bytes & 0xfff).as_str();
+        }
+
+        code
+    }
+
+    /// The 32-bit register (w0) that's the lower half of the 64-bit one (x0)
+    fn lower_half(register: &str) -> String {
+        match register.strip_prefix('x') {
+            Some(number) => format!("w{}", number),
+            None => exit(format!("Register {} doesn't have a lower half.", register), ExitCode::Internal),
+        }
+    }
+
+    /// Whether the register is one of the floating point (and SIMD) registers
+    fn is_float_register(register: &str) -> bool {
+        register.starts_with('v')
+    }
+
+    /// The name of the part of the floating point register that holds a value of the given size (d0 for the lower 64 bits of v0)
+    fn float_part(register: &str, bits: u8) -> String {
+        let prefix = match bits {
+            8 => 'b',
+            16 => 'h',
+            32 => 's',
+            _ => 'd',
+        };
+
+        format!("{}{}", prefix, &register[1..])
+    }
+
+    /// A load or store of a value of the type at base + offset.
+    /// The scaled instructions (like ldr) take offsets that are multiples of the size, the unscaled ones (like ldur) small unaligned ones,
+    /// every other offset is put into the scratch register first.
+    fn memory_access(&self, is_load: bool, register: &str, base: &str, offset: i64, value_type: &ValueType) -> String {
+        let bytes = value_type.bytes(self);
+        let stores_scratch = !is_load && register == Self::SCRATCH_REGISTER;
+
+        // Loads of signed values extend them to the whole (64-bit) register, everything else only needs the 32-bit register then
+        let (instruction, unscaled_instruction, register) = match (is_load, bytes, value_type.is_signed()) {
+            // Floating point registers are accessed through their part of the right size, there's nothing to extend
+            _ if Self::is_float_register(register) => (if is_load { "ldr" } else { "str" }, if is_load { "ldur" } else { "stur" }, Self::float_part(register, bytes as u8 * 8)),
+            (_, 8, _) => (if is_load { "ldr" } else { "str" }, if is_load { "ldur" } else { "stur" }, register.to_string()),
+            (true, 1, true) => ("ldrsb", "ldursb", register.to_string()),
+            (true, 2, true) => ("ldrsh", "ldursh", register.to_string()),
+            (true, _, true) => ("ldrsw", "ldursw", register.to_string()),
+            (true, 1, false) => ("ldrb", "ldurb", Self::lower_half(register)),
+            (true, 2, false) => ("ldrh", "ldurh", Self::lower_half(register)),
+            (true, _, false) => ("ldr", "ldur", Self::lower_half(register)),
+            (false, 1, _) => ("strb", "sturb", Self::lower_half(register)),
+            (false, 2, _) => ("strh", "sturh", Self::lower_half(register)),
+            (false, _, _) => ("str", "stur", Self::lower_half(register)),
+        };
+
+        if offset % bytes as i64 == 0 && (0..=4095 * bytes as i64).contains(&offset) {
+            return format!("{}\t{}, [{}, #{}]\n", instruction, register, base, offset);
+        }
+
+        if (-256..256).contains(&offset) {
+            return format!("{}\t{}, [{}, #{}]\n", unscaled_instruction, register, base, offset);
+        }
+
+        if base == Self::SCRATCH_REGISTER {
+            exit(format!("The offset {} can't be used with the scratch register as the base.", offset), ExitCode::Internal);
+        }
+
+        if stores_scratch {
+            exit(format!("The scratch register can't be stored at the offset {}, as it's needed to find the position.", offset), ExitCode::Internal);
+        }
+
+        self.load_immediate(Self::SCRATCH_REGISTER, offset) + format!("{}\t{}, [{}, {}]\n", instruction, register, base, Self::SCRATCH_REGISTER).as_str()
+    }
+
+    /// Store (stp) or load (ldp) two whole registers of the same class at the offset from the stack pointer
+    fn register_pair_access(&self, instruction: &str, first: &str, second: &str, stack_offset: usize) -> String {
+        if Self::is_float_register(first) != Self::is_float_register(second) {
+            exit(format!("Registers {} and {} can't be accessed as a pair, as they're of different classes.", first, second), ExitCode::Internal);
+        }
+
+        // The offset of a pair only has 7 bits (in 8B steps)
+        if stack_offset > 504 {
+            let is_load = instruction == "ldp";
+            return self.memory_access(is_load, first, "sp", stack_offset as i64, &ValueType::default()) + self.memory_access(is_load, second, "sp", stack_offset as i64 + 8, &ValueType::default()).as_str();
+        }
+
+        match Self::is_float_register(first) {
+            true => format!("{}\t{}, {}, [sp, #{}]\n", instruction, Self::float_part(first, 64), Self::float_part(second, 64), stack_offset),
+            false => format!("{}\t{}, {}, [sp, #{}]\n", instruction, first, second, stack_offset),
+        }
+    }
+}
+
+impl Arch for AArch64 {
+    fn name(&self) -> String {
+        self.os.name().to_string()
+    }
+
+    fn architecture_bits(&self) -> u8 {
+        Self::BITS_COUNT
+    }
+
+    fn generate_assembly(&self, functions: Vec<MacroFunction>) -> String {
+        let mut assembly = match self.os {
+            AArch64Os::MacOs => ".section\t__TEXT,__text,regular,pure_instructions\n".to_string(),
+            AArch64Os::Linux => ".text\n".to_string(),
+        };
+
+        let mut constants: Vec<Vec<u8>> = Vec::new();
+
+        for function in functions {
+            assembly += generate_function(self, &function.name, function.instructions, &mut constants).as_str();
+        }
+
+        assembly += generate_constants(self, &constants).as_str();
+
+        if self.os == AArch64Os::Linux {
+            // Tell the linker the stack doesn't need to be executable
+            assembly += ".section\t.note.GNU-stack,\"\",%progbits\n";
+        }
+
+        assembly
+    }
+}
+
+impl NativeArch for AArch64 {
+    fn registers(&self) -> Vec<Register> {
+        self.registers.clone()
+    }
+
+    fn symbol(&self, function_name: &str) -> String {
+        format!("{}{}", self.os.symbol_prefix(), function_name)
+    }
+
+    fn function_header(&self, symbol: &str) -> String {
+        match self.os {
+            AArch64Os::MacOs => format!(".globl\t{}\n.p2align\t2\n", symbol),
+            AArch64Os::Linux => format!(".globl\t{}\n.p2align\t2\n.type\t{}, %function\n", symbol, symbol),
+        }
+    }
+
+    fn prologue(&self, frame_size: usize) -> String {
+        let mut code = "stp\tx29, x30, [sp, #-16]!\n".to_string();
+        code += "mov\tx29, sp\n";
+
+        code += Self::adjust_stack_pointer("sub", frame_size).as_str();
+
+        code
+    }
+
+    fn epilogue(&self, frame_size: usize) -> String {
+        let mut code = Self::adjust_stack_pointer("add", frame_size);
+
+        code += "ldp\tx29, x30, [sp], #16\n";
+        code += "ret\n";
+
+        code
+    }
+
+    fn return_without_frame(&self) -> String {
+        "ret\n".to_string()
+    }
+
+    fn incoming_arguments_offset(&self) -> i64 {
+        // The frame pointer points at the saved frame pointer and link register
+        16
+    }
+
+    fn stack_argument_slot(&self, offset: &mut usize, value_type: &ValueType) -> usize {
+        // Apple packs the arguments on the stack, each one only takes up (and is aligned to) its own size
+        match self.os {
+            AArch64Os::MacOs => allocate_stack_slot(offset, value_type.bytes(self)),
+            AArch64Os::Linux => allocate_stack_slot(offset, 8),
+        }
+    }
+
+    fn variadic_arguments_on_stack(&self) -> bool {
+        // Apple's ABI puts them on the stack, so va_arg never has to look at registers
+        self.os == AArch64Os::MacOs
+    }
+
+    fn call(&self, symbol: &str) -> String {
+        format!("bl\t{}\n", symbol)
+    }
+
+    fn local_label(&self, name: &str) -> String {
+        format!("{}{}", self.os.local_label_prefix(), name)
+    }
+
+    fn jump(&self, label: &str) -> String {
+        format!("b\t{}\n", label)
+    }
+
+    fn branch_if_not_zero(&self, register: &str, label: &str) -> String {
+        format!("cbnz\t{}, {}\n", register, label)
+    }
+
+    fn move_register(&self, destination: &str, source: &str) -> String {
+        // Floating point registers are moved as a whole (their lower 64 bits), also from and to integer registers (bit by bit)
+        match (Self::is_float_register(destination), Self::is_float_register(source)) {
+            (false, false) => format!("mov\t{}, {}\n", destination, source),
+            (true, true) => format!("fmov\t{}, {}\n", Self::float_part(destination, 64), Self::float_part(source, 64)),
+            (true, false) => format!("fmov\t{}, {}\n", Self::float_part(destination, 64), source),
+            (false, true) => format!("fmov\t{}, {}\n", destination, Self::float_part(source, 64)),
+        }
+    }
+
+    fn store_register(&self, register: &str, stack_offset: usize, value_type: &ValueType) -> String {
+        self.memory_access(false, register, "sp", stack_offset as i64, value_type)
+    }
+
+    fn store_register_pair(&self, first: &str, second: &str, stack_offset: usize) -> String {
+        self.register_pair_access("stp", first, second, stack_offset)
+    }
+
+    fn load_register(&self, register: &str, stack_offset: usize, value_type: &ValueType) -> String {
+        self.memory_access(true, register, "sp", stack_offset as i64, value_type)
+    }
+
+    fn load_register_pair(&self, first: &str, second: &str, stack_offset: usize) -> String {
+        self.register_pair_access("ldp", first, second, stack_offset)
+    }
+
+    fn load_indirect(&self, register: &str, base: &str, offset: i64, value_type: &ValueType) -> String {
+        self.memory_access(true, register, base, offset, value_type)
+    }
+
+    fn store_indirect(&self, register: &str, base: &str, offset: i64, value_type: &ValueType) -> String {
+        self.memory_access(false, register, base, offset, value_type)
+    }
+
+    fn extend(&self, register: &str, value_type: &ValueType) -> String {
+        // Floating point values always fill their part of the register
+        if Self::is_float_register(register) {
+            return String::new();
+        }
+
+        let lower_half = Self::lower_half(register);
+
+        // Writing to the 32-bit register clears the upper half of the 64-bit one
+        match (value_type.bytes(self), value_type.is_signed()) {
+            (8, _) => String::new(),
+            (1, true) => format!("sxtb\t{}, {}\n", register, lower_half),
+            (2, true) => format!("sxth\t{}, {}\n", register, lower_half),
+            (_, true) => format!("sxtw\t{}, {}\n", register, lower_half),
+            (1, false) => format!("uxtb\t{}, {}\n", lower_half, lower_half),
+            (2, false) => format!("uxth\t{}, {}\n", lower_half, lower_half),
+            (_, false) => format!("mov\t{}, {}\n", lower_half, lower_half),
+        }
+    }
+
+    fn stack_address(&self, destination: &str, offset: &RegisterOrImmediate) -> String {
+        match offset {
+            RegisterOrImmediate::Register(register) => format!("add\t{}, sp, {}\n", destination, register),
+            RegisterOrImmediate::Immediate(value) if (0..4096).contains(value) => format!("add\t{}, sp, #{}\n", destination, value),
+            RegisterOrImmediate::Immediate(value) => self.load_immediate(Self::SCRATCH_REGISTER, *value) + format!("add\t{}, sp, {}\n", destination, Self::SCRATCH_REGISTER).as_str(),
+        }
+    }
+
+    fn load_immediate(&self, register: &str, value: i64) -> String {
+        let chunks: Vec<u64> = (0..4).map(|i| (value as u64 >> (16 * i)) & 0xffff).collect();
+
+        // Start with movn if most of the chunks are all ones (e.g. for small negative values), as movn sets the others to ones
+        let inverted = chunks.iter().filter(|&&x| x == 0xffff).count() > chunks.iter().filter(|&&x| x == 0).count();
+        let filler = if inverted { 0xffff } else { 0 };
+
+        let mut code = String::new();
+
+        for (i, chunk) in chunks.iter().enumerate() {
+            if *chunk == filler { continue; }
+
+            if code.is_empty() {
+                let (instruction, chunk) = if inverted { ("movn", !chunk & 0xffff) } else { ("movz", *chunk) };
+                code += format!("{}\t{}, #{}, lsl #{}\n", instruction, register, chunk, 16 * i).as_str();
+            } else {
+                code += format!("movk\t{}, #{}, lsl #{}\n", register, chunk, 16 * i).as_str();
+            }
+        }
+
+        // All chunks are the same (0 or -1)
+        if code.is_empty() {
+            code = format!("{}\t{}, #0\n", if inverted { "movn" } else { "movz" }, register);
+        }
+
+        code
+    }
+
+    fn load_address(&self, register: &str, label: &str) -> String {
+        // adrp gets the address of the 4KB page, the add the offset within it
+        match self.os {
+            AArch64Os::MacOs => format!("adrp\t{}, {}@PAGE\nadd\t{}, {}, {}@PAGEOFF\n", register, label, register, register, label),
+            AArch64Os::Linux => format!("adrp\t{}, {}\nadd\t{}, {}, :lo12:{}\n", register, label, register, register, label),
+        }
+    }
+
+    fn constant_section(&self, is_c_string: bool) -> String {
+        match (self.os, is_c_string) {
+            (AArch64Os::MacOs, true) => ".section\t__TEXT,__cstring,cstring_literals\n".to_string(),
+            (AArch64Os::MacOs, false) => ".section\t__TEXT,__const\n".to_string(),
+            (AArch64Os::Linux, _) => ".section\t.rodata\n".to_string(),
+        }
+    }
+
+    fn immediate_fits(&self, operation: Operation, value: i64) -> bool {
+        match operation {
+            // add, sub and cmp take 12-bit unsigned immediates (negative ones are handled by using the opposite instruction)
+            Operation::Add | Operation::Subtract => value.unsigned_abs() < 4096,
+            _ if operation.is_comparison() => (0..4096).contains(&value),
+            Operation::ShiftLeft | Operation::ShiftRightArithmetic | Operation::ShiftRightLogical => true,
+            _ => false,
+        }
+    }
+
+    fn compute(&self, operation: Operation, destination: &str, left: &str, right: &RegisterOrImmediate) -> String {
+        let right = match right {
+            RegisterOrImmediate::Register(register) => register.clone(),
+            RegisterOrImmediate::Immediate(value) => match operation {
+                Operation::Add | Operation::Subtract => {
+                    // Adding a negative value is subtracting a positive one and the other way round
+                    let is_add = (operation == Operation::Add) == (*value >= 0);
+                    return format!("{}\t{}, {}, #{}\n", if is_add { "add" } else { "sub" }, destination, left, value.unsigned_abs());
+                }
+                Operation::ShiftLeft | Operation::ShiftRightArithmetic | Operation::ShiftRightLogical => format!("#{}", value & 63),
+                _ => format!("#{}", value),
+            },
+        };
+
+        let instruction = match operation {
+            Operation::Add => "add",
+            Operation::Subtract => "sub",
+            Operation::Multiply => "mul",
+            Operation::DivideSigned => "sdiv",
+            Operation::DivideUnsigned => "udiv",
+            Operation::RemainderSigned | Operation::RemainderUnsigned => {
+                // There's no remainder instruction, so calculate left - (left / right) * right
+                let division = if operation == Operation::RemainderSigned { "sdiv" } else { "udiv" };
+                let mut code = format!("{}\t{}, {}, {}\n", division, Self::SCRATCH_REGISTER, left, right);
+                code += format!("msub\t{}, {}, {}, {}\n", destination, Self::SCRATCH_REGISTER, right, left).as_str();
+                return code;
+            }
+            Operation::And => "and",
+            Operation::Or => "orr",
+            Operation::Xor => "eor",
+            Operation::ShiftLeft => "lsl",
+            Operation::ShiftRightArithmetic => "asr",
+            Operation::ShiftRightLogical => "lsr",
+            _ => {
+                let condition = match operation {
+                    Operation::Equal => "eq",
+                    Operation::NotEqual => "ne",
+                    Operation::LessSigned => "lt",
+                    Operation::LessOrEqualSigned => "le",
+                    Operation::GreaterSigned => "gt",
+                    Operation::GreaterOrEqualSigned => "ge",
+                    Operation::LessUnsigned => "lo",
+                    Operation::LessOrEqualUnsigned => "ls",
+                    Operation::GreaterUnsigned => "hi",
+                    _ => "hs",
+                };
+
+                let mut code = format!("cmp\t{}, {}\n", left, right);
+                code += format!("cset\t{}, {}\n", destination, condition).as_str();
+                return code;
+            }
+        };
+
+        format!("{}\t{}, {}, {}\n", instruction, destination, left, right)
+    }
+
+    fn compute_unary(&self, operation: UnaryOperation, destination: &str, source: &str) -> String {
+        match operation {
+            UnaryOperation::Negate => format!("neg\t{}, {}\n", destination, source),
+            UnaryOperation::Not => format!("mvn\t{}, {}\n", destination, source),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use crate::compiler::low_level::arch::aarch64::aarch64::{AArch64, AArch64Os};
+    use crate::compiler::low_level::arch::arch::{Arch, NativeArch, RegisterOrImmediate};
+    use crate::compiler::low_level::arch::register::RegisterTag;
+    use crate::compiler::low_level::ir_text::parse_ir;
+    use crate::compiler::low_level::macro_function::MacroFunction;
+    use crate::compiler::low_level::macro_instruction::MacroInstruction;
+    use crate::compiler::low_level::variable::{ValueType, Variable};
+
+    #[test]
+    fn test_generate_assembly(){
+        let aarch64 = AArch64::new(AArch64Os::MacOs);
+
+        let size = Variable::new("my_app:main.rsl:Main:size".to_string(), vec![]);
+        let count = Variable::new("my_app:main.rsl:Main:count".to_string(), vec![]);
+
+        // Swap the first two arguments and pass them on
+        let instructions: Vec<MacroInstruction> = vec![
+            MacroInstruction::DeclareVariable(size.clone()),
+            MacroInstruction::DeclareVariable(count.clone()),
+            MacroInstruction::GetArgument(size.clone(), 0),
+            MacroInstruction::GetArgument(count.clone(), 1),
+            MacroInstruction::UseVariableAsArgument(count.clone(), 0),
+            MacroInstruction::UseVariableAsArgument(size.clone(), 1),
+            MacroInstruction::CallFunction("calloc".to_string(), 2, None, None),
+            MacroInstruction::DestroyVariable(size.clone()),
+            MacroInstruction::DestroyVariable(count.clone()),
+        ];
+
+        let assembly = aarch64.generate_assembly(vec![MacroFunction::new("main".to_string(), 2, false, instructions)]);
+        println!("{}", assembly);
+
+        assert!(assembly.contains("_main:\nstp\tx29, x30, [sp, #-16]!\nmov\tx29, sp\n"));
+        assert!(assembly.contains("bl\t_calloc\n"));
+        assert!(assembly.ends_with("ldp\tx29, x30, [sp], #16\nret\n"));
+
+        // The values of x0 and x1 need to be swapped through the scratch register
+        assert!(assembly.contains("mov\tx8, x"));
+    }
+
+    #[test]
+    fn test_repeated_arguments(){
+        let (functions, diagnostics) = parse_ir("repeated_arguments.rslir".to_string(), include_str!("../fixtures/repeated_arguments.rslir"));
+        assert_eq!(diagnostics, vec![]);
+
+        let assembly = AArch64::new(AArch64Os::Linux).generate_assembly(functions);
+        println!("{}", assembly);
+
+        // a stays in x0 and b goes to x2, the other argument registers get copies (from where the values were before)
+        assert!(assembly.contains("mov\tx2, x1\nmov\tx3, x0\nmov\tx4, x1\nmov\tx1, x0\nbl\tweigh\n"));
+    }
+
+    #[test]
+    fn test_large_frame(){
+        let aarch64 = AArch64::new(AArch64Os::MacOs);
+
+        // Frames over 4KiB don't fit into a single immediate
+        assert!(aarch64.prologue(4112).ends_with("mov\tx29, sp\nsub\tsp, sp, #1, lsl #12\nsub\tsp, sp, #16\n"));
+        assert!(aarch64.epilogue(4112).starts_with("add\tsp, sp, #1, lsl #12\nadd\tsp, sp, #16\nldp"));
+        assert!(aarch64.prologue(8192).ends_with("mov\tx29, sp\nsub\tsp, sp, #2, lsl #12\n"));
+        assert!(aarch64.prologue(48).ends_with("mov\tx29, sp\nsub\tsp, sp, #48\n"));
+    }
+
+    #[test]
+    fn test_linux_differences(){
+        let mac_os = AArch64::new(AArch64Os::MacOs);
+        let linux = AArch64::new(AArch64Os::Linux);
+
+        assert_eq!(mac_os.symbol("malloc"), "_malloc");
+        assert_eq!(linux.symbol("malloc"), "malloc");
+
+        // x18 is only off limits on macOS
+        let x18_is_usable = |aarch64: &AArch64| aarch64.registers.iter().any(|x| x.name == "x18" && x.tags.contains(&RegisterTag::GeneralPurpose));
+        assert!(!x18_is_usable(&mac_os));
+        assert!(x18_is_usable(&linux));
+
+        let count = Variable::new("my_app:main.rsl:Main:count".to_string(), vec![]);
+        let assembly = linux.generate_assembly(vec![MacroFunction::new("main".to_string(), 1, false, vec![
+            MacroInstruction::DeclareVariable(count.clone()),
+            MacroInstruction::GetArgument(count.clone(), 0),
+            MacroInstruction::UseVariableAsArgument(count.clone(), 0),
+            MacroInstruction::CallFunction("exit".to_string(), 1, None, None),
+            MacroInstruction::DestroyVariable(count.clone()),
+        ])]);
+
+        assert!(assembly.starts_with(".text\n.globl\tmain\n"));
+        assert!(assembly.contains("bl\texit\n"));
+
+        // Constants are addressed through their page
+        assert_eq!(linux.load_address("x0", ".Lconstant.0"), "adrp\tx0, .Lconstant.0\nadd\tx0, x0, :lo12:.Lconstant.0\n");
+        assert_eq!(mac_os.load_address("x0", "Lconstant.0"), "adrp\tx0, Lconstant.0@PAGE\nadd\tx0, x0, Lconstant.0@PAGEOFF\n");
+    }
+
+    #[test]
+    fn test_indirect_offsets(){
+        let linux = AArch64::new(AArch64Os::Linux);
+        let default = ValueType::default();
+
+        // Scaled offsets, unscaled ones and ones that need the scratch register
+        assert_eq!(linux.load_indirect("x0", "x1", 32760, &default), "ldr\tx0, [x1, #32760]\n");
+        assert_eq!(linux.load_indirect("x0", "x1", -8, &default), "ldur\tx0, [x1, #-8]\n");
+        assert_eq!(linux.store_indirect("x0", "x1", 12, &default), "stur\tx0, [x1, #12]\n");
+        assert!(linux.store_indirect("x0", "x1", 40000, &default).ends_with("str\tx0, [x1, x8]\n"));
+        assert_eq!(linux.stack_address("x0", &RegisterOrImmediate::Immediate(16)), "add\tx0, sp, #16\n");
+    }
+
+    #[test]
+    fn test_sized_accesses(){
+        let linux = AArch64::new(AArch64Os::Linux);
+        let i32 = ValueType::named("i32").unwrap();
+        let u8 = ValueType::named("u8").unwrap();
+        let i16 = ValueType::named("i16").unwrap();
+
+        // Smaller values use the lower half of the register and are extended when they're loaded
+        assert_eq!(linux.load_indirect("x0", "x1", 4, &i32), "ldrsw\tx0, [x1, #4]\n");
+        assert_eq!(linux.load_indirect("x0", "x1", 3, &u8), "ldrb\tw0, [x1, #3]\n");
+        assert_eq!(linux.load_indirect("x0", "x1", 3, &i16), "ldursh\tx0, [x1, #3]\n");
+        assert_eq!(linux.store_indirect("x0", "x1", 1, &u8), "strb\tw0, [x1, #1]\n");
+        assert_eq!(linux.store_indirect("x0", "x1", 8, &i16), "strh\tw0, [x1, #8]\n");
+        assert_eq!(linux.store_register("x2", 4, &i32), "str\tw2, [sp, #4]\n");
+
+        assert_eq!(linux.extend("x0", &u8), "uxtb\tw0, w0\n");
+        assert_eq!(linux.extend("x0", &i32), "sxtw\tx0, w0\n");
+        assert_eq!(linux.extend("x0", &ValueType::default()), "");
+    }
+
+    #[test]
+    fn test_float_registers(){
+        let linux = AArch64::new(AArch64Os::Linux);
+
+        // Floating point and integer arguments are counted separately: a goes to v0, b to x0, c to v1
+        let (functions, diagnostics) = parse_ir("test.rslir".to_string(), "
+            function @second(3) -> value {
+                declare f64 %a
+                %a = get_argument 0
+                declare %b
+                %b = get_argument 1
+                declare f64 %c
+                %c = get_argument 2
+                return %c
+            }
+
+            function @main(0) {
+                declare %p
+                %p = constant \"\\00\\00\\00\\00\\00\\00\\04\\40\"
+                declare f64 %x
+                %x = load %p, 0
+                declare f64 %y
+                %y = load %p, 0
+                declare %n
+                %n = immediate 7
+                declare f32 %s
+                %s = load %p, 4
+                declare f64 %r
+                argument %x, 0
+                argument %n, 1
+                argument %y, 2
+                %r = call @second, 3
+                store %p, 0, %s
+            }
+        ");
+        assert_eq!(diagnostics, vec![]);
+
+        let assembly = linux.generate_assembly(functions);
+        println!("{}", assembly);
+
+        assert!(assembly.contains("fmov\td0, d1\n"));
+        assert!(assembly.contains("ldr\td0, [x0, #0]\n"));
+        assert!(assembly.contains("mov\tx0, x1\n") || assembly.contains("movz\tx0, #7"));
+        assert!(assembly.contains("ldr\td1, [x0, #0]\n"));
+        assert!(assembly.contains("ldr\ts2, [x0, #4]\n"));
+
+        assert_eq!(linux.move_register("x1", "v2"), "fmov\tx1, d2\n");
+        assert_eq!(linux.store_register_pair("v8", "v9", 16), "stp\td8, d9, [sp, #16]\n");
+    }
+
+    #[test]
+    fn test_stack_arguments(){
+        let (functions, diagnostics) = parse_ir("stack_arguments.rslir".to_string(), include_str!("../fixtures/stack_arguments.rslir"));
+        assert_eq!(diagnostics, vec![]);
+
+        // Eight arguments fit into registers, the other two are in 8B slots above the saved frame pointer and link register
+        let linux = AArch64::new(AArch64Os::Linux).generate_assembly(functions.clone());
+        assert!(linux.contains("ldrb\tw9, [x29, #16]\n"));
+        assert!(linux.contains("ldrsw\tx10, [x29, #24]\n"));
+        assert!(linux.contains("stp\tx6, x6, [sp, #0]\nmov\tx7, x6\nbl\tweigh\n"));
+
+        // Apple packs them by their size instead
+        let mac_os = AArch64::new(AArch64Os::MacOs);
+        assert!(mac_os.generate_assembly(functions).contains("ldrsw\tx10, [x29, #20]\n"));
+
+        let mut offset = 0;
+        let offsets: Vec<usize> = ["u8", "i32", "u8", "i64"].iter().map(|x| mac_os.stack_argument_slot(&mut offset, &ValueType::named(x).unwrap())).collect();
+        assert_eq!(offsets, vec![0, 4, 8, 16]);
+    }
+
+    #[test]
+    fn test_variadic_arguments(){
+        let (functions, diagnostics) = parse_ir("test.rslir".to_string(), "
+            function @main(0) -> value {
+                declare %format
+                declare %a
+                declare %b
+                %format = constant \"%d %d\\0a\\00\"
+                %a = immediate 4
+                %b = immediate 2
+                argument %format, 0
+                argument %a, 1
+                argument %b, 2
+                %a = call @printf, 3, variadic 1
+                return %a
+            }
+        ");
+        assert_eq!(diagnostics, vec![]);
+
+        // Only the format is passed in a register, the numbers go into 8B slots at the bottom of the frame
+        let mac_os = AArch64::new(AArch64Os::MacOs).generate_assembly(functions.clone());
+        assert!(mac_os.contains("sub\tsp, sp, #16\n"));
+        assert!(mac_os.contains("stp\tx1, x2, [sp, #0]\nbl\t_printf\n"));
+
+        // Everywhere else, variadic arguments are passed like any other
+        let linux = AArch64::new(AArch64Os::Linux).generate_assembly(functions);
+        assert!(!linux.contains("str\t"));
+    }
+
+    #[test]
+    fn test_call_survivors(){
+        let (functions, diagnostics) = parse_ir("call_survivors.rslir".to_string(), include_str!("../fixtures/call_survivors.rslir"));
+        assert_eq!(diagnostics, vec![]);
+
+        let assembly = AArch64::new(AArch64Os::Linux).generate_assembly(functions);
+        println!("{}", assembly);
+
+        // Ten values go to the callee-saved registers, the other two are spilled as a pair and loaded again right after the call
+        assert!(assembly.contains("stp\tx10, x11, [sp, #0]\n"));
+        assert!(assembly.contains("bl\tseven\nldp\tx1, x2, [sp, #0]\n"));
+    }
+
+    #[test]
+    fn test_saved_registers(){
+        let (functions, diagnostics) = parse_ir("call_survivors.rslir".to_string(), include_str!("../fixtures/call_survivors.rslir"));
+        assert_eq!(diagnostics, vec![]);
+
+        let assembly = AArch64::new(AArch64Os::Linux).generate_assembly(functions);
+
+        // Leaf functions only use caller-saved registers and don't need a frame
+        assert!(assembly.contains("scramble:\nmovz\t"));
+        assert!(assembly.contains(".Lscramble.return:\nret\n"));
+        assert!(assembly.contains(".Lseven.return:\nret\n"));
+
+        // main saves exactly the callee-saved registers it uses, in pairs above the spill slots
+        assert!(assembly.contains("sub\tsp, sp, #96\nstp\tx19, x20, [sp, #16]\n"));
+        assert!(assembly.contains(".Lmain.return:\nldp\tx19, x20, [sp, #16]\n"));
+        assert!(assembly.contains("ldp\tx27, x28, [sp, #80]\nadd\tsp, sp, #96\n"));
+        assert_eq!(assembly.matches("stp\tx29, x30").count(), 1);
+    }
+}
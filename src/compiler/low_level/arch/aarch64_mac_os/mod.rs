@@ -1,3 +0,0 @@
-pub mod aarch64_mac_os;
-mod function_gen;
-mod variable_manager;
\ No newline at end of file
@@ -1,4 +1,13 @@
-use crate::compiler::low_level::macro_instruction::MacroInstruction;
+use crate::compiler::low_level::arch::aarch64::aarch64::{AArch64, AArch64Os};
+use crate::compiler::low_level::arch::c::c::C99;
+use crate::compiler::low_level::arch::function_gen::allocate_stack_slot;
+use crate::compiler::low_level::arch::register::Register;
+use crate::compiler::low_level::arch::riscv64_linux::riscv64_linux::RiscV64Linux;
+use crate::compiler::low_level::arch::wasm32::wasm32::Wasm32;
+use crate::compiler::low_level::arch::x86_64_linux::x86_64_linux::X86_64Linux;
+use crate::compiler::low_level::macro_function::MacroFunction;
+use crate::compiler::low_level::operation::{Operation, UnaryOperation};
+use crate::compiler::low_level::variable::ValueType;
 
 // The general definition and layout of every architecture
 
@@ -10,6 +19,158 @@ pub trait Arch{
     fn architecture_bits(&self) -> u8;
 
 
-    /// Generate assembly from the macro instructions in the given instruction set.
-   fn generate_assembly(&self, macro_instructions: Vec<MacroInstruction>) -> String;
-}
\ No newline at end of file
+    /// Generate assembly for the functions (made up of macro instructions) in the given instruction set.
+   fn generate_assembly(&self, functions: Vec<MacroFunction>) -> String;
+
+    /// Whether the assembly can be turned into an executable by the system's C compiler
+    fn is_native(&self) -> bool {
+        true
+    }
+
+    /// The extension of the files the output of generate_assembly is written to when building
+    fn file_extension(&self) -> &'static str {
+        "s"
+    }
+}
+
+/// An architecture that runs on real hardware.
+/// The shared function generator (function_gen) and variable manager (variable_manager) generate its code,
+/// they only need to know its registers and how the individual instructions are spelled.
+pub trait NativeArch: Arch {
+    fn registers(&self) -> Vec<Register>;
+
+    /// The name of a function as the assembler and the linker see it
+    fn symbol(&self, function_name: &str) -> String;
+
+    /// Everything that comes before the function's label (like making the symbol global)
+    fn function_header(&self, symbol: &str) -> String;
+
+    /// Set up the frame pointer and reserve the given amount of bytes (a multiple of 16) on the stack
+    fn prologue(&self, frame_size: usize) -> String;
+
+    /// Undo the prologue and return
+    fn epilogue(&self, frame_size: usize) -> String;
+
+    /// Return from a function that doesn't have a prologue (and epilogue) at all
+    fn return_without_frame(&self) -> String;
+
+    /// The offset from the frame pointer (once the prologue has run) to the arguments the caller passed on the stack
+    fn incoming_arguments_offset(&self) -> i64;
+
+    /// Reserve the slot of the next argument that's passed on the stack and return its offset from the stack pointer at the call.
+    /// The offset counts the bytes the stack-passed arguments before it take up, every argument takes up a whole 8B slot by default.
+    fn stack_argument_slot(&self, offset: &mut usize, _value_type: &ValueType) -> usize {
+        allocate_stack_slot(offset, 8)
+    }
+
+    /// Whether the variadic arguments of a call are all passed on the stack (instead of the registers the other arguments use)
+    fn variadic_arguments_on_stack(&self) -> bool {
+        false
+    }
+
+    fn call(&self, symbol: &str) -> String;
+
+    /// The name of a label that's only visible inside the assembly file (the name is already unique)
+    fn local_label(&self, name: &str) -> String;
+
+    fn jump(&self, label: &str) -> String;
+
+    /// Jump to the label if the register isn't 0
+    fn branch_if_not_zero(&self, register: &str, label: &str) -> String;
+
+    fn move_register(&self, destination: &str, source: &str) -> String;
+
+    /// Store a register at the given offset from the stack pointer (only as many bytes as the type has)
+    fn store_register(&self, register: &str, stack_offset: usize, value_type: &ValueType) -> String;
+
+    /// Store two whole registers next to each other (the first one at the given offset, the second one 8B above it)
+    fn store_register_pair(&self, first: &str, second: &str, stack_offset: usize) -> String {
+        self.store_register(first, stack_offset, &ValueType::default()) + self.store_register(second, stack_offset + 8, &ValueType::default()).as_str()
+    }
+
+    /// Load a value of the type from the given offset from the stack pointer (extended to the whole register)
+    fn load_register(&self, register: &str, stack_offset: usize, value_type: &ValueType) -> String;
+
+    /// Load two whole registers from next to each other (the counterpart of store_register_pair)
+    fn load_register_pair(&self, first: &str, second: &str, stack_offset: usize) -> String {
+        self.load_register(first, stack_offset, &ValueType::default()) + self.load_register(second, stack_offset + 8, &ValueType::default()).as_str()
+    }
+
+    /// Load the value at base + offset (the base is a register holding an address) into the register
+    fn load_indirect(&self, register: &str, base: &str, offset: i64, value_type: &ValueType) -> String;
+
+    /// Store the register at base + offset, the base may only be the scratch register if the offset is small
+    fn store_indirect(&self, register: &str, base: &str, offset: i64, value_type: &ValueType) -> String;
+
+    /// Sign- or zero-extend the value of the type in the low bits of the register to the whole register
+    fn extend(&self, register: &str, value_type: &ValueType) -> String;
+
+    /// Calculate destination = stack pointer + offset (the address of something on the stack)
+    fn stack_address(&self, destination: &str, offset: &RegisterOrImmediate) -> String;
+
+    /// Put any 64-bit value into a register
+    fn load_immediate(&self, register: &str, value: i64) -> String;
+
+    /// Put the address of a label (e.g. of a constant) into a register
+    fn load_address(&self, register: &str, label: &str) -> String;
+
+    /// Switch to the section constants are put in.
+    /// C strings (which end with their only 0) may get a section of their own.
+    fn constant_section(&self, is_c_string: bool) -> String;
+
+    /// Whether the value can be used as the right operand of the operation directly (without loading it into a register first)
+    fn immediate_fits(&self, operation: Operation, value: i64) -> bool;
+
+    /// Calculate destination = left <operation> right.
+    /// The destination may be the same register as one of the operands.
+    /// Nothing but the destination and the scratch register may be changed.
+    fn compute(&self, operation: Operation, destination: &str, left: &str, right: &RegisterOrImmediate) -> String;
+
+    /// Calculate destination = <operation> source (with the same rules as compute)
+    fn compute_unary(&self, operation: UnaryOperation, destination: &str, source: &str) -> String;
+}
+
+/// The right operand of an operation on a native architecture
+#[derive(Clone, Debug, PartialEq)]
+pub enum RegisterOrImmediate {
+    Register(String),
+    Immediate(i64),             // Only ever passed if NativeArch::immediate_fits allows it
+}
+
+/// All the architectures code can be generated for
+pub fn all_architectures() -> Vec<Box<dyn Arch>> {
+    vec![
+        Box::new(AArch64::new(AArch64Os::MacOs)),
+        Box::new(AArch64::new(AArch64Os::Linux)),
+        Box::new(X86_64Linux::new()),
+        Box::new(RiscV64Linux::new()),
+        Box::new(Wasm32::new()),
+        Box::new(C99::new()),
+    ]
+}
+
+/// Find an architecture by its name (see Arch::name)
+pub fn architecture_named(name: &str) -> Option<Box<dyn Arch>> {
+    all_architectures().into_iter().find(|x| x.name() == name)
+}
+
+/// The architecture of the machine the compiler is running on (if code can be generated for it)
+pub fn host_architecture() -> Option<Box<dyn Arch>> {
+    if cfg!(all(target_arch = "aarch64", target_os = "macos")) {
+        return architecture_named(AArch64Os::MacOs.name());
+    }
+
+    if cfg!(all(target_arch = "aarch64", target_os = "linux")) {
+        return architecture_named(AArch64Os::Linux.name());
+    }
+
+    if cfg!(all(target_arch = "x86_64", target_os = "linux")) {
+        return architecture_named(X86_64Linux::NAME);
+    }
+
+    if cfg!(all(target_arch = "riscv64", target_os = "linux")) {
+        return architecture_named(RiscV64Linux::NAME);
+    }
+
+    None
+}
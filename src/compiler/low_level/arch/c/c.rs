@@ -0,0 +1,416 @@
+use std::collections::HashMap;
+use crate::compiler::low_level::arch::arch::Arch;
+use crate::compiler::low_level::macro_function::MacroFunction;
+use crate::compiler::low_level::macro_instruction::MacroInstruction;
+use crate::compiler::low_level::operation::{Operand, Operation, UnaryOperation};
+use crate::compiler::low_level::variable::{BitUnit, ValueKind, ValueType, Variable};
+use crate::util::exit::{exit, ExitCode};
+
+/// Portable C99, which any C compiler can turn into an executable.
+/// Every variable becomes a local named after its (mangled) full name, every value is calculated as an intptr_t
+/// and converted to the type of the variable when it's stored in it.
+pub struct C99 {}
+
+impl C99 {
+    pub const NAME: &'static str = "c";
+    pub const BITS_COUNT: u8 = 64;                  // intptr_t is at most 64 bits wide on the platforms we care about
+    pub const ENTRY_POINT_NAME: &'static str = "main";
+
+    const VALUE_TYPE: &'static str = "intptr_t";
+    const ARGUMENT_PREFIX: &'static str = "argument_";
+    const CONSTANT_PREFIX: &'static str = "constant_";    // Constants are static arrays named after their index (e.g. constant_0)
+
+    /// The parameters of the entry point (C requires main's signature to look like this)
+    const ENTRY_POINT_PARAMETERS: [&'static str; 3] = ["int", "char **", "char **"];
+
+    pub fn new() -> Self {
+        C99 {}
+    }
+
+    /// The C type of a variable
+    fn c_type(value_type: &ValueType) -> &'static str {
+        match (&value_type.unit, value_type.kind) {
+            (_, ValueKind::Float) => exit("Floating point values aren't supported by the C backend yet.".to_string(), ExitCode::Internal),
+            (BitUnit::ArchitectureMax, ValueKind::Signed) => Self::VALUE_TYPE,
+            (BitUnit::ArchitectureMax, ValueKind::Unsigned) => "uintptr_t",
+            (BitUnit::Byte, ValueKind::Signed) => "int8_t",
+            (BitUnit::Byte, ValueKind::Unsigned) => "uint8_t",
+            (BitUnit::Word, ValueKind::Signed) => "int16_t",
+            (BitUnit::Word, ValueKind::Unsigned) => "uint16_t",
+            (BitUnit::DoubleWord, ValueKind::Signed) => "int32_t",
+            (BitUnit::DoubleWord, ValueKind::Unsigned) => "uint32_t",
+            (BitUnit::QuadWord, ValueKind::Signed) => "int64_t",
+            (BitUnit::QuadWord, ValueKind::Unsigned) => "uint64_t",
+        }
+    }
+
+    /// The C type of a variable of the function (only declarations know the type)
+    fn variable_type(types: &HashMap<String, ValueType>, variable: &Variable) -> &'static str {
+        Self::c_type(types.get(&variable.full_name).unwrap_or(&ValueType::default()))
+    }
+
+    /// Turn the full name of a variable (or a label) into a C identifier.
+    /// Letters and digits stay the same, underscores are doubled and everything else is replaced by _ and its hex code,
+    /// so different full names never end up with the same identifier.
+    pub fn mangle(full_name: &str) -> String {
+        Self::mangle_with_prefix("v_", full_name)
+    }
+
+    fn mangle_with_prefix(prefix: &str, full_name: &str) -> String {
+        let mut name = prefix.to_string();
+
+        for character in full_name.chars() {
+            match character {
+                'a'..='z' | 'A'..='Z' | '0'..='9' => name.push(character),
+                '_' => name += "__",
+                _ => name += format!("_{:02x}", character as u32).as_str(),
+            }
+        }
+
+        name
+    }
+
+    /// The C expression for an operand
+    fn operand(operand: &Operand) -> String {
+        match operand {
+            Operand::Variable(variable) => Self::mangle(&variable.full_name),
+            Operand::Immediate(value) if i32::try_from(*value).is_ok() => value.to_string(),
+            // The smallest value can't be written as a literal (as its positive counterpart is too large)
+            Operand::Immediate(i64::MIN) => format!("(({})INT64_MIN)", Self::VALUE_TYPE),
+            Operand::Immediate(value) => format!("(({})INT64_C({}))", Self::VALUE_TYPE, value),
+        }
+    }
+
+    /// The C expression for the operation.
+    /// Everything that could overflow is calculated without a sign, as signed overflows are undefined in C.
+    fn operation(operation: Operation, left: &Operand, right: &Operand) -> String {
+        let (left, right) = (Self::operand(left), Self::operand(right));
+
+        let unsigned = |operator: &str| format!("({})((uintptr_t){} {} (uintptr_t){})", Self::VALUE_TYPE, left, operator, right);
+        let signed = |operator: &str| format!("{} {} {}", left, operator, right);
+        let comparison = |operator: &str| format!("({})({} {} {})", Self::VALUE_TYPE, left, operator, right);
+        let unsigned_comparison = |operator: &str| format!("({})((uintptr_t){} {} (uintptr_t){})", Self::VALUE_TYPE, left, operator, right);
+
+        // Shift amounts are only as large as the width of the value allows (like on most architectures)
+        let shift_amount = format!("({} & (sizeof({}) * 8 - 1))", right, Self::VALUE_TYPE);
+
+        match operation {
+            Operation::Add => unsigned("+"),
+            Operation::Subtract => unsigned("-"),
+            Operation::Multiply => unsigned("*"),
+            Operation::DivideSigned => signed("/"),
+            Operation::DivideUnsigned => unsigned("/"),
+            Operation::RemainderSigned => signed("%"),
+            Operation::RemainderUnsigned => unsigned("%"),
+            Operation::And => signed("&"),
+            Operation::Or => signed("|"),
+            Operation::Xor => signed("^"),
+            Operation::ShiftLeft => format!("({})((uintptr_t){} << {})", Self::VALUE_TYPE, left, shift_amount),
+            Operation::ShiftRightArithmetic => format!("{} >> {}", left, shift_amount),
+            Operation::ShiftRightLogical => format!("({})((uintptr_t){} >> {})", Self::VALUE_TYPE, left, shift_amount),
+            Operation::Equal => comparison("=="),
+            Operation::NotEqual => comparison("!="),
+            Operation::LessSigned => comparison("<"),
+            Operation::LessOrEqualSigned => comparison("<="),
+            Operation::GreaterSigned => comparison(">"),
+            Operation::GreaterOrEqualSigned => comparison(">="),
+            Operation::LessUnsigned => unsigned_comparison("<"),
+            Operation::LessOrEqualUnsigned => unsigned_comparison("<="),
+            Operation::GreaterUnsigned => unsigned_comparison(">"),
+            Operation::GreaterOrEqualUnsigned => unsigned_comparison(">="),
+        }
+    }
+
+    /// The name of a function in C. Names that aren't C identifiers (like Main.helper) are mangled,
+    /// the others stay the same, so library functions can be called.
+    fn function_name(name: &str) -> String {
+        let is_identifier = name.chars().all(|x| x.is_ascii_alphanumeric() || x == '_') && !name.starts_with(|x: char| x.is_ascii_digit());
+
+        match is_identifier {
+            true => name.to_string(),
+            false => Self::mangle_with_prefix("f_", name),
+        }
+    }
+
+    /// The parameter list of a function that isn't the entry point
+    fn parameters(count: usize, named: bool) -> String {
+        if count == 0 {
+            return "void".to_string();
+        }
+
+        (0..count).map(|i| match named {
+            true => format!("{} {}{}", Self::VALUE_TYPE, Self::ARGUMENT_PREFIX, i),
+            false => Self::VALUE_TYPE.to_string(),
+        }).collect::<Vec<String>>().join(", ")
+    }
+
+    fn generate_function(&self, function: MacroFunction, functions: &[(String, usize)], declarations: &mut Vec<(String, usize, bool)>, constants: &mut Vec<Vec<u8>>) -> String {
+        let is_entry_point = function.name == Self::ENTRY_POINT_NAME;
+
+        let mut body = String::new();
+        let mut argument_count = 0;
+
+        // The arguments for the next call (in order)
+        let mut arguments: Vec<Option<Variable>> = Vec::new();
+
+        let types: HashMap<String, ValueType> = function.instructions.iter().filter_map(|x| match x {
+            MacroInstruction::DeclareVariable(variable) => Some((variable.full_name.clone(), variable.value_type.clone())),
+            _ => None,
+        }).collect();
+
+        for instruction in function.instructions {
+            match instruction {
+                MacroInstruction::DeclareVariable(variable) => {
+                    body += format!("    {} {} = 0;\n", Self::c_type(&variable.value_type), Self::mangle(&variable.full_name)).as_str();
+                }
+                MacroInstruction::DestroyVariable(_) => {}
+                MacroInstruction::GetArgument(variable, n) => {
+                    if is_entry_point && n >= Self::ENTRY_POINT_PARAMETERS.len() {
+                        exit(format!("The entry point can't have more than {} arguments in C.", Self::ENTRY_POINT_PARAMETERS.len()), ExitCode::BadCode);
+                    }
+
+                    argument_count = argument_count.max(n + 1);
+                    body += format!("    {} = ({}){}{};\n", Self::mangle(&variable.full_name), Self::VALUE_TYPE, Self::ARGUMENT_PREFIX, n).as_str();
+                }
+                MacroInstruction::UseVariableAsArgument(variable, n) => {
+                    if arguments.len() <= n {
+                        arguments.resize(n + 1, None);
+                    }
+
+                    arguments[n] = Some(variable);
+                }
+                MacroInstruction::CallFunction(function_name, argument_count, fixed_argument_count, result) => {
+                    if arguments.len() != argument_count || arguments.iter().any(|x| x.is_none()) {
+                        exit(format!("The arguments of the call to \"{}\" don't match its argument count ({}).", function_name, argument_count), ExitCode::Internal);
+                    }
+
+                    let argument_names = arguments.drain(..).flatten().map(|x| Self::mangle(&x.full_name)).collect::<Vec<String>>();
+                    let call = format!("{}({})", Self::function_name(&function_name), argument_names.join(", "));
+
+                    match result {
+                        Some(result) => body += format!("    {} = {};\n", Self::mangle(&result.full_name), call).as_str(),
+                        None => body += format!("    {};\n", call).as_str(),
+                    }
+
+                    // The functions of the program have prototypes already
+                    if let Some((_, parameter_count)) = functions.iter().find(|(x, _)| *x == function_name) {
+                        if *parameter_count != argument_count {
+                            exit(format!("Function \"{}\" has {} parameters, but it's called with {} arguments.", function_name, parameter_count, argument_count), ExitCode::Internal);
+                        }
+                        continue;
+                    }
+
+                    // Variadic functions are declared with their fixed parameters only
+                    let (parameter_count, is_variadic) = match fixed_argument_count {
+                        Some(count) => (count, true),
+                        None => (argument_count, false),
+                    };
+
+                    match declarations.iter().find(|(x, _, _)| *x == function_name) {
+                        Some((_, count, variadic)) if *count != parameter_count || *variadic != is_variadic => {
+                            exit(format!("Function \"{}\" is called with {} and {} arguments, but C functions are declared with a fixed amount of parameters.", function_name, count, argument_count), ExitCode::BadCode);
+                        }
+                        Some(_) => {}
+                        None => declarations.push((function_name, parameter_count, is_variadic)),
+                    }
+                }
+                MacroInstruction::Compute(target, operation, left, right) => {
+                    body += format!("    {} = {};\n", Self::mangle(&target.full_name), Self::operation(operation, &left, &right)).as_str();
+                }
+                MacroInstruction::ComputeUnary(target, operation, operand) => {
+                    let value = match operation {
+                        UnaryOperation::Negate => format!("({})(0 - (uintptr_t){})", Self::VALUE_TYPE, Self::operand(&operand)),
+                        UnaryOperation::Not => format!("~{}", Self::operand(&operand)),
+                    };
+                    body += format!("    {} = {};\n", Self::mangle(&target.full_name), value).as_str();
+                }
+                MacroInstruction::LoadImmediate(target, value) => {
+                    body += format!("    {} = {};\n", Self::mangle(&target.full_name), Self::operand(&Operand::Immediate(value))).as_str();
+                }
+                MacroInstruction::LoadConstantAddress(target, bytes) => {
+                    // Constants with the same bytes are only stored once
+                    let index = constants.iter().position(|x| *x == bytes).unwrap_or_else(|| {
+                        constants.push(bytes);
+                        constants.len() - 1
+                    });
+
+                    body += format!("    {} = ({}){}{};\n", Self::mangle(&target.full_name), Self::VALUE_TYPE, Self::CONSTANT_PREFIX, index).as_str();
+                }
+                MacroInstruction::Copy(target, source) => {
+                    body += format!("    {} = {};\n", Self::mangle(&target.full_name), Self::mangle(&source.full_name)).as_str();
+                }
+                MacroInstruction::Load(target, address, offset) => {
+                    body += format!("    {} = *({} *)((char *){} + {});\n", Self::mangle(&target.full_name), Self::variable_type(&types, &target), Self::mangle(&address.full_name), offset).as_str();
+                }
+                MacroInstruction::Store(address, offset, value) => {
+                    body += format!("    *({} *)((char *){} + {}) = {};\n", Self::variable_type(&types, &value), Self::mangle(&address.full_name), offset, Self::mangle(&value.full_name)).as_str();
+                }
+                MacroInstruction::AddressOf(target, variable) => {
+                    body += format!("    {} = ({})&{};\n", Self::mangle(&target.full_name), Self::VALUE_TYPE, Self::mangle(&variable.full_name)).as_str();
+                }
+                // Labels have their own namespace in C, so they can't clash with the variables.
+                // A label has to be followed by a statement, which a declaration isn't.
+                MacroInstruction::Label(label) => {
+                    body += format!("{}: ;\n", Self::mangle(&label)).as_str();
+                }
+                MacroInstruction::Jump(label) => {
+                    body += format!("    goto {};\n", Self::mangle(&label)).as_str();
+                }
+                MacroInstruction::BranchIf(condition, label) => {
+                    body += format!("    if ({}) goto {};\n", Self::mangle(&condition.full_name), Self::mangle(&label)).as_str();
+                }
+                MacroInstruction::Return(value) => {
+                    match value {
+                        Some(value) if is_entry_point => body += format!("    return (int){};\n", Self::mangle(&value.full_name)).as_str(),
+                        Some(value) => body += format!("    return {};\n", Self::mangle(&value.full_name)).as_str(),
+                        None => body += "    return 0;\n",
+                    }
+                }
+            }
+        }
+
+        if !is_entry_point {
+            let mut code = format!("{} {}({}) {{\n", Self::VALUE_TYPE, Self::function_name(&function.name), Self::parameters(function.parameter_count, true));
+            for i in argument_count..function.parameter_count {
+                code += format!("    (void){}{};\n", Self::ARGUMENT_PREFIX, i).as_str();
+            }
+            code += body.as_str();
+            code += "    return 0;\n";
+            code += "}\n";
+
+            return code;
+        }
+
+        // C only allows main to take (int, char **, char **) or a prefix of it,
+        // so all of them are taken and the ones that aren't used are simply ignored
+        let parameters = Self::ENTRY_POINT_PARAMETERS.iter().enumerate()
+            .map(|(i, parameter_type)| format!("{} {}{}", parameter_type, Self::ARGUMENT_PREFIX, i))
+            .collect::<Vec<String>>();
+
+        let mut code = format!("int {}({}) {{\n", function.name, parameters.join(", "));
+        for i in argument_count..Self::ENTRY_POINT_PARAMETERS.len() {
+            code += format!("    (void){}{};\n", Self::ARGUMENT_PREFIX, i).as_str();
+        }
+        code += body.as_str();
+        code += "    return 0;\n";
+        code += "}\n";
+
+        code
+    }
+}
+
+impl Arch for C99 {
+    fn name(&self) -> String {
+        Self::NAME.to_string()
+    }
+
+    fn architecture_bits(&self) -> u8 {
+        Self::BITS_COUNT
+    }
+
+    fn generate_assembly(&self, functions: Vec<MacroFunction>) -> String {
+        // (name, parameter count) of the functions of the program, they get prototypes, so they can call each other in any order
+        let prototypes: Vec<(String, usize)> = functions.iter().filter(|x| x.name != Self::ENTRY_POINT_NAME).map(|x| (x.name.clone(), x.parameter_count)).collect();
+
+        // Every other function that's called needs to be declared first (with exactly the amount of arguments it's called with)
+        let mut declarations: Vec<(String, usize, bool)> = Vec::new();
+
+        let mut constants: Vec<Vec<u8>> = Vec::new();
+        let code: Vec<String> = functions.into_iter().map(|x| self.generate_function(x, &prototypes, &mut declarations, &mut constants)).collect();
+
+        let mut source = "#include <stdint.h>\n\n".to_string();
+
+        // The declarations don't know the real types of library functions (like exit),
+        // compilers that know those functions would complain about every one of them otherwise
+        source += "#if defined(__clang__)\n#pragma clang diagnostic ignored \"-Wincompatible-library-redeclaration\"\n";
+        source += "#elif defined(__GNUC__)\n#pragma GCC diagnostic ignored \"-Wbuiltin-declaration-mismatch\"\n#endif\n\n";
+
+        for (name, parameter_count, is_variadic) in &declarations {
+            let parameters = match (*is_variadic, *parameter_count) {
+                (true, 0) => "...".to_string(),
+                (true, count) => format!("{}, ...", Self::parameters(count, false)),
+                (false, count) => Self::parameters(count, false),
+            };
+            source += format!("{} {}({});\n", Self::VALUE_TYPE, Self::function_name(name), parameters).as_str();
+        }
+
+        for (name, parameter_count) in &prototypes {
+            source += format!("{} {}({});\n", Self::VALUE_TYPE, Self::function_name(name), Self::parameters(*parameter_count, false)).as_str();
+        }
+
+        if !declarations.is_empty() || !prototypes.is_empty() {
+            source += "\n";
+        }
+
+        for (i, bytes) in constants.iter().enumerate() {
+            // Arrays can't be empty in C
+            let values = match bytes.is_empty() {
+                true => "0".to_string(),
+                false => bytes.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(", "),
+            };
+            source += format!("static const unsigned char {}{}[] = {{{}}};\n", Self::CONSTANT_PREFIX, i, values).as_str();
+        }
+
+        if !constants.is_empty() {
+            source += "\n";
+        }
+
+        source += code.join("\n").as_str();
+
+        source
+    }
+
+    fn file_extension(&self) -> &'static str {
+        "c"
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use crate::compiler::low_level::arch::arch::Arch;
+    use crate::compiler::low_level::arch::c::c::C99;
+    use crate::compiler::low_level::macro_function::MacroFunction;
+    use crate::compiler::low_level::macro_instruction::MacroInstruction;
+    use crate::compiler::low_level::variable::Variable;
+
+    #[test]
+    fn test_mangle(){
+        assert_eq!(C99::mangle("my_app:main.rsl:Main:count"), "v_my__app_3amain_2ersl_3aMain_3acount");
+        assert_ne!(C99::mangle("a:b"), C99::mangle("a_b"));
+    }
+
+    #[test]
+    fn test_generate_assembly(){
+        let c = C99::new();
+
+        let count = Variable::new("my_app:main.rsl:Main:count".to_string(), vec![]);
+
+        let format = Variable::new("my_app:main.rsl:Main:format".to_string(), vec![]);
+
+        // Print and exit with the amount of arguments the program got
+        let instructions: Vec<MacroInstruction> = vec![
+            MacroInstruction::DeclareVariable(count.clone()),
+            MacroInstruction::GetArgument(count.clone(), 0),
+            MacroInstruction::DeclareVariable(format.clone()),
+            MacroInstruction::LoadConstantAddress(format.clone(), b"%d\n\0".to_vec()),
+            MacroInstruction::UseVariableAsArgument(format.clone(), 0),
+            MacroInstruction::UseVariableAsArgument(count.clone(), 1),
+            MacroInstruction::CallFunction("printf".to_string(), 2, Some(1), None),
+            MacroInstruction::UseVariableAsArgument(count.clone(), 0),
+            MacroInstruction::CallFunction("exit".to_string(), 1, None, None),
+            MacroInstruction::DestroyVariable(format.clone()),
+            MacroInstruction::DestroyVariable(count.clone()),
+        ];
+
+        let source = c.generate_assembly(vec![MacroFunction::new("main".to_string(), 1, false, instructions)]);
+        println!("{}", source);
+
+        let count = C99::mangle(&count.full_name);
+        let format = C99::mangle(&format.full_name);
+        assert!(source.contains("intptr_t exit(intptr_t);\n"));
+        assert!(source.contains("intptr_t printf(intptr_t, ...);\n"));
+        assert!(source.contains("int main(int argument_0, char ** argument_1, char ** argument_2) {\n"));
+        assert!(source.contains(format!("    {} = (intptr_t)argument_0;\n", count).as_str()));
+        assert!(source.contains(format!("    printf({}, {});\n    exit({});\n", format, count, count).as_str()));
+    }
+}
@@ -0,0 +1,1145 @@
+use std::collections::{HashMap, HashSet};
+use crate::compiler::low_level::arch::arch::{NativeArch, RegisterOrImmediate};
+use crate::compiler::low_level::arch::register::{Register as ArchRegister, RegisterClass, RegisterSaver, RegisterTag};
+use crate::compiler::low_level::arch::variable_manager::{order_variable_locations, reload_after_call};
+use crate::compiler::low_level::data_position::DataPosition;
+use crate::compiler::low_level::data_position::DataPosition::Register;
+use crate::compiler::low_level::liveness::Liveness;
+use crate::compiler::low_level::macro_instruction::MacroInstruction;
+use crate::compiler::low_level::operation::{Operand, Operation, UnaryOperation};
+use crate::compiler::low_level::variable::{BitUnit, ValueKind, ValueType, Variable};
+use crate::util::exit::{exit, ExitCode};
+
+const ARGUMENT_PREFIX: &str = "argument-";
+const IMMEDIATE_PREFIX: &str = "immediate-";
+
+/// The label in front of the epilogue (labels of the macro instructions always contain a dot, so they can't clash with it)
+const RETURN_LABEL: &str = "return";
+
+/// What's known about a label of the function that's being generated
+struct LabelLayout {
+    variables: Vec<Variable>,   // The variables that are alive at the label and the (single) position each of them is in there
+    placed: bool,               // Whether the code after the label has been generated already, the layout can't change anymore then
+}
+
+/// Generate the assembly for a single function (including its prologue and epilogue).
+/// The constants it uses are added to the constants of the file (see generate_constants).
+pub fn generate_function(arch: &dyn NativeArch, name: &str, macro_instructions: Vec<MacroInstruction>, constants: &mut Vec<Vec<u8>>) -> String {
+    let registers = arch.registers();
+
+    let mut body = "".to_string();
+
+    let mut alive_variables: Vec<Variable> = Vec::new();
+
+    // Add reserved space for a register that serves as an intermediate register during some arithmetic operations
+    // The scratch register (like x8 on aarch64) is perfect for this
+    // as its contents don't need to preserved after a function call anyway
+    let scratch_register = scratch_register(arch);
+    alive_variables.push(Variable::new("arithmetic_reserve".to_string(), vec![Register(scratch_register)]));
+
+    // The arguments still sit in their registers (or in the caller's frame) when the function starts.
+    // Keep them alive as pseudo-variables until they're claimed by their GetArgument instruction.
+    // Their type is the one of the variable they're read into, which decides where they're passed.
+    let parameters: Vec<(usize, ValueType)> = macro_instructions.iter().filter_map(|x| match x {
+        MacroInstruction::GetArgument(variable, n) => Some((*n, declared_type(&macro_instructions, &variable.full_name))),
+        _ => None,
+    }).collect();
+
+    let (parameter_positions, _) = argument_positions(arch, &parameters, None);
+
+    for (n, value_type) in parameters {
+        let argument_name = format!("{}{}", ARGUMENT_PREFIX, n);
+        if alive_variables.iter().any(|x| x.full_name == argument_name) { continue; }
+
+        let position = match &parameter_positions[&n] {
+            DataPosition::StackOffset(offset) => DataPosition::Heap(Box::new(Register(frame_pointer(arch))), arch.incoming_arguments_offset() + *offset as i64),
+            position => position.clone(),
+        };
+
+        alive_variables.push(Variable::new(argument_name, vec![position]).with_type(value_type));
+    }
+
+    // The arguments of calls that don't fit into registers are put at the bottom of the frame, which is where the callee looks for them.
+    // The area is as large as the largest call needs it.
+    let mut outgoing_arguments_size = 0;
+
+    for i in 0..macro_instructions.len() {
+        if i > 0 && matches!(macro_instructions[i - 1], MacroInstruction::UseVariableAsArgument(_, _)) { continue; }
+
+        let arguments: Vec<(usize, ValueType)> = call_arguments(&macro_instructions, i).into_iter()
+            .map(|(n, variable)| (n, declared_type(&macro_instructions, &variable.full_name))).collect();
+
+        outgoing_arguments_size = outgoing_arguments_size.max(argument_positions(arch, &arguments, fixed_argument_count(&macro_instructions, i)).1);
+    }
+
+    // The amount of bytes allocated to the stack since the start of the current subroutine
+    let mut stack_offset_since_function_start: usize = outgoing_arguments_size;
+
+    // Variables whose address is taken always live in their own stack slot (their home),
+    // so the value is the same no matter if it's accessed directly or through the address
+    let mut homes: HashMap<String, usize> = HashMap::new();
+
+    for instruction in &macro_instructions {
+        let MacroInstruction::AddressOf(_, variable) = instruction else { continue; };
+        if homes.contains_key(&variable.full_name) { continue; }
+
+        let value_type = declared_type(&macro_instructions, &variable.full_name);
+        homes.insert(variable.full_name.clone(), allocate_stack_slot(&mut stack_offset_since_function_start, value_type.bytes(arch)));
+    }
+
+    // Where the variables are read, which decides what stays in registers
+    let liveness = Liveness::analyze(&macro_instructions);
+
+    let mut layouts: HashMap<String, LabelLayout> = HashMap::new();
+
+    // Code that follows a jump only runs if it's jumped to, which means it starts with a label
+    let mut reachable = true;
+
+    // The code that moves variables around before a conditional branch is taken, it's put after the epilogue
+    let mut trampolines = String::new();
+
+    // Whether there's a Return, which jumps to the epilogue
+    let mut has_returned = false;
+
+    // The variables that have been put on the stack to survive the next call, they're loaded again right after it
+    let mut spilled_for_call: Vec<String> = Vec::new();
+
+    // Every register that has held a value, the callee-saved ones among them are saved by the prologue
+    let mut used_registers: HashSet<String> = HashSet::new();
+
+    for (i, current_instruction) in macro_instructions.iter().enumerate() {
+        used_registers.extend(alive_variables.iter().flat_map(|x| x.positions.iter().filter_map(|x| x.register_name())));
+
+        if !reachable && !matches!(current_instruction, MacroInstruction::Label(_)) { continue; }
+
+        // Generate assembly for current instruction
+        match current_instruction.clone() {
+            MacroInstruction::DeclareVariable(variable) => {
+                if alive_variables.iter().any(|x| x.full_name == variable.full_name) {
+                    exit(format!("Variable \"{}\" has been declared twice.", variable.full_name), ExitCode::Internal);
+                }
+
+                alive_variables.push(variable);
+            }
+
+            MacroInstruction::DestroyVariable(variable) => {
+                // Forgetting about the variable is enough, its register or stack space is free for the taking now
+                alive_variables.retain(|x| x.full_name != variable.full_name);
+            }
+
+            MacroInstruction::UseVariableAsArgument(_, _) => {
+                // All the arguments of a call are moved to their positions at once (when the first one is encountered),
+                // so the following ones are in place already.
+                if i > 0 && matches!(macro_instructions[i - 1], MacroInstruction::UseVariableAsArgument(_, _)) { continue; }
+
+                let (code, spilled) = order_variable_locations(arch, &mut alive_variables, registers.clone(), &macro_instructions, i, &liveness, &mut stack_offset_since_function_start);
+                body += code.as_str();
+                spilled_for_call = spilled;
+            }
+
+            MacroInstruction::CallFunction(function_name, _, _, result) => {
+                // Calls without arguments still need the values that have to survive them out of the way
+                if i == 0 || !matches!(macro_instructions[i - 1], MacroInstruction::UseVariableAsArgument(_, _)) {
+                    let (code, spilled) = order_variable_locations(arch, &mut alive_variables, registers.clone(), &macro_instructions, i, &liveness, &mut stack_offset_since_function_start);
+                    body += code.as_str();
+                    spilled_for_call = spilled;
+                }
+
+                body += arch.call(&arch.symbol(&function_name)).as_str();
+
+                // The call has overwritten whatever was in the caller-saved registers before
+                for variable in alive_variables.iter_mut() {
+                    variable.positions.retain(|x| !x.register_name().is_some_and(|x| registers.iter().any(|register| register.name == x && register.saver == RegisterSaver::Caller)));
+                }
+
+                if let Some(result) = result {
+                    let return_register = return_register(arch, RegisterClass::of(&value_type(&alive_variables, &result.full_name)));
+
+                    let Some(variable) = alive_variables.iter_mut().find(|x| x.full_name == result.full_name) else {
+                        exit(format!("The result of the call to \"{}\" was supposed to be stored in variable \"{}\", which hasn't been declared.", function_name, result.full_name), ExitCode::Internal);
+                    };
+
+                    // Just like with arguments, the variable takes over the register the result is in
+                    variable.positions = vec![Register(return_register.clone())];
+                    body += arch.extend(&return_register, &variable.value_type).as_str();
+                }
+
+                body += reload_after_call(arch, &mut alive_variables, &registers, &spilled_for_call).as_str();
+                spilled_for_call.clear();
+            }
+
+            MacroInstruction::GetArgument(variable, n) => {
+                let argument_name = format!("{}{}", ARGUMENT_PREFIX, n);
+
+                let Some(argument_position) = alive_variables.iter().position(|x| x.full_name == argument_name) else {
+                    exit(format!("Argument {} of function \"{}\" has been read twice.", n, name), ExitCode::Internal);
+                };
+                let argument = alive_variables.remove(argument_position);
+
+                let Some(variable) = alive_variables.iter_mut().find(|x| x.full_name == variable.full_name) else {
+                    exit(format!("Argument {} was supposed to be stored in variable \"{}\", which hasn't been declared.", n, variable.full_name), ExitCode::Internal);
+                };
+
+                // Nothing needs to be moved, the variable just takes over the argument's place.
+                // Only the bits of the type are passed, the others may be anything.
+                variable.positions = argument.positions;
+
+                if let Some(register) = variable.positions.iter().find_map(|x| x.register_name()) {
+                    body += arch.extend(&register, &variable.value_type).as_str();
+                } else if liveness.is_dead_after(i, &variable.full_name) {
+                    variable.positions = vec![];
+                } else {
+                    // Arguments from the caller's frame are loaded right away (which extends them), only registers and the own frame hold variables
+                    let full_name = variable.full_name.clone();
+                    let mut context = Context { arch, registers: &registers, liveness: &liveness, index: i, alive_variables: &mut alive_variables, stack_offset: &mut stack_offset_since_function_start, code: &mut body };
+                    place_in_register(&mut context, &full_name, &[], true);
+                }
+            }
+
+            MacroInstruction::Compute(target, operation, left, right) => {
+                let mut context = Context { arch, registers: &registers, liveness: &liveness, index: i, alive_variables: &mut alive_variables, stack_offset: &mut stack_offset_since_function_start, code: &mut body };
+                generate_computation(&mut context, i, target, operation, left, right);
+            }
+
+            MacroInstruction::ComputeUnary(target, operation, operand) => {
+                let mut context = Context { arch, registers: &registers, liveness: &liveness, index: i, alive_variables: &mut alive_variables, stack_offset: &mut stack_offset_since_function_start, code: &mut body };
+                generate_unary_computation(&mut context, target, operation, operand);
+            }
+
+            MacroInstruction::LoadImmediate(target, value) => {
+                let mut context = Context { arch, registers: &registers, liveness: &liveness, index: i, alive_variables: &mut alive_variables, stack_offset: &mut stack_offset_since_function_start, code: &mut body };
+                let destination = place_in_register(&mut context, &target.full_name, &[], false);
+                let value = integer_type(context.alive_variables, &target.full_name).normalize(arch, value);
+                *context.code += arch.load_immediate(&destination, value).as_str();
+            }
+
+            MacroInstruction::LoadConstantAddress(target, bytes) => {
+                let label = constant_label(arch, constants, bytes);
+
+                let mut context = Context { arch, registers: &registers, liveness: &liveness, index: i, alive_variables: &mut alive_variables, stack_offset: &mut stack_offset_since_function_start, code: &mut body };
+                let destination = place_in_register(&mut context, &target.full_name, &[], false);
+                *context.code += arch.load_address(&destination, &label).as_str();
+            }
+
+            MacroInstruction::Copy(target, source) => {
+                if RegisterClass::of(&value_type(&alive_variables, &target.full_name)) != RegisterClass::of(&value_type(&alive_variables, &source.full_name)) {
+                    exit(format!("Variable \"{}\" is copied into variable \"{}\", but converting between integers and floating point values isn't supported yet.", source.full_name, target.full_name), ExitCode::Internal);
+                }
+
+                let mut context = Context { arch, registers: &registers, liveness: &liveness, index: i, alive_variables: &mut alive_variables, stack_offset: &mut stack_offset_since_function_start, code: &mut body };
+                let source_register = place_in_register(&mut context, &source.full_name, &[], true);
+                let destination = place_in_register(&mut context, &target.full_name, std::slice::from_ref(&source.full_name), false);
+
+                if destination != source_register {
+                    *context.code += arch.move_register(&destination, &source_register).as_str();
+                }
+
+                // The target might be smaller than the source
+                let target_type = value_type(context.alive_variables, &target.full_name);
+                if target_type != value_type(context.alive_variables, &source.full_name) {
+                    *context.code += arch.extend(&destination, &target_type).as_str();
+                }
+            }
+
+            MacroInstruction::Load(target, address, offset) => {
+                let mut context = Context { arch, registers: &registers, liveness: &liveness, index: i, alive_variables: &mut alive_variables, stack_offset: &mut stack_offset_since_function_start, code: &mut body };
+                let base = place_in_register(&mut context, &address.full_name, &[], true);
+                let destination = place_in_register(&mut context, &target.full_name, &[address.full_name], false);
+
+                // The type of the target decides how many bytes are read
+                let target_type = value_type(context.alive_variables, &target.full_name);
+                *context.code += load_position(arch, &destination, &DataPosition::Heap(Box::new(Register(base)), offset), &target_type).as_str();
+            }
+
+            MacroInstruction::Store(address, offset, value) => {
+                let mut context = Context { arch, registers: &registers, liveness: &liveness, index: i, alive_variables: &mut alive_variables, stack_offset: &mut stack_offset_since_function_start, code: &mut body };
+                let value_register = place_in_register(&mut context, &value.full_name, &[], true);
+                let base = place_in_register(&mut context, &address.full_name, std::slice::from_ref(&value.full_name), true);
+
+                let value_type = value_type(context.alive_variables, &value.full_name);
+                *context.code += store_position(arch, &value_register, &DataPosition::Heap(Box::new(Register(base)), offset), &value_type).as_str();
+            }
+
+            MacroInstruction::AddressOf(target, variable) => {
+                let mut context = Context { arch, registers: &registers, liveness: &liveness, index: i, alive_variables: &mut alive_variables, stack_offset: &mut stack_offset_since_function_start, code: &mut body };
+                let destination = place_in_register(&mut context, &target.full_name, &[], false);
+
+                *context.code += arch.stack_address(&destination, &RegisterOrImmediate::Immediate(homes[&variable.full_name] as i64)).as_str();
+            }
+
+            MacroInstruction::Label(label) => {
+                // Falling through to the label is just like jumping to it
+                if reachable {
+                    let is_dead = |variable: &str| liveness.is_dead(i, variable);
+                    body += moves_to_label(arch, name, &label, &alive_variables, &is_dead, &mut layouts, &mut stack_offset_since_function_start).as_str();
+                }
+
+                if !layouts.contains_key(&label) {
+                    // Nothing has jumped here so far and the code before doesn't get here either,
+                    // so the code after the label can only be reached if it's jumped to from below
+                    let jumped_to_later = macro_instructions[i + 1..].iter().any(|x| matches!(x, MacroInstruction::Jump(target) | MacroInstruction::BranchIf(_, target) if *target == label));
+
+                    if !jumped_to_later {
+                        reachable = false;
+                        continue;
+                    }
+
+                    // The variables stay where they are, the jumps from below have to adapt
+                    let is_dead = |variable: &str| liveness.is_dead(i, variable);
+                    let variables = label_variables(arch, &alive_variables, &is_dead, &mut stack_offset_since_function_start);
+                    layouts.insert(label.clone(), LabelLayout { variables, placed: false });
+                }
+
+                let Some(layout) = layouts.get_mut(&label) else { continue; };
+
+                layout.placed = true;
+                alive_variables = layout.variables.clone();
+                reachable = true;
+
+                body += format!("{}:\n", label_symbol(arch, name, &label)).as_str();
+            }
+
+            MacroInstruction::Jump(label) => {
+                let is_dead = |variable: &str| liveness.is_dead_after(i, variable);
+                body += moves_to_label(arch, name, &label, &alive_variables, &is_dead, &mut layouts, &mut stack_offset_since_function_start).as_str();
+                body += arch.jump(&label_symbol(arch, name, &label)).as_str();
+
+                reachable = false;
+            }
+
+            MacroInstruction::Return(value) => {
+                if let Some(value) = &value && !alive_variables.iter().any(|x| x.full_name == value.full_name) {
+                    exit(format!("Variable \"{}\" is returned, but it hasn't been declared (or has been destroyed already).", value.full_name), ExitCode::Internal);
+                }
+
+                // A value that has never been set doesn't need to be moved
+                if let Some(value) = value.and_then(|value| alive_variables.iter().find(|x| x.full_name == value.full_name))
+                    && let Some(position) = value.get_cheapest_position() {
+                    body += move_values(arch, vec![(Register(return_register(arch, RegisterClass::of(&value.value_type))), position, value.value_type.clone())]).as_str();
+                }
+
+                body += arch.jump(&label_symbol(arch, name, RETURN_LABEL)).as_str();
+
+                has_returned = true;
+                reachable = false;
+            }
+
+            MacroInstruction::BranchIf(condition, label) => {
+                integer_type(&alive_variables, &condition.full_name);
+
+                let mut context = Context { arch, registers: &registers, liveness: &liveness, index: i, alive_variables: &mut alive_variables, stack_offset: &mut stack_offset_since_function_start, code: &mut body };
+                let condition = place_in_register(&mut context, &condition.full_name, &[], true);
+
+                // The label must find the variables with a home there
+                body += sync_homes(arch, &mut alive_variables, &homes, None).as_str();
+
+                let is_dead = |variable: &str| liveness.is_dead_after(i, variable);
+                let moves = moves_to_label(arch, name, &label, &alive_variables, &is_dead, &mut layouts, &mut stack_offset_since_function_start);
+
+                if moves.is_empty() {
+                    body += arch.branch_if_not_zero(&condition, &label_symbol(arch, name, &label)).as_str();
+                } else {
+                    // The variables may only be moved if the branch is taken, so that happens somewhere else
+                    let trampoline = label_symbol(arch, name, &format!("moves{}", i));
+                    body += arch.branch_if_not_zero(&condition, &trampoline).as_str();
+
+                    trampolines += format!("{}:\n", trampoline).as_str();
+                    trampolines += moves.as_str();
+                    trampolines += arch.jump(&label_symbol(arch, name, &label)).as_str();
+                }
+            }
+        }
+
+        // The arguments of a call must stay where they've been put until the call
+        if reachable && !matches!(current_instruction, MacroInstruction::UseVariableAsArgument(_, _)) {
+            body += sync_homes(arch, &mut alive_variables, &homes, current_instruction.written_variable()).as_str();
+        }
+    }
+
+    used_registers.extend(alive_variables.iter().flat_map(|x| x.positions.iter().filter_map(|x| x.register_name())));
+
+    // Returns jump to the epilogue, the last one doesn't need to if it's right before it
+    if has_returned {
+        let return_label = label_symbol(arch, name, RETURN_LABEL);
+        let jump = arch.jump(&return_label);
+
+        if !reachable && body.ends_with(&jump) {
+            body.truncate(body.len() - jump.len());
+        }
+
+        body += format!("{}:\n", return_label).as_str();
+    }
+
+    // Only the callee-saved registers the function uses are saved, at the top of the frame
+    let saved_registers: Vec<(String, usize, ValueType)> = registers.iter()
+        .filter(|x| x.saver == RegisterSaver::Callee && x.tags.contains(&RegisterTag::GeneralPurpose) && used_registers.contains(&x.name))
+        .map(|x| {
+            // Only the lower 64 bits of floating point registers are ever callee-saved
+            let value_type = match x.class {
+                RegisterClass::Integer => ValueType::default(),
+                RegisterClass::Float => ValueType::new(BitUnit::QuadWord, ValueKind::Float),
+            };
+            (x.name.clone(), allocate_stack_slot(&mut stack_offset_since_function_start, 8), value_type)
+        }).collect();
+
+    // The stack pointer must always be 16B-aligned
+    let frame_size = stack_offset_since_function_start.div_ceil(16) * 16;
+
+    // A function that doesn't call anything, has nothing on the stack and doesn't read arguments from the caller's frame doesn't need a frame
+    let calls_functions = macro_instructions.iter().any(|x| matches!(x, MacroInstruction::CallFunction(_, _, _, _)));
+    let has_stack_parameters = parameter_positions.values().any(|x| !matches!(x, Register(_)));
+    let needs_frame = frame_size > 0 || calls_functions || has_stack_parameters;
+
+    let symbol = arch.symbol(name);
+
+    let mut assembly = arch.function_header(&symbol);
+    assembly += format!("{}:\n", symbol).as_str();
+
+    if needs_frame {
+        assembly += arch.prologue(frame_size).as_str();
+        assembly += store_values(arch, saved_registers.clone()).as_str();
+    }
+
+    assembly += body.as_str();
+
+    match needs_frame {
+        true => {
+            assembly += load_values(arch, saved_registers).as_str();
+            assembly += arch.epilogue(frame_size).as_str();
+        }
+        false => assembly += arch.return_without_frame().as_str(),
+    }
+
+    assembly += trampolines.as_str();
+
+    assembly
+}
+
+/// The label of the constant with the given bytes, constants with the same bytes share their label
+fn constant_label(arch: &dyn NativeArch, constants: &mut Vec<Vec<u8>>, bytes: Vec<u8>) -> String {
+    let index = match constants.iter().position(|x| *x == bytes) {
+        Some(index) => index,
+        None => {
+            constants.push(bytes);
+            constants.len() - 1
+        }
+    };
+
+    arch.local_label(&format!("constant.{}", index))
+}
+
+/// Generate the read-only data of the file (the constants the functions use)
+pub fn generate_constants(arch: &dyn NativeArch, constants: &[Vec<u8>]) -> String {
+    let mut code = String::new();
+
+    for (i, bytes) in constants.iter().enumerate() {
+        let is_c_string = bytes.iter().position(|&x| x == 0) == Some(bytes.len() - 1);
+
+        code += arch.constant_section(is_c_string).as_str();
+        code += format!("{}:\n", arch.local_label(&format!("constant.{}", i))).as_str();
+
+        if !bytes.is_empty() {
+            code += format!(".byte\t{}\n", bytes.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(", ")).as_str();
+        }
+    }
+
+    code
+}
+
+/// The name a label of the function has in the assembly (labels are only unique within their function)
+fn label_symbol(arch: &dyn NativeArch, function_name: &str, label: &str) -> String {
+    arch.local_label(&format!("{}.{}", function_name, label))
+}
+
+/// The variables of a label's layout: the alive variables, each one in a single position (or none if it's dead there).
+fn label_variables(arch: &dyn NativeArch, alive_variables: &[Variable], is_dead: &dyn Fn(&str) -> bool, stack_offset: &mut usize) -> Vec<Variable> {
+    alive_variables.iter().map(|variable| {
+        if is_dead(&variable.full_name) {
+            return Variable { positions: vec![], ..variable.clone() };
+        }
+
+        // Variables that don't have a value yet still need a place, as they might get one on another path to the label
+        let position = variable.get_cheapest_position()
+            .unwrap_or_else(|| DataPosition::StackOffset(allocate_stack_slot(stack_offset, variable.value_type.bytes(arch))));
+
+        Variable { positions: vec![position], ..variable.clone() }
+    }).collect()
+}
+
+/// Generate the code that moves the variables to where the label expects them.
+/// The first path to a label decides where the variables are there, the others have to adapt.
+/// Variables that aren't alive on every path to a label are dead after it.
+/// Variables whose value is dead there keep no position, nothing needs to be moved for them.
+fn moves_to_label(arch: &dyn NativeArch, function_name: &str, label: &str, alive_variables: &[Variable], is_dead: &dyn Fn(&str) -> bool, layouts: &mut HashMap<String, LabelLayout>, stack_offset: &mut usize) -> String {
+    let Some(layout) = layouts.get_mut(label) else {
+        let variables = label_variables(arch, alive_variables, is_dead, stack_offset);
+        layouts.insert(label.to_string(), LabelLayout { variables, placed: false });
+        return String::new();
+    };
+
+    // Once the code after the label uses a variable, every path has to provide it
+    if !layout.placed {
+        layout.variables.retain(|x| alive_variables.iter().any(|variable| variable.full_name == x.full_name));
+    }
+
+    let mut moves: Vec<(DataPosition, DataPosition, ValueType)> = Vec::new();
+
+    for target in &layout.variables {
+        let Some(variable) = alive_variables.iter().find(|x| x.full_name == target.full_name) else {
+            exit(format!("Variable \"{}\" isn't alive when jumping back to label \"{}\" of function \"{}\".", target.full_name, label, function_name), ExitCode::Internal);
+        };
+
+        // A variable without a value doesn't need to be moved
+        let (Some(destination), Some(source)) = (target.get_cheapest_position(), variable.get_cheapest_position()) else { continue; };
+        moves.push((destination, source, variable.value_type.clone()));
+    }
+
+    move_values(arch, moves)
+}
+
+/// Everything that's needed to generate code in the middle of a function
+struct Context<'a> {
+    arch: &'a dyn NativeArch,
+    registers: &'a [ArchRegister],
+    liveness: &'a Liveness,
+    index: usize,               // The index of the instruction the code is generated for
+    alive_variables: &'a mut Vec<Variable>,
+    stack_offset: &'a mut usize,
+    code: &'a mut String,
+}
+
+/// Generate the code for target = left <operation> right.
+/// Both operands and the target are put into registers first, immediates are only used directly if the architecture can encode them.
+fn generate_computation(context: &mut Context, index: usize, target: Variable, operation: Operation, left: Operand, right: Operand) {
+    let target_type = integer_type(context.alive_variables, &target.full_name);
+
+    for operand in [&left, &right] {
+        if let Some(variable) = operand.variable() {
+            integer_type(context.alive_variables, &variable.full_name);
+        }
+    }
+
+    // Nothing needs to be done at runtime if everything is known already
+    if let (Operand::Immediate(left), Operand::Immediate(right)) = (&left, &right) && let Some(result) = operation.evaluate(*left, *right) {
+        let destination = place_in_register(context, &target.full_name, &[], false);
+        *context.code += context.arch.load_immediate(&destination, target_type.normalize(context.arch, result)).as_str();
+        return;
+    }
+
+    // Immediates are easier to handle on the right side
+    let (operation, left, right) = match (&left, &right, operation.swapped()) {
+        (Operand::Immediate(_), Operand::Variable(_), Some(swapped)) => (swapped, right, left),
+        _ => (operation, left, right),
+    };
+
+    // The immediates that had to be put into registers, they're only needed for this computation
+    let mut temporaries: Vec<String> = Vec::new();
+
+    let left_name = match left {
+        Operand::Variable(variable) => variable.full_name,
+        Operand::Immediate(value) => load_temporary(context, format!("{}{}-left", IMMEDIATE_PREFIX, index), value, &[], &mut temporaries),
+    };
+    let left_register = place_in_register(context, &left_name, &[], true);
+
+    let (right_name, right) = match right {
+        Operand::Variable(variable) => {
+            let register = place_in_register(context, &variable.full_name, std::slice::from_ref(&left_name), true);
+            (Some(variable.full_name), RegisterOrImmediate::Register(register))
+        }
+        Operand::Immediate(value) if context.arch.immediate_fits(operation, value) => (None, RegisterOrImmediate::Immediate(value)),
+        Operand::Immediate(value) => {
+            let name = load_temporary(context, format!("{}{}-right", IMMEDIATE_PREFIX, index), value, std::slice::from_ref(&left_name), &mut temporaries);
+            let register = place_in_register(context, &name, &[], true);
+            (Some(name), RegisterOrImmediate::Register(register))
+        }
+    };
+
+    let mut keep = vec![left_name];
+    keep.extend(right_name);
+    let destination = place_in_register(context, &target.full_name, &keep, false);
+
+    *context.code += context.arch.compute(operation, &destination, &left_register, &right).as_str();
+    *context.code += context.arch.extend(&destination, &target_type).as_str();
+
+    context.alive_variables.retain(|x| !temporaries.contains(&x.full_name));
+}
+
+/// Generate the code for target = <operation> operand
+fn generate_unary_computation(context: &mut Context, target: Variable, operation: UnaryOperation, operand: Operand) {
+    let target_type = integer_type(context.alive_variables, &target.full_name);
+
+    let source_name = match operand {
+        Operand::Variable(variable) => {
+            integer_type(context.alive_variables, &variable.full_name);
+            variable.full_name
+        }
+        Operand::Immediate(value) => {
+            let destination = place_in_register(context, &target.full_name, &[], false);
+            *context.code += context.arch.load_immediate(&destination, target_type.normalize(context.arch, operation.evaluate(value))).as_str();
+            return;
+        }
+    };
+
+    let source = place_in_register(context, &source_name, &[], true);
+    let destination = place_in_register(context, &target.full_name, &[source_name], false);
+
+    *context.code += context.arch.compute_unary(operation, &destination, &source).as_str();
+    *context.code += context.arch.extend(&destination, &target_type).as_str();
+}
+
+/// The type of an alive variable
+fn value_type(alive_variables: &[Variable], full_name: &str) -> ValueType {
+    match alive_variables.iter().find(|x| x.full_name == full_name) {
+        Some(variable) => variable.value_type.clone(),
+        None => exit(format!("Variable \"{}\" is used, but it hasn't been declared (or has been destroyed already).", full_name), ExitCode::Internal),
+    }
+}
+
+/// The type of an alive variable that's used in a computation (or as a condition), which only works with integers so far
+fn integer_type(alive_variables: &[Variable], full_name: &str) -> ValueType {
+    let value_type = value_type(alive_variables, full_name);
+
+    if value_type.kind == ValueKind::Float {
+        exit(format!("Variable \"{}\" is used in a computation, but computations with floating point values aren't supported yet.", full_name), ExitCode::Internal);
+    }
+
+    value_type
+}
+
+/// The type of the variable in its declaration (the default type if it isn't declared, which the verifier reports)
+fn declared_type(macro_instructions: &[MacroInstruction], full_name: &str) -> ValueType {
+    macro_instructions.iter().find_map(|x| match x {
+        MacroInstruction::DeclareVariable(variable) if variable.full_name == full_name => Some(variable.value_type.clone()),
+        _ => None,
+    }).unwrap_or_default()
+}
+
+/// Reserve a stack slot of the given size (which is also its alignment) and return its offset
+pub fn allocate_stack_slot(stack_offset: &mut usize, bytes: usize) -> usize {
+    let offset = stack_offset.next_multiple_of(bytes);
+    *stack_offset = offset + bytes;
+    offset
+}
+
+/// Put an immediate into a register that's reserved by a new pseudo-variable and return the name of the pseudo-variable
+fn load_temporary(context: &mut Context, name: String, value: i64, keep: &[String], temporaries: &mut Vec<String>) -> String {
+    context.alive_variables.push(Variable::new(name.clone(), vec![]));
+    temporaries.push(name.clone());
+
+    let register = place_in_register(context, &name, keep, false);
+    *context.code += context.arch.load_immediate(&register, value).as_str();
+
+    name
+}
+
+/// Make sure the variable is in a general purpose register of its class and return the register.
+/// If there's no free register left, another variable of the class (but none of the ones to keep) is moved to the stack.
+/// The current value is only loaded (from the stack) if it's going to be read.
+fn place_in_register(context: &mut Context, full_name: &str, keep: &[String], load_value: bool) -> String {
+    let Some(index) = context.alive_variables.iter().position(|x| x.full_name == full_name) else {
+        exit(format!("Variable \"{}\" is used, but it hasn't been declared (or has been destroyed already).", full_name), ExitCode::Internal);
+    };
+
+    if let Some(register) = context.alive_variables[index].positions.iter().find_map(|x| x.register_name()) {
+        return register;
+    }
+
+    let occupied_registers: Vec<String> = context.alive_variables.iter().flat_map(|x| x.positions.iter().filter_map(|x| x.register_name())).collect();
+
+    let class = RegisterClass::of(&context.alive_variables[index].value_type);
+    let is_usable = |register: &ArchRegister| register.class == class && register.tags.contains(&RegisterTag::GeneralPurpose);
+
+    if !context.registers.iter().any(is_usable) {
+        exit(format!("Variable \"{}\" is of type {}, but architecture \"{}\" doesn't have registers for it yet.", full_name, context.alive_variables[index].value_type.name(), context.arch.name()), ExitCode::Internal);
+    }
+
+    let free_register = context.registers.iter()
+        .find(|x| is_usable(x) && !occupied_registers.contains(&x.name))
+        .map(|x| x.name.clone());
+
+    let register = match free_register {
+        Some(register) => register,
+        None => {
+            // Move another variable to the stack. Dead variables don't even need to be stored,
+            // otherwise the variable that's read last is the one to go.
+            let is_candidate = |x: &Variable| x.full_name != full_name && !keep.contains(&x.full_name) &&
+                x.positions.iter().any(|x| x.register_name().is_some_and(|x| context.registers.iter().any(|register| register.name == x && is_usable(register))));
+
+            let priority = |x: &Variable| {
+                if context.liveness.is_dead(context.index, &x.full_name) { return usize::MAX; }
+
+                // Pseudo-variables aren't known to the liveness analysis, so they're assumed to be needed soon
+                context.liveness.next_use(context.index, &x.full_name).unwrap_or(0)
+            };
+
+            let victim = context.alive_variables.iter().enumerate().filter(|(_, x)| is_candidate(x))
+                .max_by_key(|(i, x)| (priority(x), usize::MAX - i)).map(|(i, _)| i);
+
+            let Some(victim) = victim else {
+                exit(format!("There's no register left for variable \"{}\".", full_name), ExitCode::Internal);
+            };
+
+            let register = context.alive_variables[victim].positions.iter().find_map(|x| x.register_name()).unwrap();
+
+            if context.liveness.is_dead(context.index, &context.alive_variables[victim].full_name) {
+                context.alive_variables[victim].positions.retain(|x| x.register_name().is_none());
+            } else {
+                let value_type = context.alive_variables[victim].value_type.clone();
+                let stack_offset = allocate_stack_slot(context.stack_offset, value_type.bytes(context.arch));
+
+                *context.code += context.arch.store_register(&register, stack_offset, &value_type).as_str();
+                context.alive_variables[victim].positions = vec![DataPosition::StackOffset(stack_offset)];
+            }
+
+            register
+        }
+    };
+
+    let variable = &mut context.alive_variables[index];
+
+    if load_value && let Some(position) = variable.get_cheapest_position() {
+        *context.code += load_position(context.arch, &register, &position, &variable.value_type).as_str();
+    }
+
+    variable.positions = vec![Register(register.clone())];
+
+    register
+}
+
+/// Make sure the variables with a home are only in their home after an instruction.
+/// The written variable is stored there first, every other copy (e.g. in a register it's been loaded into) might be outdated
+/// once something is stored through the address.
+fn sync_homes(arch: &dyn NativeArch, alive_variables: &mut [Variable], homes: &HashMap<String, usize>, written: Option<&Variable>) -> String {
+    let mut code = String::new();
+
+    for variable in alive_variables.iter_mut() {
+        let Some(home) = homes.get(&variable.full_name) else { continue; };
+        let home = DataPosition::StackOffset(*home);
+
+        if written.is_some_and(|x| x.full_name == variable.full_name) && let Some(position) = variable.get_cheapest_position() {
+            code += move_values(arch, vec![(home.clone(), position, variable.value_type.clone())]).as_str();
+        }
+
+        variable.positions = vec![home];
+    }
+
+    code
+}
+
+/// The register that may be overwritten at any time (e.g. to break up cycles when moving registers around)
+pub fn scratch_register(arch: &dyn NativeArch) -> String {
+    let Some(register) = arch.registers().into_iter().find(|x| x.tags.contains(&RegisterTag::Scratch)) else {
+        exit(format!("Architecture \"{}\" doesn't have a scratch register.", arch.name()), ExitCode::Internal);
+    };
+
+    register.name
+}
+
+/// The register functions return their result of the class in
+fn return_register(arch: &dyn NativeArch, class: RegisterClass) -> String {
+    let Some(register) = arch.registers().into_iter().find(|x| x.class == class && x.tags.contains(&RegisterTag::ReturnValue)) else {
+        exit(format!("Architecture \"{}\" doesn't have a register for return values of class {:?}.", arch.name(), class), ExitCode::Internal);
+    };
+
+    register.name
+}
+
+/// The register that points at the start of the function's frame (after the prologue)
+fn frame_pointer(arch: &dyn NativeArch) -> String {
+    let Some(register) = arch.registers().into_iter().find(|x| x.tags.contains(&RegisterTag::FramePointer)) else {
+        exit(format!("Architecture \"{}\" doesn't have a frame pointer.", arch.name()), ExitCode::Internal);
+    };
+
+    register.name
+}
+
+/// The arguments of the call whose UseVariableAsArgument instructions start at the index (as their position and variable)
+pub fn call_arguments(instructions: &[MacroInstruction], index: usize) -> Vec<(usize, &Variable)> {
+    instructions[index..].iter().map_while(|x| match x {
+        MacroInstruction::UseVariableAsArgument(variable, n) => Some((*n, variable)),
+        _ => None,
+    }).collect()
+}
+
+/// The amount of fixed arguments of the call the arguments starting at the index belong to, if the called function is variadic
+pub fn fixed_argument_count(instructions: &[MacroInstruction], index: usize) -> Option<usize> {
+    match instructions[index..].iter().find(|x| !matches!(x, MacroInstruction::UseVariableAsArgument(_, _))) {
+        Some(MacroInstruction::CallFunction(_, _, fixed_argument_count, _)) => *fixed_argument_count,
+        _ => None,
+    }
+}
+
+/// Where the arguments (given as position and type) are passed and how many bytes the ones on the stack take up.
+/// Every register class has its own argument registers, which are handed out in the order of the positions,
+/// so the first floating point argument goes to the first floating point argument register no matter what comes before it.
+/// Once the registers of a class have run out, its arguments go on the stack, at an offset from the stack pointer at the call
+/// (see NativeArch::stack_argument_slot).
+/// Positions that aren't given (e.g. parameters that are never read) count as integers of the default type.
+/// The variadic arguments (the ones from the fixed argument count on) always go on the stack on some platforms (see NativeArch::variadic_arguments_on_stack).
+pub fn argument_positions(arch: &dyn NativeArch, arguments: &[(usize, ValueType)], fixed_argument_count: Option<usize>) -> (HashMap<usize, DataPosition>, usize) {
+    let registers = arch.registers();
+    let count = arguments.iter().map(|(position, _)| position + 1).max().unwrap_or(0);
+
+    let mut numbers: HashMap<RegisterClass, u8> = HashMap::new();
+    let mut stack_size = 0;
+    let mut positions = HashMap::new();
+
+    for position in 0..count {
+        let value_type = arguments.iter().find(|(x, _)| *x == position).map_or(ValueType::default(), |(_, value_type)| value_type.clone());
+        let class = RegisterClass::of(&value_type);
+
+        if let Some(fixed_argument_count) = fixed_argument_count && position >= fixed_argument_count && arch.variadic_arguments_on_stack() {
+            // Each variadic argument takes up a whole 8B slot
+            if arguments.iter().any(|(x, _)| *x == position) {
+                positions.insert(position, DataPosition::StackOffset(allocate_stack_slot(&mut stack_size, 8)));
+            }
+            continue;
+        }
+
+        let number = numbers.entry(class).or_insert(0);
+        let register = registers.iter().find(|x| x.is_argument(*number, class));
+        *number = number.saturating_add(1);
+
+        let argument_position = match register {
+            Some(register) => Register(register.name.clone()),
+            None => DataPosition::StackOffset(arch.stack_argument_slot(&mut stack_size, &value_type)),
+        };
+
+        if arguments.iter().any(|(x, _)| *x == position) {
+            positions.insert(position, argument_position);
+        }
+    }
+
+    (positions, stack_size)
+}
+
+/// Store the registers at their offsets (from the stack pointer), whole registers of the same class that are next to each other are stored as a pair
+pub fn store_values(arch: &dyn NativeArch, values: Vec<(String, usize, ValueType)>) -> String {
+    access_values(arch, values, false)
+}
+
+/// Load the registers from their offsets (from the stack pointer), the counterpart of store_values
+pub fn load_values(arch: &dyn NativeArch, values: Vec<(String, usize, ValueType)>) -> String {
+    access_values(arch, values, true)
+}
+
+fn access_values(arch: &dyn NativeArch, mut values: Vec<(String, usize, ValueType)>, is_load: bool) -> String {
+    values.sort_by_key(|x| x.1);
+    let mut code = String::new();
+
+    let registers = arch.registers();
+    let class = |name: &str| registers.iter().find(|x| x.name == name).map(|x| x.class);
+
+    let mut i = 0;
+    while i < values.len() {
+        let (register, offset, value_type) = &values[i];
+
+        if let Some((next_register, next_offset, next_type)) = values.get(i + 1)
+            && *next_offset == offset + 8 && value_type.is_full_width(arch) && next_type.is_full_width(arch) && class(register) == class(next_register) {
+            code += match is_load {
+                true => arch.load_register_pair(register, next_register, *offset),
+                false => arch.store_register_pair(register, next_register, *offset),
+            }.as_str();
+            i += 2;
+            continue;
+        }
+
+        code += match is_load {
+            true => arch.load_register(register, *offset, value_type),
+            false => arch.store_register(register, *offset, value_type),
+        }.as_str();
+        i += 1;
+    }
+
+    code
+}
+
+/// Generate the code that moves values from their current positions (the second ones) to their new positions (the first ones)
+/// as if it all happened at once, so no value is overwritten before it's been moved.
+/// Every position may only be the destination of a single move. Stack positions are never shared between variables,
+/// so the stack position a value is moved to can't hold another value that's still needed.
+/// The registers indirect positions use to find their value may not be the destination of a move.
+/// The scratch register is used to break up cycles, so it can't be part of a move itself (other than staying where it is).
+pub fn move_values(arch: &dyn NativeArch, moves: Vec<(DataPosition, DataPosition, ValueType)>) -> String {
+    let mut code = String::new();
+
+    let scratch_register = scratch_register(arch);
+
+    if let Some((destination, source, _)) = moves.iter().find(|(destination, source, _)| destination != source && [destination, source].iter().any(|x| x.register_name().as_ref() == Some(&scratch_register))) {
+        exit(format!("The scratch register can't be moved, but it's part of the move from {:?} to {:?}.", source, destination), ExitCode::Internal);
+    }
+
+    // (destination, source)
+    let mut register_moves: Vec<(String, String)> = Vec::new();
+
+    // (destination, position in memory, type)
+    let mut loads: Vec<(String, DataPosition, ValueType)> = Vec::new();
+
+    // (register, stack offset, type), stored (or loaded) as pairs where possible
+    let mut stack_stores: Vec<(String, usize, ValueType)> = Vec::new();
+    let mut stack_loads: Vec<(String, usize, ValueType)> = Vec::new();
+
+    for (destination, source, value_type) in moves {
+        if destination == source { continue; }
+
+        match (destination, source) {
+            (Register(destination), Register(source)) => register_moves.push((destination, source)),
+            (Register(destination), DataPosition::StackOffset(offset)) => stack_loads.push((destination, offset, value_type)),
+            (Register(destination), source) => loads.push((destination, source, value_type)),
+            // Storing doesn't overwrite anything that's still needed, so it can happen before everything else
+            (DataPosition::StackOffset(offset), Register(source)) => stack_stores.push((source, offset, value_type)),
+            (destination, Register(source)) => code += store_position(arch, &source, &destination, &value_type).as_str(),
+            (destination, source) => {
+                code += load_position(arch, &scratch_register, &source, &value_type).as_str();
+                code += store_position(arch, &scratch_register, &destination, &value_type).as_str();
+            }
+        }
+    }
+
+    code += store_values(arch, stack_stores).as_str();
+
+    while !register_moves.is_empty() {
+        // A move can happen once no other move still needs to read its destination
+        let free_move = register_moves.iter().position(|(destination, _)| !register_moves.iter().any(|(_, source)| source == destination));
+
+        if let Some(free_move) = free_move {
+            let (destination, source) = register_moves.remove(free_move);
+            code += arch.move_register(&destination, &source).as_str();
+            continue;
+        }
+
+        // Only cycles are left. Break one up by parking a value in the scratch register,
+        // which frees up the register it's been in.
+        let source = register_moves[0].1.clone();
+        code += arch.move_register(&scratch_register, &source).as_str();
+        register_moves[0].1 = scratch_register.clone();
+    }
+
+    // Loading from memory can't overwrite anything that's still needed
+    code += load_values(arch, stack_loads).as_str();
+
+    for (destination, position, value_type) in loads {
+        code += load_position(arch, &destination, &position, &value_type).as_str();
+    }
+
+    code
+}
+
+/// Generate the code that loads the value (of the type) at the position into the register
+fn load_position(arch: &dyn NativeArch, register: &str, position: &DataPosition, value_type: &ValueType) -> String {
+    match position {
+        Register(source) => arch.move_register(register, source),
+        DataPosition::StackOffset(offset) => arch.load_register(register, *offset, value_type),
+        _ => {
+            let (code, base, offset) = indirect_base(arch, position);
+            code + arch.load_indirect(register, &base, offset, value_type).as_str()
+        }
+    }
+}
+
+/// Generate the code that stores the register (holding a value of the type) at the position
+fn store_position(arch: &dyn NativeArch, register: &str, position: &DataPosition, value_type: &ValueType) -> String {
+    match position {
+        Register(destination) => arch.move_register(destination, register),
+        DataPosition::StackOffset(offset) => arch.store_register(register, *offset, value_type),
+        _ => {
+            let (code, base, offset) = indirect_base(arch, position);
+
+            if !code.is_empty() && register == scratch_register(arch) {
+                exit(format!("The scratch register can't be stored at {:?}, as it's needed to find the position.", position), ExitCode::Internal);
+            }
+
+            code + arch.store_indirect(register, &base, offset, value_type).as_str()
+        }
+    }
+}
+
+/// Generate the code that gets the address of an indirect position into a register.
+/// Returns the code, the register and the offset from the address in the register.
+fn indirect_base(arch: &dyn NativeArch, position: &DataPosition) -> (String, String, i64) {
+    let scratch_register = scratch_register(arch);
+
+    match position {
+        DataPosition::Heap(address, offset) => match address.as_ref() {
+            Register(base) => (String::new(), base.clone(), *offset),
+            address => (load_position(arch, &scratch_register, address, &ValueType::default()), scratch_register, *offset),
+        },
+        DataPosition::StackOffsetAt(offset) => {
+            let mut code = String::new();
+
+            let offset_register = match offset.as_ref() {
+                Register(register) => register.clone(),
+                offset => {
+                    code += load_position(arch, &scratch_register, offset, &ValueType::default()).as_str();
+                    scratch_register.clone()
+                }
+            };
+
+            code += arch.stack_address(&scratch_register, &RegisterOrImmediate::Register(offset_register)).as_str();
+            (code, scratch_register, 0)
+        }
+        _ => exit(format!("{:?} isn't an indirect position.", position), ExitCode::Internal),
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use std::collections::HashMap;
+    use crate::compiler::low_level::arch::aarch64::aarch64::{AArch64, AArch64Os};
+    use crate::compiler::low_level::arch::function_gen::move_values;
+    use crate::compiler::low_level::data_position::DataPosition;
+    use crate::compiler::low_level::data_position::DataPosition::{Register, StackOffset};
+    use crate::compiler::low_level::variable::ValueType;
+
+    /// The registers and stack slots a piece of AArch64 code that only moves, loads and stores whole registers leaves behind
+    fn simulate(code: &str, registers: &mut HashMap<String, i64>, stack: &mut HashMap<usize, i64>) {
+        // x0/w0 and v0/d0 are the same registers
+        let register = |name: &str| match &name[..1] {
+            "w" => format!("x{}", &name[1..]),
+            "d" => format!("v{}", &name[1..]),
+            _ => name.to_string(),
+        };
+
+        for line in code.lines() {
+            let (instruction, operands) = line.split_once('\t').unwrap();
+            let (first, second) = operands.split_once(", ").unwrap();
+
+            match instruction {
+                "mov" | "fmov" => {
+                    let value = registers[&register(second)];
+                    registers.insert(register(first), value);
+                }
+                "ldr" | "str" | "ldp" | "stp" => {
+                    // Pairs access the second register 8B above the first one
+                    let accesses: Vec<(&str, usize, &str)> = match second.split_once(", [sp, #") {
+                        Some((second, offset)) => {
+                            let offset: usize = offset.strip_suffix(']').unwrap().parse().unwrap();
+                            vec![(first, offset, &instruction[..2]), (second, offset + 8, &instruction[..2])]
+                        }
+                        None => vec![(first, second.strip_prefix("[sp, #").and_then(|x| x.strip_suffix(']')).unwrap().parse().unwrap(), &instruction[..2])],
+                    };
+
+                    for (name, offset, kind) in accesses {
+                        match kind {
+                            "ld" => { registers.insert(register(name), stack[&offset]); }
+                            _ => { stack.insert(offset, registers[&register(name)]); }
+                        }
+                    }
+                }
+                _ => panic!("Unexpected instruction: {}", line),
+            }
+        }
+    }
+
+    /// Move the values (destination, source) and check that every destination got the value of its source
+    /// and nothing else but the scratch register changed
+    fn check_moves(arch: &AArch64, moves: &[(DataPosition, DataPosition)], registers: &[String]) -> String {
+        let mut register_values: HashMap<String, i64> = registers.iter().chain([&"x8".to_string()]).enumerate().map(|(i, x)| (x.clone(), i as i64)).collect();
+        let mut stack_values: HashMap<usize, i64> = (0..32).map(|i| (i * 8, 1000 + i as i64)).collect();
+
+        let mut expected: Vec<(DataPosition, i64)> = moves.iter().map(|(destination, source)| (destination.clone(), value_at(source, &register_values, &stack_values))).collect();
+        expected.extend(registers.iter().map(|x| Register(x.clone())).chain((0..32).map(|x| StackOffset(x * 8)))
+            .filter(|x| !moves.iter().any(|(destination, _)| destination == x))
+            .map(|x| { let value = value_at(&x, &register_values, &stack_values); (x, value) }));
+
+        let code = move_values(arch, moves.iter().map(|(destination, source)| (destination.clone(), source.clone(), ValueType::default())).collect());
+        simulate(&code, &mut register_values, &mut stack_values);
+
+        for (position, value) in expected {
+            assert_eq!(value_at(&position, &register_values, &stack_values), value, "{:?} is wrong after {:?}:\n{}", position, moves, code);
+        }
+
+        code
+    }
+
+    /// The value the simulation has at the position
+    fn value_at(position: &DataPosition, registers: &HashMap<String, i64>, stack: &HashMap<usize, i64>) -> i64 {
+        match position {
+            Register(name) => registers[name],
+            StackOffset(offset) => stack[offset],
+            _ => unreachable!(),
+        }
+    }
+
+    /// All permutations of 0..n
+    fn permutations(n: usize) -> Vec<Vec<usize>> {
+        if n == 0 { return vec![vec![]]; }
+
+        permutations(n - 1).into_iter().flat_map(|permutation| (0..n).map(move |i| {
+            let mut permutation = permutation.clone();
+            permutation.insert(i, n - 1);
+            permutation
+        })).collect()
+    }
+
+    #[test]
+    fn test_permutations(){
+        let aarch64 = AArch64::new(AArch64Os::Linux);
+        let registers: Vec<String> = ["x0", "x1", "x2", "x3", "v0"].iter().map(|x| x.to_string()).collect();
+
+        // The values in the first two slots go to the registers or to the other two slots (a stack slot that's moved to can't hold anything else)
+        let sources: Vec<DataPosition> = registers.iter().map(|x| Register(x.clone())).chain([StackOffset(0), StackOffset(8)]).collect();
+        let destinations: Vec<DataPosition> = registers.iter().map(|x| Register(x.clone())).chain([StackOffset(16), StackOffset(24)]).collect();
+
+        for permutation in permutations(sources.len()) {
+            let moves: Vec<(DataPosition, DataPosition)> = permutation.iter().enumerate().map(|(i, &x)| (destinations[x].clone(), sources[i].clone())).collect();
+            let code = check_moves(&aarch64, &moves, &registers);
+
+            // Every register that isn't moved to itself takes one move, every cycle of registers one more
+            let mut cycles = 0;
+            let mut visited = vec![false; registers.len()];
+
+            for start in 0..registers.len() {
+                let mut length = 0;
+                let mut current = start;
+
+                while current < registers.len() && !visited[current] {
+                    visited[current] = true;
+                    current = permutation[current];
+                    length += 1;
+                }
+
+                if current == start && length > 1 { cycles += 1; }
+            }
+
+            let register_moves = (0..registers.len()).filter(|&x| permutation[x] < registers.len() && permutation[x] != x).count();
+            assert_eq!(code.lines().filter(|x| !x.contains("[sp")).count(), register_moves + cycles, "{:?}:\n{}", permutation, code);
+        }
+    }
+
+    #[test]
+    fn test_random_moves(){
+        let aarch64 = AArch64::new(AArch64Os::MacOs);
+        let registers: Vec<String> = (0..16).filter(|&x| x != 8).map(|x| format!("x{}", x)).chain((0..8).map(|x| format!("v{}", x))).collect();
+        let positions: Vec<DataPosition> = registers.iter().map(|x| Register(x.clone())).chain((0..32).map(|x| StackOffset(x * 8))).collect();
+
+        // xorshift, so the moves are the same every time
+        let mut state: u64 = 0x2545F4914F6CDD1D;
+        let mut random = |limit: usize| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % limit as u64) as usize
+        };
+
+        for _ in 0..5000 {
+            // Shuffle the positions, the first ones are read from, the others may be moved to
+            let mut shuffled = positions.clone();
+            for i in (1..shuffled.len()).rev() {
+                let j = random(i + 1);
+                shuffled.swap(i, j);
+            }
+
+            let source_count = random(12) + 1;
+            let sources = &shuffled[..source_count];
+
+            // Registers may also be moved to while they're read from, the same value may be moved to several positions
+            let mut moves: Vec<(DataPosition, DataPosition)> = Vec::new();
+            for destination in shuffled.iter().filter(|x| matches!(x, Register(_)) || !sources.contains(x)).take(random(16) + 1) {
+                moves.push((destination.clone(), sources[random(source_count)].clone()));
+            }
+
+            check_moves(&aarch64, &moves, &registers);
+        }
+    }
+}
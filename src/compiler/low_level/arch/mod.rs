@@ -1,3 +1,10 @@
+#[allow(clippy::module_inception)]
 pub mod arch;
-pub mod aarch64_mac_os;
-mod register;
\ No newline at end of file
+pub mod aarch64;
+pub mod x86_64_linux;
+pub mod riscv64_linux;
+pub mod wasm32;
+pub mod c;
+pub mod register;
+mod function_gen;
+mod variable_manager;
\ No newline at end of file
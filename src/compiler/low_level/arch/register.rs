@@ -3,30 +3,35 @@ This is a helper struct to find the correct register usage later.
 Individual architectures might implement register/stack/heap selection handling themselves.
  */
 
+use crate::compiler::low_level::variable::{ValueKind, ValueType};
+
 #[derive(Clone, PartialEq, Hash, Eq)]
 pub struct Register{
     pub name: String,
     pub size_bits: u8,
     pub saver: RegisterSaver,
     pub tags: Vec<RegisterTag>,
+    pub class: RegisterClass,
 }
 
 impl Register{
     pub fn new(name: String, size_bits: u8, saver: RegisterSaver, tags: Vec<RegisterTag>) -> Register{
-        Register { name, size_bits, saver, tags }
+        Register { name, size_bits, saver, tags, class: RegisterClass::Integer }
+    }
+
+    /// The same register, but of another class (registers are integer registers by default)
+    pub fn with_class(mut self, class: RegisterClass) -> Register {
+        self.class = class;
+        self
     }
 
-    pub fn is_argument(&self, number: u8) -> bool {
+    /// Whether the register holds the n-th argument of its class (each class counts its arguments on its own)
+    pub fn is_argument(&self, number: u8, class: RegisterClass) -> bool {
         let mut is_nth_argument = false;
         self.tags.iter().for_each(| x |
             {
-                match x.clone(){
-                    RegisterTag::Argument(n) => {
-                        if n == number{
-                            is_nth_argument = true;
-                        }
-                    }
-                    _ => {}
+                if let RegisterTag::Argument(n) = x.clone() && n == number && self.class == class {
+                    is_nth_argument = true;
                 }
             }
         );
@@ -35,6 +40,23 @@ impl Register{
     }
 }
 
+/// The kind of values a register can hold, a value never goes into a register of another class
+#[derive(Clone, Copy, PartialEq, Hash, Eq, Debug)]
+pub enum RegisterClass {
+    Integer,    // General purpose registers (integers and addresses)
+    Float,      // Floating point (and SIMD) registers
+}
+
+impl RegisterClass {
+    /// The class of registers values of the type are kept in
+    pub fn of(value_type: &ValueType) -> RegisterClass {
+        match value_type.kind {
+            ValueKind::Float => RegisterClass::Float,
+            _ => RegisterClass::Integer,
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, Hash, Eq)]
 pub enum RegisterSaver{
     Caller,     // caller-saved register
@@ -46,9 +68,11 @@ pub enum RegisterSaver{
 #[derive(Clone, PartialEq, Hash, Eq)]
 pub enum RegisterTag {
     Argument(/*(n-th argument) n=*/u8),
+    ReturnValue,            // Where a function leaves its result
     GeneralPurpose,
     Scratch,
     StackPointer,
     FramePointer,
+    LinkRegister,
     NoModify
 }
\ No newline at end of file
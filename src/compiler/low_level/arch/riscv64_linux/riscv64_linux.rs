@@ -0,0 +1,437 @@
+use crate::compiler::low_level::arch::arch::{Arch, NativeArch, RegisterOrImmediate};
+use crate::compiler::low_level::arch::function_gen::{generate_constants, generate_function};
+use crate::compiler::low_level::macro_function::MacroFunction;
+use crate::compiler::low_level::macro_instruction::MacroInstruction;
+use crate::compiler::low_level::operation::{Operation, UnaryOperation};
+use crate::compiler::low_level::arch::register::*;
+use crate::compiler::low_level::variable::{ValueKind, ValueType};
+use crate::util::exit::{exit, ExitCode};
+
+/// RV64GC using the LP64D ABI (Linux), the assembly is written for GNU as
+pub struct RiscV64Linux {
+    pub registers: Vec<Register>
+}
+
+impl RiscV64Linux {
+    pub const NAME: &'static str = "riscv64-linux";
+    pub const BITS_COUNT: u8 = 64;
+
+    /// The largest offset loads and stores can encode directly (it's a signed 12-bit immediate)
+    const MAX_IMMEDIATE_OFFSET: usize = 2047;
+
+    /// The register that's used whenever an instruction needs an extra register
+    const SCRATCH_REGISTER: &'static str = "t6";
+
+    pub fn new() -> Self {
+        RiscV64Linux {
+            registers: vec![
+                Register::new("zero".to_string(), 64, RegisterSaver::OS, vec![RegisterTag::NoModify]),
+                Register::new("ra".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::LinkRegister]),
+                Register::new("sp".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::StackPointer]),
+                Register::new("gp".to_string(), 64, RegisterSaver::OS, vec![RegisterTag::NoModify]),
+                Register::new("tp".to_string(), 64, RegisterSaver::OS, vec![RegisterTag::NoModify]),
+                Register::new("a0".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::Argument(0), RegisterTag::ReturnValue, RegisterTag::GeneralPurpose]),
+                Register::new("a1".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::Argument(1), RegisterTag::GeneralPurpose]),
+                Register::new("a2".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::Argument(2), RegisterTag::GeneralPurpose]),
+                Register::new("a3".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::Argument(3), RegisterTag::GeneralPurpose]),
+                Register::new("a4".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::Argument(4), RegisterTag::GeneralPurpose]),
+                Register::new("a5".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::Argument(5), RegisterTag::GeneralPurpose]),
+                Register::new("a6".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::Argument(6), RegisterTag::GeneralPurpose]),
+                Register::new("a7".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::Argument(7), RegisterTag::GeneralPurpose]),
+                Register::new("t0".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::GeneralPurpose]),
+                Register::new("t1".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::GeneralPurpose]),
+                Register::new("t2".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::GeneralPurpose]),
+                Register::new("t3".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::GeneralPurpose]),
+                Register::new("t4".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::GeneralPurpose]),
+                Register::new("t5".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::GeneralPurpose]),
+                Register::new("t6".to_string(), 64, RegisterSaver::None, vec![RegisterTag::Scratch]),
+                Register::new("s0".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::FramePointer]),
+                Register::new("s1".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]),
+                Register::new("s2".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]),
+                Register::new("s3".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]),
+                Register::new("s4".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]),
+                Register::new("s5".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]),
+                Register::new("s6".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]),
+                Register::new("s7".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]),
+                Register::new("s8".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]),
+                Register::new("s9".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]),
+                Register::new("s10".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]),
+                Register::new("s11".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]),
+            ]
+        }
+    }
+
+    /// LP64D passes floating point values in the F/D registers, which aren't modeled, so they'd end up in the wrong registers
+    fn check_types(function: &MacroFunction) {
+        for instruction in &function.instructions {
+            let MacroInstruction::DeclareVariable(variable) = instruction else { continue; };
+
+            if variable.value_type.kind == ValueKind::Float {
+                exit(format!("Variable \"{}\" of function \"{}\" is of type {}, floating point values aren't supported on riscv64 yet.", variable.full_name, function.name, variable.value_type.name()), ExitCode::Internal);
+            }
+        }
+    }
+
+    /// The instruction that loads a value of the type (extended to the whole register)
+    fn load_instruction(&self, value_type: &ValueType) -> &'static str {
+        match (value_type.bytes(self), value_type.is_signed()) {
+            (8, _) => "ld",
+            (1, true) => "lb",
+            (2, true) => "lh",
+            (_, true) => "lw",
+            (1, false) => "lbu",
+            (2, false) => "lhu",
+            (_, false) => "lwu",
+        }
+    }
+
+    /// The instruction that stores a value of the type
+    fn store_instruction(&self, value_type: &ValueType) -> &'static str {
+        match value_type.bytes(self) {
+            1 => "sb",
+            2 => "sh",
+            4 => "sw",
+            _ => "sd",
+        }
+    }
+
+    /// Loads and stores only take 12-bit offsets, larger ones are added to the base (in the scratch register) first.
+    /// Returns the code that has to come first, the base and the offset to use.
+    fn indirect_base(base: &str, offset: i64) -> (String, String, i64) {
+        if (-2048..2048).contains(&offset) {
+            return (String::new(), base.to_string(), offset);
+        }
+
+        if base == Self::SCRATCH_REGISTER {
+            exit(format!("The offset {} can't be used with the scratch register as the base.", offset), ExitCode::Internal);
+        }
+
+        let code = format!("li\t{}, {}\nadd\t{}, {}, {}\n", Self::SCRATCH_REGISTER, offset, Self::SCRATCH_REGISTER, Self::SCRATCH_REGISTER, base);
+        (code, Self::SCRATCH_REGISTER.to_string(), 0)
+    }
+
+    /// Like indirect_base, but the register that's stored can't be the scratch register if that's needed for the offset
+    fn store_base(register: &str, base: &str, offset: i64) -> (String, String, i64) {
+        if register == Self::SCRATCH_REGISTER && !(-2048..2048).contains(&offset) {
+            exit(format!("The scratch register can't be stored at the offset {}, as it's needed to find the position.", offset), ExitCode::Internal);
+        }
+
+        Self::indirect_base(base, offset)
+    }
+}
+
+impl Arch for RiscV64Linux {
+    fn name(&self) -> String {
+        Self::NAME.to_string()
+    }
+
+    fn architecture_bits(&self) -> u8 {
+        Self::BITS_COUNT
+    }
+
+    fn generate_assembly(&self, functions: Vec<MacroFunction>) -> String {
+        let mut assembly = ".text\n".to_string();
+
+        let mut constants: Vec<Vec<u8>> = Vec::new();
+
+        for function in functions {
+            Self::check_types(&function);
+            assembly += generate_function(self, &function.name, function.instructions, &mut constants).as_str();
+        }
+
+        assembly += generate_constants(self, &constants).as_str();
+
+        // Tell the linker the stack doesn't need to be executable
+        assembly += ".section\t.note.GNU-stack,\"\",@progbits\n";
+
+        assembly
+    }
+}
+
+impl NativeArch for RiscV64Linux {
+    fn registers(&self) -> Vec<Register> {
+        self.registers.clone()
+    }
+
+    fn symbol(&self, function_name: &str) -> String {
+        function_name.to_string()
+    }
+
+    fn function_header(&self, symbol: &str) -> String {
+        format!(".globl\t{}\n.p2align\t1\n.type\t{}, @function\n", symbol, symbol)
+    }
+
+    fn prologue(&self, frame_size: usize) -> String {
+        // Save the return address and the frame pointer at the top of the frame (like aarch64 does)
+        let mut code = "addi\tsp, sp, -16\n".to_string();
+        code += "sd\tra, 8(sp)\n";
+        code += "sd\ts0, 0(sp)\n";
+        code += "addi\ts0, sp, 16\n";
+
+        if frame_size > Self::MAX_IMMEDIATE_OFFSET + 1 {
+            // addi can't subtract that much, nothing lives in t0 at the start of a function though
+            code += format!("li\tt0, {}\n", frame_size).as_str();
+            code += "sub\tsp, sp, t0\n";
+        } else if frame_size > 0 {
+            code += format!("addi\tsp, sp, -{}\n", frame_size).as_str();
+        }
+
+        code
+    }
+
+    fn epilogue(&self, _frame_size: usize) -> String {
+        // The frame pointer knows where the frame started, no matter how large it is
+        let mut code = "addi\tsp, s0, -16\n".to_string();
+        code += "ld\tra, 8(sp)\n";
+        code += "ld\ts0, 0(sp)\n";
+        code += "addi\tsp, sp, 16\n";
+        code += "ret\n";
+
+        code
+    }
+
+    fn return_without_frame(&self) -> String {
+        "ret\n".to_string()
+    }
+
+    fn incoming_arguments_offset(&self) -> i64 {
+        // The frame pointer is the stack pointer of the caller
+        0
+    }
+
+    fn call(&self, symbol: &str) -> String {
+        format!("call\t{}\n", symbol)
+    }
+
+    fn local_label(&self, name: &str) -> String {
+        format!(".L{}", name)
+    }
+
+    fn jump(&self, label: &str) -> String {
+        format!("j\t{}\n", label)
+    }
+
+    fn branch_if_not_zero(&self, register: &str, label: &str) -> String {
+        // The assembler turns this into a jump around a jump if the label is too far away
+        format!("bnez\t{}, {}\n", register, label)
+    }
+
+    fn move_register(&self, destination: &str, source: &str) -> String {
+        format!("mv\t{}, {}\n", destination, source)
+    }
+
+    fn store_register(&self, register: &str, stack_offset: usize, value_type: &ValueType) -> String {
+        let (prefix, base, offset) = Self::store_base(register, "sp", stack_offset as i64);
+        prefix + format!("{}\t{}, {}({})\n", self.store_instruction(value_type), register, offset, base).as_str()
+    }
+
+    fn load_register(&self, register: &str, stack_offset: usize, value_type: &ValueType) -> String {
+        let (prefix, base, offset) = Self::indirect_base("sp", stack_offset as i64);
+        prefix + format!("{}\t{}, {}({})\n", self.load_instruction(value_type), register, offset, base).as_str()
+    }
+
+    fn load_indirect(&self, register: &str, base: &str, offset: i64, value_type: &ValueType) -> String {
+        let (prefix, base, offset) = Self::indirect_base(base, offset);
+        prefix + format!("{}\t{}, {}({})\n", self.load_instruction(value_type), register, offset, base).as_str()
+    }
+
+    fn store_indirect(&self, register: &str, base: &str, offset: i64, value_type: &ValueType) -> String {
+        let (prefix, base, offset) = Self::store_base(register, base, offset);
+        prefix + format!("{}\t{}, {}({})\n", self.store_instruction(value_type), register, offset, base).as_str()
+    }
+
+    fn extend(&self, register: &str, value_type: &ValueType) -> String {
+        // sext.w and andi only cover signed double words and unsigned bytes, other values are shifted up to the top and back down again
+        let unused_bits = 64 - value_type.bits(self);
+
+        match (unused_bits, value_type.is_signed()) {
+            (0, _) => String::new(),
+            (32, true) => format!("sext.w\t{}, {}\n", register, register),
+            (56, false) => format!("andi\t{}, {}, 255\n", register, register),
+            (_, signed) => format!("slli\t{}, {}, {}\n{}\t{}, {}, {}\n", register, register, unused_bits, if signed { "srai" } else { "srli" }, register, register, unused_bits),
+        }
+    }
+
+    fn stack_address(&self, destination: &str, offset: &RegisterOrImmediate) -> String {
+        match offset {
+            RegisterOrImmediate::Register(register) => format!("add\t{}, sp, {}\n", destination, register),
+            RegisterOrImmediate::Immediate(value) if (-2048..2048).contains(value) => format!("addi\t{}, sp, {}\n", destination, value),
+            RegisterOrImmediate::Immediate(value) => format!("li\t{}, {}\nadd\t{}, sp, {}\n", Self::SCRATCH_REGISTER, value, destination, Self::SCRATCH_REGISTER),
+        }
+    }
+
+    fn load_immediate(&self, register: &str, value: i64) -> String {
+        // The assembler picks the instructions (lui/addi/slli/...) itself
+        format!("li\t{}, {}\n", register, value)
+    }
+
+    fn load_address(&self, register: &str, label: &str) -> String {
+        // lla is auipc + addi (relative to the pc)
+        format!("lla\t{}, {}\n", register, label)
+    }
+
+    fn constant_section(&self, _is_c_string: bool) -> String {
+        ".section\t.rodata\n".to_string()
+    }
+
+    fn immediate_fits(&self, operation: Operation, value: i64) -> bool {
+        let fits_12_bits = (-2048..2048).contains(&value);
+
+        match operation {
+            Operation::Add | Operation::And | Operation::Or | Operation::Xor | Operation::LessSigned | Operation::LessUnsigned => fits_12_bits,
+            // Subtracting is adding the negated value
+            Operation::Subtract => (-2047..=2048).contains(&value),
+            Operation::ShiftLeft | Operation::ShiftRightArithmetic | Operation::ShiftRightLogical => true,
+            _ => false,
+        }
+    }
+
+    fn compute(&self, operation: Operation, destination: &str, left: &str, right: &RegisterOrImmediate) -> String {
+        let scratch = Self::SCRATCH_REGISTER;
+
+        let right = match right {
+            RegisterOrImmediate::Register(register) => register,
+            RegisterOrImmediate::Immediate(value) => {
+                let (instruction, value) = match operation {
+                    Operation::Add => ("addi", *value),
+                    Operation::Subtract => ("addi", -*value),
+                    Operation::And => ("andi", *value),
+                    Operation::Or => ("ori", *value),
+                    Operation::Xor => ("xori", *value),
+                    Operation::LessSigned => ("slti", *value),
+                    Operation::LessUnsigned => ("sltiu", *value),
+                    Operation::ShiftLeft => ("slli", *value & 63),
+                    Operation::ShiftRightArithmetic => ("srai", *value & 63),
+                    _ => ("srli", *value & 63),
+                };
+
+                return format!("{}\t{}, {}, {}\n", instruction, destination, left, value);
+            }
+        };
+
+        let instruction = match operation {
+            Operation::Add => "add",
+            Operation::Subtract => "sub",
+            Operation::Multiply => "mul",
+            Operation::DivideSigned => "div",
+            Operation::DivideUnsigned => "divu",
+            Operation::RemainderSigned => "rem",
+            Operation::RemainderUnsigned => "remu",
+            Operation::And => "and",
+            Operation::Or => "or",
+            Operation::Xor => "xor",
+            Operation::ShiftLeft => "sll",
+            Operation::ShiftRightArithmetic => "sra",
+            Operation::ShiftRightLogical => "srl",
+            Operation::LessSigned => "slt",
+            Operation::LessUnsigned => "sltu",
+            Operation::GreaterSigned => return format!("slt\t{}, {}, {}\n", destination, right, left),
+            Operation::GreaterUnsigned => return format!("sltu\t{}, {}, {}\n", destination, right, left),
+            _ => {
+                // There are only "set if less than" instructions, everything else is built from them (or from the difference)
+                let comparison = match operation {
+                    Operation::Equal => format!("xor\t{}, {}, {}\nseqz\t{}, {}\n", scratch, left, right, destination, scratch),
+                    Operation::NotEqual => format!("xor\t{}, {}, {}\nsnez\t{}, {}\n", scratch, left, right, destination, scratch),
+                    Operation::LessOrEqualSigned => format!("slt\t{}, {}, {}\nxori\t{}, {}, 1\n", scratch, right, left, destination, scratch),
+                    Operation::GreaterOrEqualSigned => format!("slt\t{}, {}, {}\nxori\t{}, {}, 1\n", scratch, left, right, destination, scratch),
+                    Operation::LessOrEqualUnsigned => format!("sltu\t{}, {}, {}\nxori\t{}, {}, 1\n", scratch, right, left, destination, scratch),
+                    _ => format!("sltu\t{}, {}, {}\nxori\t{}, {}, 1\n", scratch, left, right, destination, scratch),
+                };
+
+                return comparison;
+            }
+        };
+
+        format!("{}\t{}, {}, {}\n", instruction, destination, left, right)
+    }
+
+    fn compute_unary(&self, operation: UnaryOperation, destination: &str, source: &str) -> String {
+        match operation {
+            UnaryOperation::Negate => format!("neg\t{}, {}\n", destination, source),
+            UnaryOperation::Not => format!("not\t{}, {}\n", destination, source),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use crate::compiler::low_level::arch::arch::{Arch, NativeArch};
+    use crate::compiler::low_level::arch::riscv64_linux::riscv64_linux::RiscV64Linux;
+    use crate::compiler::low_level::ir_text::parse_ir;
+    use crate::compiler::low_level::macro_function::MacroFunction;
+    use crate::compiler::low_level::macro_instruction::MacroInstruction;
+    use crate::compiler::low_level::variable::{ValueType, Variable};
+
+    #[test]
+    fn test_generate_assembly(){
+        let riscv64 = RiscV64Linux::new();
+
+        let size = Variable::new("my_app:main.rsl:Main:size".to_string(), vec![]);
+        let count = Variable::new("my_app:main.rsl:Main:count".to_string(), vec![]);
+
+        // Swap the first two arguments and pass them on
+        let instructions: Vec<MacroInstruction> = vec![
+            MacroInstruction::DeclareVariable(size.clone()),
+            MacroInstruction::DeclareVariable(count.clone()),
+            MacroInstruction::GetArgument(size.clone(), 0),
+            MacroInstruction::GetArgument(count.clone(), 1),
+            MacroInstruction::UseVariableAsArgument(count.clone(), 0),
+            MacroInstruction::UseVariableAsArgument(size.clone(), 1),
+            MacroInstruction::CallFunction("calloc".to_string(), 2, None, None),
+            MacroInstruction::DestroyVariable(size.clone()),
+            MacroInstruction::DestroyVariable(count.clone()),
+        ];
+
+        let assembly = riscv64.generate_assembly(vec![MacroFunction::new("main".to_string(), 2, false, instructions)]);
+        println!("{}", assembly);
+
+        assert!(assembly.contains("main:\naddi\tsp, sp, -16\nsd\tra, 8(sp)\nsd\ts0, 0(sp)\n"));
+        assert!(assembly.contains("call\tcalloc\n"));
+        assert!(assembly.contains("ld\tra, 8(sp)\nld\ts0, 0(sp)\naddi\tsp, sp, 16\nret\n"));
+
+        // The values of a0 and a1 need to be swapped through the scratch register
+        assert!(assembly.contains("mv\tt6, a"));
+    }
+
+    #[test]
+    fn test_large_offsets(){
+        let riscv64 = RiscV64Linux::new();
+        let default = ValueType::default();
+
+        // Offsets that don't fit into 12 bits are added to the stack pointer in the scratch register first
+        assert_eq!(riscv64.store_register("a0", 2040, &default), "sd\ta0, 2040(sp)\n");
+        assert_eq!(riscv64.store_register("a0", 4096, &default), "li\tt6, 4096\nadd\tt6, t6, sp\nsd\ta0, 0(t6)\n");
+        assert_eq!(riscv64.load_register("s1", 2048, &ValueType::named("u8").unwrap()), "li\tt6, 2048\nadd\tt6, t6, sp\nlbu\ts1, 0(t6)\n");
+    }
+
+    #[test]
+    fn test_passed_survivors(){
+        let (functions, diagnostics) = parse_ir("test.rslir".to_string(), "
+            function @main(0) -> value {
+                declare %a
+                declare %b
+                declare %r
+                %a = immediate 5
+                %b = immediate 7
+                %r = call @id, 0
+                argument %a, 0
+                %r = call @id, 1
+                argument %b, 0
+                %r = call @id, 1
+                %r = add %r, %a
+                %r = add %r, %b
+                return %r
+            }
+        ");
+        assert_eq!(diagnostics, vec![]);
+
+        let assembly = RiscV64Linux::new().generate_assembly(functions);
+        println!("{}", assembly);
+
+        // a and b are in callee-saved registers since the first call, passing them doesn't need another copy
+        let calls: Vec<&str> = assembly.split("call\tid\n").collect();
+        assert_eq!(calls.len(), 4);
+        assert!(calls[1].starts_with("mv\ta0, s") && calls[1].lines().count() == 1);
+        assert!(calls[2].starts_with("mv\ta0, s") && calls[2].lines().count() == 1);
+    }
+}
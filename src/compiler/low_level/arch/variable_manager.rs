@@ -0,0 +1,456 @@
+use std::collections::HashMap;
+use crate::compiler::low_level::arch::arch::NativeArch;
+use crate::compiler::low_level::arch::function_gen::{allocate_stack_slot, argument_positions, call_arguments, fixed_argument_count, load_values, move_values, store_values};
+use crate::compiler::low_level::arch::register::{Register, RegisterClass, RegisterSaver, RegisterTag};
+use crate::compiler::low_level::data_position::DataPosition;
+use crate::compiler::low_level::liveness::Liveness;
+use crate::compiler::low_level::macro_instruction::MacroInstruction;
+use crate::compiler::low_level::variable::{ValueType, Variable};
+
+/// Move the variables to where the call that starts at the index needs them.
+/// Variables that are passed go to their argument registers, the others keep their position if possible,
+/// the ones that are read soonest being the most important. Dead variables give up their registers.
+/// The call overwrites the caller-saved registers, so the values that are still needed afterwards go to callee-saved registers
+/// (or the stack if there are none left). Passed values that are needed afterwards keep a copy there as well.
+/// All the values are moved at once (see move_values), so any order of the arguments works.
+/// Returns the code and the variables that have been spilled for the call, so they can be loaded again afterwards (see reload_after_call).
+pub fn order_variable_locations(arch: &dyn NativeArch, variables: &mut [Variable], registers: Vec<Register>, instructions: &[MacroInstruction], index: usize, liveness: &Liveness, stack_offset: &mut usize) -> (String, Vec<String>){
+    // Variables, where they should be, where they are and the inverse of the relevance they get to their target position (basically a bit like nice on unix-like systems)
+    let mut variables_info: Vec<(Variable, DataPosition, usize)> = Vec::new();
+
+    // The code that will be used to move the variables around
+    let mut code = String::new();
+
+    // The arguments of the call by their position: the variable, the argument register it goes to and how far away it's used.
+    // Variables that aren't passed to a function call have no reason to stay in its argument registers,
+    // so calls themselves don't pin anything.
+    let mut argument_slots: HashMap<usize, (String, DataPosition, usize)> = HashMap::new();
+
+    // The type of a variable decides where it's passed
+    let type_of = |full_name: &str| variables.iter().find(|x| x.full_name == full_name).map(|x| x.value_type.clone()).unwrap_or_default();
+
+    let call_arguments = call_arguments(instructions, index);
+    let (argument_positions, _) = argument_positions(arch, &call_arguments.iter().map(|(arg_pos, variable)| (*arg_pos, type_of(&variable.full_name))).collect::<Vec<_>>(), fixed_argument_count(instructions, index));
+
+    // The arguments that don't fit within the registers reserved for arguments are copied to their slot at the bottom of the frame right away,
+    // which only ever needs the scratch register
+    let mut stack_arguments: Vec<(DataPosition, DataPosition, ValueType)> = Vec::new();
+
+    for (distance, (arg_pos, variable)) in call_arguments.iter().enumerate() {
+        match &argument_positions[arg_pos] {
+            DataPosition::Register(argument_register) => {
+                argument_slots.insert(*arg_pos, (variable.full_name.clone(), DataPosition::Register(argument_register.clone()), distance));
+            }
+            slot => {
+                let Some(variable) = variables.iter().find(|x| x.full_name == variable.full_name) else { continue; };
+                let Some(position) = variable.get_cheapest_position() else { continue; };
+
+                stack_arguments.push((slot.clone(), position, variable.value_type.clone()));
+            }
+        }
+    }
+
+    // A variable that's passed more than once goes to the register of the argument that's used first,
+    // the other argument registers get a copy once everything is in place.
+    let mut arguments: HashMap<String, (DataPosition, usize)> = HashMap::new();
+    let mut argument_copies: Vec<(String, String)> = Vec::new();
+
+    let mut slots: Vec<&(String, DataPosition, usize)> = argument_slots.values().collect();
+    slots.sort_by_key(|x| x.2);
+
+    for (name, position, distance) in slots {
+        match arguments.contains_key(name) {
+            true => argument_copies.push((position.register_name().unwrap(), name.clone())),
+            false => { arguments.insert(name.clone(), (position.clone(), *distance)); }
+        }
+    }
+
+    code += move_values(arch, stack_arguments).as_str();
+
+    // Everything that's alive after the call (except for its result, which the call sets) has to survive it.
+    // The pseudo-variables of the code generator (e.g. the arguments that haven't been claimed yet) are unknown to the liveness, so they're never dead.
+    let call_index = index + call_arguments.len();
+    let result = match instructions.get(call_index) {
+        Some(MacroInstruction::CallFunction(_, _, _, result)) => result.as_ref().map(|x| x.full_name.clone()),
+        _ => None,
+    };
+    let crosses_call = |name: &str| Some(name) != result.as_deref() && !liveness.is_dead_after(call_index, name);
+    let is_caller_saved = |name: &str| registers.iter().any(|x| x.name == name && x.saver == RegisterSaver::Caller);
+
+    // The values that are read after the call and are only in caller-saved registers now, they're loaded again after the call if they end up on the stack
+    let endangered: Vec<String> = variables.iter()
+        .filter(|x| crosses_call(&x.full_name) && liveness.next_use(call_index + 1, &x.full_name).is_some())
+        .filter(|x| !x.positions.is_empty() && x.positions.iter().all(|x| x.register_name().is_some_and(|x| is_caller_saved(&x))))
+        .map(|x| x.full_name.clone()).collect();
+
+    for variable in variables.iter_mut() {
+        if let Some((target_position, distance)) = arguments.get(&variable.full_name) {
+            variables_info.push((variable.clone(), target_position.clone(), *distance));
+            continue;
+        }
+
+        // Nothing is going to read the value anymore, so there's no reason to keep it in a register (or to store it)
+        if liveness.is_dead(index, &variable.full_name) {
+            variable.positions.retain(|x| x.register_name().is_none());
+            continue;
+        }
+
+        // Still add it somewhere since the data should still be preserved.
+        // If the variable doesn't have an associated position, it's a "stray", it just doesn't have a value yet.
+        let Some(cheapest_position) = variable.get_cheapest_position() else { continue; };
+
+        let distance = liveness.next_use(index, &variable.full_name).unwrap_or(usize::MAX);
+        variables_info.push((variable.clone(), cheapest_position, distance));
+    }
+
+    // Sort by distance (lowest first)
+    variables_info.sort_by_key(|a| a.2);
+
+    #[cfg(test)]
+    for var_info in variables_info.clone(){
+        println!("Name: {} \tCurrent Position: {:?} \tTarget Position: {:?} \tDistance: {}", var_info.0.full_name, var_info.0.positions.first(), var_info.1, var_info.2)
+    }
+
+    // The newly generated mapping from registers to variables.
+    // Basically variables_info but realistic (no registers being used multiple times)
+    let mut register_to_variable_map: HashMap<Register, Variable> = HashMap::new();
+
+    // All the variables that were in registers previously but don't fit anymore
+    // and are to be stored in the stack now.
+    let mut new_stack_items: Vec<Variable> = Vec::new();
+
+    // The registers that are not in register_to_variable_map yet,
+    // which means they can still be used to store variables to them.
+    let mut available_registers: Vec<Register> = registers.clone();
+
+    // The registers that get copies of arguments can't hold anything else
+    available_registers.retain(|x| !argument_copies.iter().any(|(register, _)| *register == x.name));
+
+    // Passed values that are needed after the call and are in a callee-saved register already keep it as their copy
+    let kept_registers: Vec<(String, String)> = variables.iter()
+        .filter(|x| arguments.contains_key(&x.full_name) && crosses_call(&x.full_name))
+        .filter_map(|x| x.positions.iter().filter_map(|x| x.register_name()).find(|x| registers.iter().any(|y| y.name == *x && y.saver == RegisterSaver::Callee)).map(|register| (x.full_name.clone(), register)))
+        .collect();
+    available_registers.retain(|x| !kept_registers.iter().any(|(_, register)| *register == x.name));
+
+    // Now, find the ideal realistic storage position for all variables
+    // This means trying to match the recommendation variable_info
+    // as best as possible (especially for all vars with a short distance)
+    // while not storing multiple variables on the same register.
+    for var_info in variables_info.clone().iter(){
+        let mut var_info = var_info.clone();
+        // If the variable should be stored in a register, look if there's place for it left somewhere
+        // If not, let it live on the stack (or the heap for that matter) for now.
+        // The only way the variable info says it should be stored on the stack is if it has
+        // been stored on the stack previously, so there's no reason to even mark it for later.
+
+        if !matches!(var_info.1, DataPosition::Register(_)){
+            // This is not a register position,
+            // continue (reasoning above).
+            continue;
+        }
+
+        // Values that have to survive the call can't stay in a caller-saved register (unless they're passed in it, they're copied then)
+        let crosses_call = crosses_call(&var_info.0.full_name);
+        let is_argument = arguments.contains_key(&var_info.0.full_name);
+        let needs_callee_saved = crosses_call && !is_argument;
+        let is_clobbered = needs_callee_saved && is_caller_saved(&var_info.1.register_name().unwrap());
+
+        // Look if a specific register is requested or
+        // just some general-purpose register
+        if !var_info.1.is_general_purpose_register() && !is_clobbered {
+            // The requested register from the available_registers list if it's still available, None otherwise.
+            let register = available_registers.iter().find(|&x| x.name == var_info.1.register_name().unwrap());
+
+            if let Some(register) = register {
+                let register = register.clone();
+
+                // The register is still available.
+                // Remove it from the list and add it to register_to_var_map.
+
+                let position_in_available_registers = available_registers.iter().position(|x| x.clone() == register).unwrap();
+                available_registers.remove(position_in_available_registers);
+
+                register_to_variable_map.insert(register, var_info.0.clone());
+                continue;
+            }
+            // The requested register is not available, so look for a general purpose register instead.
+            // This is done by just continuing to the next step, which would handel normal general-purpose register requests.
+        }
+
+        // Either there was nothing but a general purpose register needed,
+        // or the real favourite failed (was overwritten by another reg)
+        // Find the least-used general purpose register and go with that
+        // or the stack (if there are no regs left).
+
+        // The remaining registers and how much damage using them would cause
+        // (0 being the most).
+        let mut register_cost_map: Vec<(Register, usize)> = Vec::new();
+
+        // Loop through all available general purpose registers (of the variable's class, and callee-saved if it has to survive the call).
+        let class = RegisterClass::of(&var_info.0.value_type);
+        for available_register in available_registers.clone().iter().filter(|&x| x.tags.contains(&RegisterTag::GeneralPurpose) && x.class == class && (!needs_callee_saved || x.saver == RegisterSaver::Callee)){
+            let available_register = available_register.clone();
+
+            // Now calculate the cost of using this register by going through all variable infos and looking if it's used somewhere.
+            // Usage found -> use distance as cost factor, if not use usize::MAX
+            let mut costs = usize::MAX;
+
+            for var_info in variables_info.clone().iter(){
+                let var_target = var_info.clone().1;
+
+                if let Some(target_name) = var_target.register_name(){
+                    if target_name != available_register.name { continue; }
+
+                    // Register ('available_register') is going to be used by this variable (or at least the variable wants to use this reg).
+                    let distance = var_info.clone().2;
+                    costs = distance;
+                }
+            }
+
+            register_cost_map.push((available_register.clone(), costs));
+        }
+
+        // Sort so the register with the smallest distance is at the top.
+        // Of the registers that are just as good, caller-saved ones come last (they don't have to be saved by the prologue).
+        register_cost_map.sort_by_key(|a| (a.1, a.0.saver == RegisterSaver::Caller));
+
+        // Get the last register (with the biggest distance) from the register_cost_map.
+        // If it exists, use it, if not, there's no place left in the registers.
+        // This means the stack will need to handle that.
+        let most_cost_effective_reg = register_cost_map.last();
+
+        if let Some(most_cost_effective_reg) = most_cost_effective_reg {
+            // Remove the most_cost_effective_reg from the list of available registers as it's no longer going to be available
+            let reg_position_in_available_registers = available_registers.iter().position(|x| x.clone() == most_cost_effective_reg.clone().0).unwrap();
+            available_registers.remove(reg_position_in_available_registers);
+
+            // Add the variable to the mapping
+            _ = register_to_variable_map.insert(most_cost_effective_reg.clone().0, var_info.0.clone());
+
+            // Continue as the rest of the code is about handling the case that there is no space left in the registers.
+            continue;
+        }
+
+        // No place left in the registers, push to stack (if there is no alternative position (on the stack) already).
+
+        // Check if there is a stack location already.
+        if var_info.0.has_stack_position() {
+            // Stack position already exists, so delete all locations but the stack position.
+            // This is mainly to delete the register locations.
+            if let Some(position_in_variables) = variables.iter().position(|x| x.full_name == var_info.0.full_name) {
+                variables[position_in_variables].remove_everything_except_stack_position();
+            }
+
+            // Continue as the rest of the code is there to push the variable to the stack as there's no other storage location and no space in the registers anymore.
+            continue;
+        }
+
+
+        // Generate the location and update the stack offset (since function/stack frame start)
+        let location = DataPosition::StackOffset(allocate_stack_slot(stack_offset, var_info.0.value_type.bytes(arch)));
+
+        // Update the location in the  variable
+        var_info.0.positions = vec![location];
+
+        // Mark this for actually adding the push code later.
+        new_stack_items.push(var_info.clone().0);
+    }
+
+    // All the values change their positions at once: (new position, current position, type)
+    let mut moves: Vec<(DataPosition, DataPosition, ValueType)> = Vec::new();
+
+    // The variables that don't fit into the registers anymore are stored.
+    // Data that had no previous location but is now new at the stack does not need to be stored,
+    // it just needs a reserved address, which it's already got.
+    for stack_variable in new_stack_items {
+        let Some(position_in_variables) = variables.iter().position(|x| x.full_name == stack_variable.full_name) else { continue; };
+
+        if let Some(current_position @ DataPosition::Register(_)) = variables[position_in_variables].get_cheapest_position() {
+            moves.push((stack_variable.positions[0].clone(), current_position, stack_variable.value_type.clone()));
+        }
+
+        variables[position_in_variables].positions = stack_variable.positions;
+    }
+
+    // The variables that go to another register (in the order of the registers, so the code is always the same)
+    let mut register_to_variable_map: Vec<(Register, Variable)> = register_to_variable_map.into_iter().collect();
+    register_to_variable_map.sort_by_key(|(register, _)| registers.iter().position(|x| x == register));
+
+    for (target_register, variable) in register_to_variable_map {
+        let Some(position_in_variables) = variables.iter().position(|x| x.full_name == variable.full_name) else { continue; };
+        let target_position = DataPosition::Register(target_register.name);
+
+        // Variables without a position yet only need to remember the register that's been reserved for them
+        if let Some(current_position) = variables[position_in_variables].get_cheapest_position()
+            && current_position != target_position {
+            moves.push((target_position.clone(), current_position, variable.value_type.clone()));
+        }
+
+        variables[position_in_variables].positions = vec![target_position];
+    }
+
+    // The arguments that are passed more than once are copied to their other registers (from where they are now)
+    for (register, name) in &argument_copies {
+        let Some(variable) = variables_info.iter().map(|x| &x.0).find(|x| x.full_name == *name) else { continue; };
+        let Some(current_position) = variable.get_cheapest_position() else { continue; };
+
+        moves.push((DataPosition::Register(register.clone()), current_position, variable.value_type.clone()));
+    }
+
+    code += move_values(arch, moves).as_str();
+
+    for (name, register) in kept_registers {
+        if let Some(variable) = variables.iter_mut().find(|x| x.full_name == name) && !variable.positions.contains(&DataPosition::Register(register.clone())) {
+            variable.positions.push(DataPosition::Register(register));
+        }
+    }
+
+    // The passed values that are needed after the call get a copy that survives it
+    let mut copies: Vec<(String, usize, ValueType)> = Vec::new();
+
+    for variable in variables.iter_mut() {
+        if !arguments.contains_key(&variable.full_name) || !crosses_call(&variable.full_name) { continue; }
+        if variable.positions.iter().any(|x| !x.register_name().is_some_and(|x| is_caller_saved(&x))) { continue; }
+        let Some(register) = variable.get_cheapest_position().and_then(|x| x.register_name()) else { continue; };
+
+        let class = RegisterClass::of(&variable.value_type);
+        let free_register = available_registers.iter().position(|x| x.tags.contains(&RegisterTag::GeneralPurpose) && x.class == class && x.saver == RegisterSaver::Callee);
+
+        match free_register {
+            Some(free_register) => {
+                let free_register = available_registers.remove(free_register);
+                code += arch.move_register(&free_register.name, &register).as_str();
+                variable.positions.push(DataPosition::Register(free_register.name));
+            }
+            None => {
+                let offset = allocate_stack_slot(stack_offset, variable.value_type.bytes(arch));
+                copies.push((register, offset, variable.value_type.clone()));
+                variable.positions.push(DataPosition::StackOffset(offset));
+            }
+        }
+    }
+
+    code += store_values(arch, copies).as_str();
+
+    // The values that are on the stack only (because there was no callee-saved register left for them)
+    let spilled = endangered.into_iter().filter(|name| {
+        variables.iter().find(|x| x.full_name == *name).is_some_and(|x| x.positions.iter().all(|x| x.register_name().is_none_or(|x| is_caller_saved(&x))))
+    }).collect();
+
+    (code, spilled)
+}
+
+/// Load the variables that have been spilled for a call (see order_variable_locations) back into the caller-saved registers the call has freed,
+/// two values from next to each other at once where possible. The call must have forgotten about the caller-saved registers already.
+/// The variables keep their place on the stack, so spilling them for the next call is free.
+pub fn reload_after_call(arch: &dyn NativeArch, variables: &mut [Variable], registers: &[Register], spilled: &[String]) -> String {
+    let mut loads: Vec<(usize, String, usize, ValueType)> = Vec::new();
+    let mut taken: Vec<String> = variables.iter().flat_map(|x| x.positions.iter().filter_map(|x| x.register_name())).collect();
+
+    for name in spilled {
+        let Some(variable_index) = variables.iter().position(|x| x.full_name == *name) else { continue; };
+        let variable = &variables[variable_index];
+        let Some(offset) = variable.get_stack_offset() else { continue; };
+
+        let class = RegisterClass::of(&variable.value_type);
+        let Some(register) = registers.iter().find(|x| x.tags.contains(&RegisterTag::GeneralPurpose) && x.class == class && x.saver == RegisterSaver::Caller && !taken.contains(&x.name)) else { continue; };
+
+        taken.push(register.name.clone());
+        loads.push((variable_index, register.name.clone(), offset, variable.value_type.clone()));
+    }
+
+    let code = load_values(arch, loads.iter().map(|(_, register, offset, value_type)| (register.clone(), *offset, value_type.clone())).collect());
+
+    for (variable_index, register, _, _) in loads {
+        variables[variable_index].positions.insert(0, DataPosition::Register(register));
+    }
+
+    code
+}
+
+#[cfg(test)]
+mod tests{
+    use crate::compiler::low_level::arch::aarch64::aarch64::{AArch64, AArch64Os};
+    use crate::compiler::low_level::arch::arch::NativeArch;
+    use crate::compiler::low_level::arch::variable_manager::order_variable_locations;
+    use crate::compiler::low_level::ir_text::parse_ir;
+    use crate::compiler::low_level::liveness::Liveness;
+    use crate::compiler::low_level::data_position::DataPosition::Register;
+    use crate::compiler::low_level::macro_instruction::MacroInstruction;
+    use crate::compiler::low_level::variable::Variable;
+
+    /// The function's declared variables with their positions and the instructions that come after the declarations
+    fn split_declarations(instructions: &[MacroInstruction]) -> (Vec<Variable>, Vec<MacroInstruction>) {
+        let (declarations, instructions): (Vec<MacroInstruction>, Vec<MacroInstruction>) = instructions.iter().cloned()
+            .partition(|x| matches!(x, MacroInstruction::DeclareVariable(_)));
+
+        let variables = declarations.into_iter().filter_map(|x| match x {
+            MacroInstruction::DeclareVariable(variable) => Some(variable),
+            _ => None,
+        }).collect();
+
+        (variables, instructions)
+    }
+
+    #[test]
+    fn test_order_variable_locations(){
+        let aarch64 = AArch64::new(AArch64Os::MacOs);
+
+        let (functions, diagnostics) = parse_ir("test.rslir".to_string(), "
+            function @f(0) -> value {
+                declare %a{reg(x2)}
+                declare %b{reg(x0)}
+                declare %c{reg(x1)}
+                declare %d{stack(0)}
+                declare %dead{reg(x3)}
+                argument %a, 0
+                argument %b, 1
+                argument %d, 2
+                argument %b, 3
+                call @g, 4
+                %c = add %c, 1
+                destroy %dead
+                return %c
+            }
+        ");
+        assert_eq!(diagnostics, vec![]);
+
+        let (mut variables, instructions) = split_declarations(&functions[0].instructions);
+        let mut stack_offset: usize = 8;
+
+        let (code, spilled) = order_variable_locations(&aarch64, &mut variables, aarch64.registers(), &instructions, 0, &Liveness::analyze(&instructions), &mut stack_offset);
+        println!("{}", code);
+
+        // The arguments are in their registers (b twice), c is needed after the call and moves to a callee-saved register
+        assert_eq!(code, "mov\tx28, x1\nmov\tx1, x0\nmov\tx3, x0\nmov\tx0, x2\nldr\tx2, [sp, #0]\n");
+        assert_eq!(variables[0].positions, vec![Register("x0".to_string())]);
+        assert_eq!(variables[1].positions, vec![Register("x1".to_string())]);
+        assert_eq!(variables[2].positions, vec![Register("x28".to_string())]);
+        assert_eq!(variables[3].positions, vec![Register("x2".to_string())]);
+
+        // Nothing reads the dead variable anymore, so it's forgotten and nothing has to be loaded again
+        assert_eq!(variables[4].positions, vec![]);
+        assert_eq!(spilled, Vec::<String>::new());
+        assert_eq!(stack_offset, 8);
+    }
+
+    #[test]
+    fn test_swap_arguments(){
+        let aarch64 = AArch64::new(AArch64Os::MacOs);
+        let aarch64_regs = aarch64.registers();
+
+        let (functions, diagnostics) = parse_ir("swap_arguments.rslir".to_string(), include_str!("fixtures/swap_arguments.rslir"));
+        assert_eq!(diagnostics, vec![]);
+
+        // The declared variables are alive already, the rest is what's ahead
+        let (mut variables, instructions) = split_declarations(&functions[0].instructions);
+        let mut stack_offset: usize = 0;
+
+        let (code, _) = order_variable_locations(&aarch64, &mut variables, aarch64_regs, &instructions, 0, &Liveness::analyze(&instructions), &mut stack_offset);
+        println!("{}", code);
+
+        assert_eq!(variables[0].positions, vec![Register("x1".to_string())]);
+        assert_eq!(variables[1].positions, vec![Register("x0".to_string())]);
+    }
+}
@@ -0,0 +1,741 @@
+use std::collections::HashMap;
+use crate::compiler::low_level::arch::arch::Arch;
+use crate::compiler::low_level::data_position::DataPosition;
+use crate::compiler::low_level::macro_function::MacroFunction;
+use crate::compiler::low_level::macro_instruction::MacroInstruction;
+use crate::compiler::low_level::operation::{Operand, Operation, UnaryOperation};
+use crate::compiler::low_level::arch::function_gen::allocate_stack_slot;
+use crate::compiler::low_level::variable::{BitUnit, ValueKind, ValueType, Variable};
+use crate::util::exit::{exit, ExitCode};
+
+/// WebAssembly (32-bit linear memory), the output is in the text format (.wat).
+/// There are no registers, so every variable gets a local instead (DataPosition::Register holds the name of the local).
+/// Data on the stack lives on a shadow stack in linear memory, which grows downwards from STACK_START.
+/// Locals don't have an address, so variables whose address is taken live there instead.
+/// Constants are put right after it.
+/// Variadic arguments are passed like clang does it: they're put into a buffer at the bottom of the caller's stack frame
+/// (4B each, in order), whose address is passed as the last argument.
+/// wasm can't jump to arbitrary places, so the code of functions with labels is split up at them (into blocks)
+/// and a loop around all of it jumps to the block of the label whenever there's a jump.
+pub struct Wasm32 {}
+
+/// Where the variables of a function are and what type they have
+struct Frame {
+    homes: HashMap<String, usize>,          // The offsets of the variables whose address is taken in the function's stack frame
+    types: HashMap<String, ValueType>,      // The declared types of the variables
+}
+
+/// A function the host provides
+struct Import {
+    name: String,
+    parameter_count: usize, // The pointer to the variadic arguments of a variadic function is a parameter as well
+    has_result: bool,
+}
+
+impl Wasm32 {
+    pub const NAME: &'static str = "wasm32";
+    pub const BITS_COUNT: u8 = 32;
+    pub const ENTRY_POINT_NAME: &'static str = "main";
+
+    const STACK_POINTER: &'static str = "$__stack_pointer";
+    const STACK_START: usize = 65536;               // The end of the first page of memory
+    const PAGE_SIZE: usize = 65536;
+    const CONSTANT_ALIGNMENT: usize = 8;
+    const ARGUMENT_PREFIX: &'static str = "argument-";
+    const NEXT_BLOCK: &'static str = "$next-block";    // The index of the block the dispatch loop jumps to
+    const SCRATCH: &'static str = "$@scratch";         // Variables can't have an @ in their name (only quoted ones in the IR)
+    const VARIADIC_ARGUMENT_SIZE: usize = 4;            // Every variadic argument is passed as an i32
+
+    pub fn new() -> Self {
+        Wasm32 {}
+    }
+
+    /// The wasm type a value of the given size is stored as
+    fn value_type(&self, unit: BitUnit) -> &'static str {
+        match unit.resolve(self) {
+            BitUnit::QuadWord => "i64",
+            // wasm doesn't have any smaller types, everything smaller gets stored in an i32
+            _ => "i32",
+        }
+    }
+
+    /// Every variable is stored in the local of the same name, unless it has a home in the function's stack frame
+    fn place(variable: Variable, frame: &Frame) -> Variable {
+        let position = match frame.homes.get(&variable.full_name) {
+            Some(home) => DataPosition::StackOffset(*home),
+            None => DataPosition::Register(variable.full_name.clone()),
+        };
+
+        let value_type = frame.types.get(&variable.full_name).cloned().unwrap_or_default();
+
+        Variable::new(variable.full_name, vec![position]).with_type(value_type)
+    }
+
+    /// The type of a variable if it's supported, all values are calculated with the architecture's size
+    fn check_type(&self, variable: &Variable) -> ValueType {
+        let value_type = variable.value_type.clone();
+
+        if matches!(value_type.kind, ValueKind::Float) || value_type.bits(self) > self.architecture_bits() {
+            exit(format!("Variable \"{}\" is of type {}, which isn't supported on wasm32 yet.", variable.full_name, value_type.name()), ExitCode::Internal);
+        }
+
+        value_type
+    }
+
+    /// The instruction that loads (or stores) a value of the type from (or to) memory
+    fn memory_instruction(&self, value_type: &ValueType, is_load: bool) -> String {
+        let architecture_type = self.value_type(BitUnit::ArchitectureMax);
+
+        if value_type.is_full_width(self) {
+            return format!("{}.{}", architecture_type, if is_load { "load" } else { "store" });
+        }
+
+        match (is_load, value_type.is_signed()) {
+            (true, true) => format!("{}.load{}_s", architecture_type, value_type.bits(self)),
+            (true, false) => format!("{}.load{}_u", architecture_type, value_type.bits(self)),
+            (false, _) => format!("{}.store{}", architecture_type, value_type.bits(self)),
+        }
+    }
+
+    /// Sign- or zero-extend the low bits of the value on top of the operand stack, so it's a valid value of the type
+    fn normalize(&self, value_type: &ValueType) -> String {
+        if value_type.is_full_width(self) {
+            return String::new();
+        }
+
+        let architecture_type = self.value_type(BitUnit::ArchitectureMax);
+        let bits = value_type.bits(self);
+
+        match value_type.is_signed() {
+            true => format!("{}.extend{}_s\n", architecture_type, bits),
+            false => format!("{}.const {}\n{}.and\n", architecture_type, (1u64 << bits) - 1, architecture_type),
+        }
+    }
+
+    /// Memory instructions only take unsigned offsets, other offsets are added to the address on top of the operand stack first.
+    /// Returns that code and the offset to use.
+    fn memory_offset(&self, offset: i64) -> (String, i64) {
+        if (0..=u32::MAX as i64).contains(&offset) {
+            return (String::new(), offset);
+        }
+
+        let value_type = self.value_type(BitUnit::ArchitectureMax);
+        (format!("{}.const {}\n{}.add\n", value_type, offset, value_type), 0)
+    }
+
+    /// Move the stack pointer by the given amount of bytes
+    fn move_stack_pointer(&self, bytes: usize, operation: Operation) -> String {
+        let value_type = self.value_type(BitUnit::ArchitectureMax);
+        format!("global.get {}\n{}.const {}\n{}.{}\nglobal.set {}\n", Self::STACK_POINTER, value_type, bytes, value_type, Self::instruction(operation), Self::STACK_POINTER)
+    }
+
+    /// Push the value of the operand onto the operand stack
+    fn load_operand(&self, operand: Operand, frame: &Frame) -> String {
+        match operand {
+            Operand::Variable(variable) => self.load_variable(&Self::place(variable, frame)),
+            // Values that are too large are cut off, as they would be by a 32-bit register
+            Operand::Immediate(value) if self.value_type(BitUnit::ArchitectureMax) == "i32" => format!("i32.const {}\n", value as i32),
+            Operand::Immediate(value) => format!("i64.const {}\n", value),
+        }
+    }
+
+    /// The instruction (without its type) that performs the operation on the two values on top of the operand stack
+    fn instruction(operation: Operation) -> &'static str {
+        match operation {
+            Operation::Add => "add",
+            Operation::Subtract => "sub",
+            Operation::Multiply => "mul",
+            Operation::DivideSigned => "div_s",
+            Operation::DivideUnsigned => "div_u",
+            Operation::RemainderSigned => "rem_s",
+            Operation::RemainderUnsigned => "rem_u",
+            Operation::And => "and",
+            Operation::Or => "or",
+            Operation::Xor => "xor",
+            Operation::ShiftLeft => "shl",
+            Operation::ShiftRightArithmetic => "shr_s",
+            Operation::ShiftRightLogical => "shr_u",
+            Operation::Equal => "eq",
+            Operation::NotEqual => "ne",
+            Operation::LessSigned => "lt_s",
+            Operation::LessOrEqualSigned => "le_s",
+            Operation::GreaterSigned => "gt_s",
+            Operation::GreaterOrEqualSigned => "ge_s",
+            Operation::LessUnsigned => "lt_u",
+            Operation::LessOrEqualUnsigned => "le_u",
+            Operation::GreaterUnsigned => "gt_u",
+            Operation::GreaterOrEqualUnsigned => "ge_u",
+        }
+    }
+
+    /// Push the value of the variable onto the operand stack
+    fn load_variable(&self, variable: &Variable) -> String {
+        let Some(position) = variable.get_cheapest_position() else {
+            exit(format!("Variable \"{}\" is used before it has a value.", variable.full_name), ExitCode::Internal);
+        };
+
+        match position {
+            DataPosition::Register(local) => format!("local.get ${}\n", local),
+            DataPosition::StackOffset(offset) => {
+                format!("global.get {}\n{} offset={}\n", Self::STACK_POINTER, self.memory_instruction(&self.check_type(variable), true), offset)
+            }
+            _ => exit(format!("Variable \"{}\" is stored at {:?}, which isn't supported on wasm32 yet.", variable.full_name, position), ExitCode::Internal),
+        }
+    }
+
+    /// Pop the value on top of the operand stack into the variable (cutting it down to the variable's type),
+    /// uses_scratch is set if the scratch local is needed for it
+    fn store_variable(&self, variable: &Variable, uses_scratch: &mut bool) -> String {
+        let Some(position) = variable.get_cheapest_position() else {
+            exit(format!("Variable \"{}\" doesn't have a place to be stored in.", variable.full_name), ExitCode::Internal);
+        };
+
+        let value_type = self.check_type(variable);
+
+        match position {
+            DataPosition::Register(local) => format!("{}local.set ${}\n", self.normalize(&value_type), local),
+            DataPosition::StackOffset(offset) => {
+                // The address has to come before the value, so park the value in the scratch local first
+                *uses_scratch = true;
+                format!("local.set {}\nglobal.get {}\nlocal.get {}\n{} offset={}\n", Self::SCRATCH, Self::STACK_POINTER, Self::SCRATCH, self.memory_instruction(&value_type, false), offset)
+            }
+            _ => exit(format!("Variable \"{}\" is stored at {:?}, which isn't supported on wasm32 yet.", variable.full_name, position), ExitCode::Internal),
+        }
+    }
+
+    /// Continue at the block that starts at the label (block 0 is where the function starts)
+    fn jump(labels: &[String], label: &str) -> String {
+        let Some(index) = labels.iter().position(|x| x == label) else {
+            exit(format!("Label \"{}\" is jumped to, but it doesn't exist.", label), ExitCode::Internal);
+        };
+
+        format!("i32.const {}\nlocal.set {}\nbr $dispatch\n", index + 1, Self::NEXT_BLOCK)
+    }
+
+    /// The start of the dispatch loop, which jumps to the end of the block with the index in NEXT_BLOCK.
+    /// The blocks are nested, so the code of a block is put after its end (and before the end of the next one).
+    fn dispatch(block_count: usize) -> String {
+        let mut code = "loop $dispatch\n".to_string();
+
+        for i in (0..block_count).rev() {
+            code += format!("block $block{}\n", i).as_str();
+        }
+
+        code += format!("local.get {}\n", Self::NEXT_BLOCK).as_str();
+        code += "br_table";
+        for i in 0..block_count {
+            code += format!(" $block{}", i).as_str();
+        }
+        code += "\n";
+
+        // The code of the first block is what follows
+        code += "end\n";
+
+        code
+    }
+
+    /// The amount of parameters the wasm function that's called with the arguments has (see the variadic arguments in the description of Wasm32)
+    fn parameter_count(argument_count: usize, fixed_argument_count: Option<usize>) -> usize {
+        fixed_argument_count.map_or(argument_count, |x| x + 1)
+    }
+
+    /// Generate a function, the locals of a wasm function need to be declared before its body.
+    /// The signatures are (name, parameter count, whether it returns a value) of all the functions that can be called.
+    fn generate_function(&self, function: MacroFunction, signatures: &[(String, usize, bool)], constants: &mut Vec<(usize, Vec<u8>)>) -> String {
+        let value_type = self.value_type(BitUnit::ArchitectureMax);
+
+        let mut locals: Vec<String> = Vec::new();
+        let mut argument_count = function.parameter_count;
+        let mut uses_scratch = false;
+
+        let mut body = String::new();
+
+        // The arguments for the next call (in order)
+        let mut arguments: Vec<Option<Variable>> = Vec::new();
+
+        let labels: Vec<String> = function.instructions.iter().filter_map(|x| match x {
+            MacroInstruction::Label(label) => Some(label.clone()),
+            _ => None,
+        }).collect();
+
+        let types: HashMap<String, ValueType> = function.instructions.iter().filter_map(|x| match x {
+            MacroInstruction::DeclareVariable(variable) => Some((variable.full_name.clone(), variable.value_type.clone())),
+            _ => None,
+        }).collect();
+
+        // The variadic arguments of the calls go to the bottom of the stack frame, the callee's frame is right below them
+        let variadic_arguments_size = function.instructions.iter().filter_map(|x| match x {
+            MacroInstruction::CallFunction(_, argument_count, Some(fixed_argument_count), _) => Some(argument_count.saturating_sub(*fixed_argument_count) * Self::VARIADIC_ARGUMENT_SIZE),
+            _ => None,
+        }).max().unwrap_or(0);
+
+        // Variables whose address is taken get a slot of their size in the function's stack frame
+        let mut homes: HashMap<String, usize> = HashMap::new();
+        let mut homes_size = variadic_arguments_size;
+        for instruction in &function.instructions {
+            if let MacroInstruction::AddressOf(_, variable) = instruction && !homes.contains_key(&variable.full_name) {
+                let bytes = types.get(&variable.full_name).cloned().unwrap_or_default().bytes(self);
+                homes.insert(variable.full_name.clone(), allocate_stack_slot(&mut homes_size, bytes));
+            }
+        }
+
+        let frame = Frame { homes, types };
+
+        // The stack pointer has to be moved back whenever the function is left
+        let frame_size = homes_size.div_ceil(16) * 16;
+        let epilogue = match frame_size {
+            0 => String::new(),
+            _ => self.move_stack_pointer(frame_size, Operation::Add),
+        };
+
+        for instruction in function.instructions {
+            match instruction {
+                MacroInstruction::DeclareVariable(variable) => {
+                    // Every variable simply gets its own local
+                    if !locals.contains(&variable.full_name) && !frame.homes.contains_key(&variable.full_name) {
+                        locals.push(variable.full_name.clone());
+                    }
+                }
+                MacroInstruction::DestroyVariable(_) => {}
+                MacroInstruction::GetArgument(variable, n) => {
+                    argument_count = argument_count.max(n + 1);
+
+                    body += format!("local.get ${}{}\n", Self::ARGUMENT_PREFIX, n).as_str();
+                    body += self.store_variable(&Self::place(variable, &frame), &mut uses_scratch).as_str();
+                }
+                MacroInstruction::UseVariableAsArgument(variable, n) => {
+                    if arguments.len() <= n {
+                        arguments.resize(n + 1, None);
+                    }
+
+                    arguments[n] = Some(Self::place(variable, &frame));
+                }
+                MacroInstruction::CallFunction(function_name, argument_count, fixed_argument_count, result) => {
+                    if arguments.len() != argument_count || arguments.iter().any(|x| x.is_none()) {
+                        exit(format!("The arguments of the call to \"{}\" don't match its argument count ({}).", function_name, argument_count), ExitCode::Internal);
+                    }
+
+                    // Arguments are passed on the operand stack, the first one goes first
+                    for (i, argument) in arguments.drain(..).flatten().enumerate() {
+                        match fixed_argument_count {
+                            // Variadic arguments are stored in the buffer instead
+                            Some(fixed_argument_count) if i >= fixed_argument_count => {
+                                let offset = (i - fixed_argument_count) * Self::VARIADIC_ARGUMENT_SIZE;
+                                body += format!("global.get {}\n", Self::STACK_POINTER).as_str();
+                                body += self.load_variable(&argument).as_str();
+                                body += format!("{} offset={}\n", self.memory_instruction(&ValueType::default(), false), offset).as_str();
+                            }
+                            _ => body += self.load_variable(&argument).as_str(),
+                        }
+                    }
+
+                    if fixed_argument_count.is_some() {
+                        body += format!("global.get {}\n", Self::STACK_POINTER).as_str();
+                    }
+
+                    body += format!("call ${}\n", function_name).as_str();
+
+                    let parameter_count = Self::parameter_count(argument_count, fixed_argument_count);
+
+                    let has_result = match signatures.iter().find(|(x, _, _)| *x == function_name) {
+                        Some((_, expected_parameter_count, _)) if *expected_parameter_count != parameter_count => {
+                            exit(format!("Function \"{}\" has {} parameters, but it's called with {}.", function_name, expected_parameter_count, parameter_count), ExitCode::Internal);
+                        }
+                        Some((_, _, returns_value)) => *returns_value,
+                        None => exit(format!("Function \"{}\" is called, but it's neither defined nor imported.", function_name), ExitCode::Internal),
+                    };
+
+                    match result {
+                        Some(result) if has_result => body += self.store_variable(&Self::place(result, &frame), &mut uses_scratch).as_str(),
+                        Some(_) => exit(format!("The result of function \"{}\" is used, but it doesn't return a value.", function_name), ExitCode::Internal),
+                        // The result has to be taken off the operand stack even if it isn't needed
+                        None if has_result => body += "drop\n",
+                        None => {}
+                    }
+                }
+                MacroInstruction::Return(value) => {
+                    match (value, function.returns_value) {
+                        (Some(value), true) => body += self.load_variable(&Self::place(value, &frame)).as_str(),
+                        (None, false) => {}
+                        _ => exit(format!("Not every return of function \"{}\" matches whether it returns a value.", function.name), ExitCode::Internal),
+                    }
+
+                    body += epilogue.as_str();
+                    body += "return\n";
+                }
+                MacroInstruction::Compute(target, operation, left, right) => {
+                    body += self.load_operand(left, &frame).as_str();
+                    body += self.load_operand(right, &frame).as_str();
+                    body += format!("{}.{}\n", value_type, Self::instruction(operation)).as_str();
+
+                    // Comparisons always result in an i32
+                    if operation.is_comparison() && value_type == "i64" {
+                        body += "i64.extend_i32_u\n";
+                    }
+
+                    body += self.store_variable(&Self::place(target, &frame), &mut uses_scratch).as_str();
+                }
+                MacroInstruction::ComputeUnary(target, operation, operand) => {
+                    match operation {
+                        UnaryOperation::Negate => {
+                            body += format!("{}.const 0\n", value_type).as_str();
+                            body += self.load_operand(operand, &frame).as_str();
+                            body += format!("{}.sub\n", value_type).as_str();
+                        }
+                        UnaryOperation::Not => {
+                            body += self.load_operand(operand, &frame).as_str();
+                            body += format!("{}.const -1\n", value_type).as_str();
+                            body += format!("{}.xor\n", value_type).as_str();
+                        }
+                    }
+
+                    body += self.store_variable(&Self::place(target, &frame), &mut uses_scratch).as_str();
+                }
+                MacroInstruction::LoadImmediate(target, value) => {
+                    body += self.load_operand(Operand::Immediate(value), &frame).as_str();
+                    body += self.store_variable(&Self::place(target, &frame), &mut uses_scratch).as_str();
+                }
+                MacroInstruction::LoadConstantAddress(target, bytes) => {
+                    body += format!("{}.const {}\n", value_type, Self::constant_address(constants, bytes)).as_str();
+                    body += self.store_variable(&Self::place(target, &frame), &mut uses_scratch).as_str();
+                }
+                MacroInstruction::Copy(target, source) => {
+                    body += self.load_variable(&Self::place(source, &frame)).as_str();
+                    body += self.store_variable(&Self::place(target, &frame), &mut uses_scratch).as_str();
+                }
+                MacroInstruction::Load(target, address, offset) => {
+                    let (address_code, offset) = self.memory_offset(offset);
+
+                    body += self.load_variable(&Self::place(address, &frame)).as_str();
+                    body += address_code.as_str();
+                    let target = Self::place(target, &frame);
+
+                    body += format!("{} offset={}\n", self.memory_instruction(&self.check_type(&target), true), offset).as_str();
+                    body += self.store_variable(&target, &mut uses_scratch).as_str();
+                }
+                MacroInstruction::Store(address, offset, value) => {
+                    let (address_code, offset) = self.memory_offset(offset);
+
+                    body += self.load_variable(&Self::place(address, &frame)).as_str();
+                    body += address_code.as_str();
+                    let value = Self::place(value, &frame);
+
+                    body += self.load_variable(&value).as_str();
+                    body += format!("{} offset={}\n", self.memory_instruction(&self.check_type(&value), false), offset).as_str();
+                }
+                MacroInstruction::AddressOf(target, variable) => {
+                    body += format!("global.get {}\n{}.const {}\n{}.add\n", Self::STACK_POINTER, value_type, frame.homes[&variable.full_name], value_type).as_str();
+                    body += self.store_variable(&Self::place(target, &frame), &mut uses_scratch).as_str();
+                }
+                MacroInstruction::Label(_) => {
+                    // End the block before the label, the code after it belongs to the label's block
+                    body += "end\n";
+                }
+                MacroInstruction::Jump(label) => {
+                    body += Self::jump(&labels, &label).as_str();
+                }
+                MacroInstruction::BranchIf(condition, label) => {
+                    body += self.load_variable(&Self::place(condition, &frame)).as_str();
+
+                    // Conditions have to be an i32
+                    if value_type == "i64" {
+                        body += "i64.const 0\ni64.ne\n";
+                    }
+
+                    body += "if\n";
+                    body += Self::jump(&labels, &label).as_str();
+                    body += "end\n";
+                }
+            }
+        }
+
+        if !labels.is_empty() {
+            body = Self::dispatch(labels.len() + 1) + body.as_str() + "end\n";
+        }
+
+        // A function with a result must not reach its end without returning
+        if function.returns_value {
+            body += "unreachable\n";
+        } else {
+            body += epilogue.as_str();
+        }
+
+        if frame_size > 0 {
+            body = self.move_stack_pointer(frame_size, Operation::Subtract) + body.as_str();
+        }
+
+        // Only the entry point is called from the outside
+        let mut code = format!("(func ${}", function.name);
+        if function.name == Self::ENTRY_POINT_NAME {
+            code += format!(" (export \"{}\")", function.name).as_str();
+        }
+        for i in 0..argument_count {
+            code += format!(" (param ${}{} {})", Self::ARGUMENT_PREFIX, i, value_type).as_str();
+        }
+        if function.returns_value {
+            code += format!(" (result {})", value_type).as_str();
+        }
+        code += "\n";
+
+        if uses_scratch {
+            code += format!("(local {} {})\n", Self::SCRATCH, value_type).as_str();
+        }
+
+        for local in locals {
+            code += format!("(local ${} {})\n", local, value_type).as_str();
+        }
+
+        if !labels.is_empty() {
+            code += format!("(local {} i32)\n", Self::NEXT_BLOCK).as_str();
+        }
+
+        code += body.as_str();
+        code += ")\n";
+
+        code
+    }
+
+    /// The address of the constant with the given bytes (constants with the same bytes share their address).
+    /// The constants are (address, bytes), the addresses are aligned and increase.
+    fn constant_address(constants: &mut Vec<(usize, Vec<u8>)>, bytes: Vec<u8>) -> usize {
+        if let Some((address, _)) = constants.iter().find(|(_, x)| *x == bytes) {
+            return *address;
+        }
+
+        let address = match constants.last() {
+            Some((address, last)) => (address + last.len()).div_ceil(Self::CONSTANT_ALIGNMENT) * Self::CONSTANT_ALIGNMENT,
+            None => Self::STACK_START,
+        };
+        constants.push((address, bytes));
+
+        address
+    }
+
+    /// Every function that's called, but isn't defined, is expected to be provided by the host (in the "env" module).
+    /// A function that's called for its result once has one everywhere, the other calls simply drop it.
+    /// Calls of variadic functions may have any amount of variadic arguments, as they're all passed through a single pointer.
+    fn collect_imports(functions: &[MacroFunction]) -> Vec<Import> {
+        let mut imports: Vec<Import> = Vec::new();
+
+        for instruction in functions.iter().flat_map(|x| &x.instructions) {
+            let MacroInstruction::CallFunction(name, argument_count, fixed_argument_count, result) = instruction else { continue; };
+            if functions.iter().any(|x| x.name == *name) { continue; }
+
+            let parameter_count = Self::parameter_count(*argument_count, *fixed_argument_count);
+
+            let Some(import) = imports.iter_mut().find(|x| x.name == *name) else {
+                imports.push(Import { name: name.clone(), parameter_count, has_result: result.is_some() });
+                continue;
+            };
+
+            if import.parameter_count != parameter_count {
+                exit(format!("Function \"{}\" is called with {} and {} parameters (counting the variadic arguments as one), but wasm functions have a fixed signature.", name, import.parameter_count, parameter_count), ExitCode::BadCode);
+            }
+
+            import.has_result |= result.is_some();
+        }
+
+        imports
+    }
+}
+
+impl Arch for Wasm32 {
+    fn name(&self) -> String {
+        Self::NAME.to_string()
+    }
+
+    fn architecture_bits(&self) -> u8 {
+        Self::BITS_COUNT
+    }
+
+    fn generate_assembly(&self, functions: Vec<MacroFunction>) -> String {
+        let imports = Self::collect_imports(&functions);
+
+        // (name, parameter count, whether it returns a value) of the functions defined in the module and the imported ones
+        let signatures: Vec<(String, usize, bool)> = functions.iter().map(|x| (x.name.clone(), x.parameter_count, x.returns_value))
+            .chain(imports.iter().map(|x| (x.name.clone(), x.parameter_count, x.has_result)))
+            .collect();
+
+        let mut constants: Vec<(usize, Vec<u8>)> = Vec::new();
+        let code: Vec<String> = functions.into_iter().map(|x| self.generate_function(x, &signatures, &mut constants)).collect();
+
+        // The first page is the stack, the constants need as many pages as it takes after it
+        let end = constants.last().map(|(address, bytes)| address + bytes.len()).unwrap_or(Self::STACK_START);
+        let page_count = end.div_ceil(Self::PAGE_SIZE).max(1);
+
+        let value_type = self.value_type(BitUnit::ArchitectureMax);
+
+        let mut module = "(module\n".to_string();
+
+        // Imports have to come before everything else
+        for import in imports {
+            module += format!("(import \"env\" \"{}\" (func ${}", import.name, import.name).as_str();
+            for _ in 0..import.parameter_count {
+                module += format!(" (param {})", value_type).as_str();
+            }
+            if import.has_result {
+                module += format!(" (result {})", value_type).as_str();
+            }
+            module += "))\n";
+        }
+
+        module += format!("(memory (export \"memory\") {})\n", page_count).as_str();
+        module += format!("(global {} (mut {}) ({}.const {}))\n", Self::STACK_POINTER, value_type, value_type, Self::STACK_START).as_str();
+
+        for (address, bytes) in constants {
+            let data = bytes.iter().map(|x| format!("\\{:02x}", x)).collect::<String>();
+            module += format!("(data (i32.const {}) \"{}\")\n", address, data).as_str();
+        }
+
+        for function in code {
+            module += function.as_str();
+        }
+        module += ")\n";
+
+        module
+    }
+
+    fn is_native(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use crate::compiler::low_level::arch::arch::Arch;
+    use crate::compiler::low_level::arch::wasm32::wasm32::Wasm32;
+    use crate::compiler::low_level::macro_function::MacroFunction;
+    use crate::compiler::low_level::macro_instruction::MacroInstruction;
+    use crate::compiler::low_level::operation::{Operand, Operation};
+    use crate::compiler::low_level::variable::{BitUnit, Variable};
+
+    #[test]
+    fn test_generate_assembly(){
+        let wasm32 = Wasm32::new();
+
+        assert_eq!(BitUnit::ArchitectureMax.resolve(&wasm32), BitUnit::DoubleWord);
+
+        let size = Variable::new("my_app:main.rsl:Main:size".to_string(), vec![]);
+        let count = Variable::new("my_app:main.rsl:Main:count".to_string(), vec![]);
+
+        // Swap the first two arguments and pass them on
+        let instructions: Vec<MacroInstruction> = vec![
+            MacroInstruction::DeclareVariable(size.clone()),
+            MacroInstruction::DeclareVariable(count.clone()),
+            MacroInstruction::GetArgument(size.clone(), 0),
+            MacroInstruction::GetArgument(count.clone(), 1),
+            MacroInstruction::UseVariableAsArgument(count.clone(), 0),
+            MacroInstruction::UseVariableAsArgument(size.clone(), 1),
+            MacroInstruction::CallFunction("calloc".to_string(), 2, None, None),
+            MacroInstruction::DestroyVariable(size.clone()),
+            MacroInstruction::DestroyVariable(count.clone()),
+        ];
+
+        let module = wasm32.generate_assembly(vec![MacroFunction::new("main".to_string(), 2, false, instructions)]);
+        println!("{}", module);
+
+        assert!(module.contains("(import \"env\" \"calloc\" (func $calloc (param i32) (param i32)))\n"));
+        assert!(module.contains("(func $main (export \"main\") (param $argument-0 i32) (param $argument-1 i32)\n"));
+        assert!(module.contains("local.get $my_app:main.rsl:Main:count\nlocal.get $my_app:main.rsl:Main:size\ncall $calloc\n"));
+
+        // Nothing is stored on the shadow stack, so there's no need for the scratch local
+        assert!(!module.contains("scratch"));
+    }
+
+    #[test]
+    fn test_labels(){
+        let wasm32 = Wasm32::new();
+
+        let count = Variable::new("my_app:main.rsl:main:count".to_string(), vec![]);
+
+        // Count down to 0
+        let instructions: Vec<MacroInstruction> = vec![
+            MacroInstruction::DeclareVariable(count.clone()),
+            MacroInstruction::GetArgument(count.clone(), 0),
+            MacroInstruction::Label("loop1.start".to_string()),
+            MacroInstruction::Compute(count.clone(), Operation::Subtract, Operand::Variable(count.clone()), Operand::Immediate(1)),
+            MacroInstruction::BranchIf(count.clone(), "loop1.start".to_string()),
+            MacroInstruction::DestroyVariable(count.clone()),
+        ];
+
+        let module = wasm32.generate_assembly(vec![MacroFunction::new("main".to_string(), 1, false, instructions)]);
+        println!("{}", module);
+
+        assert!(module.contains("(local $next-block i32)\nloop $dispatch\nblock $block1\nblock $block0\nlocal.get $next-block\nbr_table $block0 $block1\nend\n"));
+        assert!(module.contains("if\ni32.const 1\nlocal.set $next-block\nbr $dispatch\nend\nend\n)\n"));
+    }
+
+    #[test]
+    fn test_functions(){
+        let wasm32 = Wasm32::new();
+
+        let value = Variable::new("my_app:main.rsl:twice:value".to_string(), vec![]);
+        let twice = MacroFunction::new("twice".to_string(), 1, true, vec![
+            MacroInstruction::DeclareVariable(value.clone()),
+            MacroInstruction::GetArgument(value.clone(), 0),
+            MacroInstruction::Compute(value.clone(), Operation::Add, Operand::Variable(value.clone()), Operand::Variable(value.clone())),
+            MacroInstruction::Return(Some(value.clone())),
+        ]);
+
+        // The result of abs is used once, so the other call has to drop it
+        let count = Variable::new("my_app:main.rsl:main:count".to_string(), vec![]);
+        let main = MacroFunction::new("main".to_string(), 1, false, vec![
+            MacroInstruction::DeclareVariable(count.clone()),
+            MacroInstruction::GetArgument(count.clone(), 0),
+            MacroInstruction::UseVariableAsArgument(count.clone(), 0),
+            MacroInstruction::CallFunction("abs".to_string(), 1, None, None),
+            MacroInstruction::UseVariableAsArgument(count.clone(), 0),
+            MacroInstruction::CallFunction("abs".to_string(), 1, None, Some(count.clone())),
+            MacroInstruction::UseVariableAsArgument(count.clone(), 0),
+            MacroInstruction::CallFunction("twice".to_string(), 1, None, Some(count.clone())),
+            MacroInstruction::Return(None),
+        ]);
+
+        let module = wasm32.generate_assembly(vec![twice, main]);
+        println!("{}", module);
+
+        assert!(module.contains("(import \"env\" \"abs\" (func $abs (param i32) (result i32)))\n"));
+        assert!(module.contains("(func $twice (param $argument-0 i32) (result i32)\n"));
+        assert!(module.contains("call $abs\ndrop\n"));
+        assert!(module.contains("call $twice\nlocal.set $my_app:main.rsl:main:count\nreturn\n"));
+        assert!(module.contains("return\nunreachable\n)\n(func $main (export \"main\") (param $argument-0 i32)\n"));
+    }
+
+    #[test]
+    fn test_variadic_functions(){
+        let wasm32 = Wasm32::new();
+
+        let format = Variable::new("my_app:main.rsl:main:format".to_string(), vec![]);
+        let count = Variable::new("my_app:main.rsl:main:count".to_string(), vec![]);
+
+        // printf gets a different amount of variadic arguments each time
+        let main = MacroFunction::new("main".to_string(), 2, false, vec![
+            MacroInstruction::DeclareVariable(format.clone()),
+            MacroInstruction::DeclareVariable(count.clone()),
+            MacroInstruction::GetArgument(format.clone(), 0),
+            MacroInstruction::GetArgument(count.clone(), 1),
+            MacroInstruction::UseVariableAsArgument(format.clone(), 0),
+            MacroInstruction::UseVariableAsArgument(count.clone(), 1),
+            MacroInstruction::CallFunction("printf".to_string(), 2, Some(1), None),
+            MacroInstruction::UseVariableAsArgument(format.clone(), 0),
+            MacroInstruction::UseVariableAsArgument(count.clone(), 1),
+            MacroInstruction::UseVariableAsArgument(format.clone(), 2),
+            MacroInstruction::UseVariableAsArgument(count.clone(), 3),
+            MacroInstruction::CallFunction("printf".to_string(), 4, Some(1), Some(count.clone())),
+            MacroInstruction::Return(None),
+        ]);
+
+        let module = wasm32.generate_assembly(vec![main]);
+        println!("{}", module);
+
+        assert!(module.contains("(import \"env\" \"printf\" (func $printf (param i32) (param i32) (result i32)))\n"));
+
+        // The variadic arguments are put at the bottom of the stack frame, which is then passed to printf
+        assert!(module.contains("global.get $__stack_pointer\ni32.const 16\ni32.sub\nglobal.set $__stack_pointer\n"));
+        assert!(module.contains("local.get $my_app:main.rsl:main:format\nglobal.get $__stack_pointer\nlocal.get $my_app:main.rsl:main:count\ni32.store offset=0\nglobal.get $__stack_pointer\ncall $printf\ndrop\n"));
+        assert!(module.contains("i32.store offset=0\nglobal.get $__stack_pointer\nlocal.get $my_app:main.rsl:main:format\ni32.store offset=4\nglobal.get $__stack_pointer\nlocal.get $my_app:main.rsl:main:count\ni32.store offset=8\nglobal.get $__stack_pointer\ncall $printf\nlocal.set $my_app:main.rsl:main:count\n"));
+
+        // Nothing is stored on the stack through the scratch local
+        assert!(!module.contains("scratch"));
+    }
+}
@@ -0,0 +1,2 @@
+#[allow(clippy::module_inception)]
+pub mod x86_64_linux;
\ No newline at end of file
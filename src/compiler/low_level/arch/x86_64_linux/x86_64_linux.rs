@@ -0,0 +1,823 @@
+use crate::compiler::low_level::arch::arch::{Arch, NativeArch, RegisterOrImmediate};
+use crate::compiler::low_level::arch::function_gen::{generate_constants, generate_function};
+use crate::compiler::low_level::macro_function::MacroFunction;
+use crate::compiler::low_level::operation::{Operation, UnaryOperation};
+use crate::compiler::low_level::arch::register::*;
+use crate::compiler::low_level::variable::ValueType;
+use crate::util::exit::{exit, ExitCode};
+
+/// x86_64 using the System V ABI (Linux), the assembly is written in the AT&T syntax GNU as expects
+pub struct X86_64Linux {
+    pub registers: Vec<Register>
+}
+
+impl X86_64Linux {
+    pub const NAME: &'static str = "x86_64-linux";
+    pub const BITS_COUNT: u8 = 64;
+
+    /// The register that's used whenever an instruction needs an extra register
+    const SCRATCH_REGISTER: &'static str = "r11";
+
+    pub fn new() -> Self {
+        X86_64Linux {
+            registers: vec![
+                Register::new("rax".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::ReturnValue, RegisterTag::GeneralPurpose]),
+                Register::new("rdi".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::Argument(0), RegisterTag::GeneralPurpose]),
+                Register::new("rsi".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::Argument(1), RegisterTag::GeneralPurpose]),
+                Register::new("rdx".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::Argument(2), RegisterTag::GeneralPurpose]),
+                Register::new("rcx".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::Argument(3), RegisterTag::GeneralPurpose]),
+                Register::new("r8".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::Argument(4), RegisterTag::GeneralPurpose]),
+                Register::new("r9".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::Argument(5), RegisterTag::GeneralPurpose]),
+                Register::new("r10".to_string(), 64, RegisterSaver::Caller, vec![RegisterTag::GeneralPurpose]),
+                Register::new("r11".to_string(), 64, RegisterSaver::None, vec![RegisterTag::Scratch]),
+                Register::new("rbx".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]),
+                Register::new("r12".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]),
+                Register::new("r13".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]),
+                Register::new("r14".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]),
+                Register::new("r15".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::GeneralPurpose]),
+                Register::new("rbp".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::FramePointer]),
+                Register::new("rsp".to_string(), 64, RegisterSaver::Callee, vec![RegisterTag::StackPointer]),
+            ]
+        }
+    }
+
+    /// The name of the lowest bytes of the register (e.g. eax, ax or al for rax)
+    fn part(register: &str, bytes: usize) -> String {
+        if bytes == 8 {
+            return register.to_string();
+        }
+
+        // r8 to r15 just get a suffix
+        if register.starts_with('r') && register[1..].starts_with(|x: char| x.is_ascii_digit()) {
+            let suffix = match bytes { 1 => "b", 2 => "w", _ => "d" };
+            return format!("{}{}", register, suffix);
+        }
+
+        let name = &register[1..];
+        match bytes {
+            1 if name.ends_with('x') => format!("{}l", &name[..1]),
+            1 => format!("{}l", name),
+            2 => name.to_string(),
+            _ => format!("e{}", name),
+        }
+    }
+
+    /// The instruction and the destination register that load a value of the type into the whole register
+    fn load_instruction(&self, register: &str, value_type: &ValueType) -> (&'static str, String) {
+        // Writing to the 32-bit register clears the upper half of the 64-bit one
+        match (value_type.bytes(self), value_type.is_signed()) {
+            (8, _) => ("movq", register.to_string()),
+            (1, true) => ("movsbq", register.to_string()),
+            (2, true) => ("movswq", register.to_string()),
+            (_, true) => ("movslq", register.to_string()),
+            (1, false) => ("movzbl", Self::part(register, 4)),
+            (2, false) => ("movzwl", Self::part(register, 4)),
+            (_, false) => ("movl", Self::part(register, 4)),
+        }
+    }
+
+    /// The instruction and the source register that store a value of the type
+    fn store_instruction(&self, register: &str, value_type: &ValueType) -> (&'static str, String) {
+        let bytes = value_type.bytes(self);
+
+        let instruction = match bytes {
+            1 => "movb",
+            2 => "movw",
+            4 => "movl",
+            _ => "movq",
+        };
+
+        (instruction, Self::part(register, bytes))
+    }
+
+    /// The memory operand for base + offset and the code that has to come first,
+    /// offsets that don't fit into 32 bits are put into the scratch register
+    fn indirect_operand(base: &str, offset: i64) -> (String, String) {
+        if i32::try_from(offset).is_ok() {
+            return (String::new(), format!("{}(%{})", offset, base));
+        }
+
+        if base == Self::SCRATCH_REGISTER {
+            exit(format!("The offset {} can't be used with the scratch register as the base.", offset), ExitCode::Internal);
+        }
+
+        (format!("movabsq\t${}, %{}\n", offset, Self::SCRATCH_REGISTER), format!("(%{},%{})", base, Self::SCRATCH_REGISTER))
+    }
+}
+
+impl Arch for X86_64Linux {
+    fn name(&self) -> String {
+        Self::NAME.to_string()
+    }
+
+    fn architecture_bits(&self) -> u8 {
+        Self::BITS_COUNT
+    }
+
+    fn generate_assembly(&self, functions: Vec<MacroFunction>) -> String {
+        let mut assembly = ".text\n".to_string();
+
+        let mut constants: Vec<Vec<u8>> = Vec::new();
+
+        for function in functions {
+            assembly += generate_function(self, &function.name, function.instructions, &mut constants).as_str();
+        }
+
+        assembly += generate_constants(self, &constants).as_str();
+
+        // Tell the linker the stack doesn't need to be executable
+        assembly += ".section\t.note.GNU-stack,\"\",@progbits\n";
+
+        assembly
+    }
+}
+
+impl NativeArch for X86_64Linux {
+    fn registers(&self) -> Vec<Register> {
+        self.registers.clone()
+    }
+
+    fn symbol(&self, function_name: &str) -> String {
+        function_name.to_string()
+    }
+
+    fn function_header(&self, symbol: &str) -> String {
+        format!(".globl\t{}\n.type\t{}, @function\n", symbol, symbol)
+    }
+
+    fn prologue(&self, frame_size: usize) -> String {
+        // The call pushed the return address, so pushing rbp aligns the stack to 16B again (as the SysV ABI requires for calls).
+        // The frame size is a multiple of 16, so it keeps the alignment.
+        let mut code = "pushq\t%rbp\n".to_string();
+        code += "movq\t%rsp, %rbp\n";
+
+        if frame_size > 0 {
+            code += format!("subq\t${}, %rsp\n", frame_size).as_str();
+        }
+
+        code
+    }
+
+    fn epilogue(&self, _frame_size: usize) -> String {
+        let mut code = "movq\t%rbp, %rsp\n".to_string();
+        code += "popq\t%rbp\n";
+        code += "ret\n";
+
+        code
+    }
+
+    fn return_without_frame(&self) -> String {
+        "ret\n".to_string()
+    }
+
+    fn incoming_arguments_offset(&self) -> i64 {
+        // The saved rbp and the return address
+        16
+    }
+
+    fn call(&self, symbol: &str) -> String {
+        // Variadic functions expect the amount of vector registers used for arguments in al,
+        // which is always zero for now.
+        format!("xorl\t%eax, %eax\ncall\t{}\n", symbol)
+    }
+
+    fn local_label(&self, name: &str) -> String {
+        format!(".L{}", name)
+    }
+
+    fn jump(&self, label: &str) -> String {
+        format!("jmp\t{}\n", label)
+    }
+
+    fn branch_if_not_zero(&self, register: &str, label: &str) -> String {
+        format!("testq\t%{}, %{}\njnz\t{}\n", register, register, label)
+    }
+
+    fn move_register(&self, destination: &str, source: &str) -> String {
+        format!("movq\t%{}, %{}\n", source, destination)
+    }
+
+    fn store_register(&self, register: &str, stack_offset: usize, value_type: &ValueType) -> String {
+        let (instruction, register) = self.store_instruction(register, value_type);
+        format!("{}\t%{}, {}(%rsp)\n", instruction, register, stack_offset)
+    }
+
+    fn load_register(&self, register: &str, stack_offset: usize, value_type: &ValueType) -> String {
+        let (instruction, register) = self.load_instruction(register, value_type);
+        format!("{}\t{}(%rsp), %{}\n", instruction, stack_offset, register)
+    }
+
+    fn load_indirect(&self, register: &str, base: &str, offset: i64, value_type: &ValueType) -> String {
+        let (prefix, operand) = Self::indirect_operand(base, offset);
+        let (instruction, register) = self.load_instruction(register, value_type);
+        prefix + format!("{}\t{}, %{}\n", instruction, operand, register).as_str()
+    }
+
+    fn store_indirect(&self, register: &str, base: &str, offset: i64, value_type: &ValueType) -> String {
+        let (prefix, operand) = Self::indirect_operand(base, offset);
+
+        if !prefix.is_empty() && register == Self::SCRATCH_REGISTER {
+            exit(format!("The scratch register can't be stored at the offset {}, as it's needed to find the position.", offset), ExitCode::Internal);
+        }
+
+        let (instruction, register) = self.store_instruction(register, value_type);
+        prefix + format!("{}\t%{}, {}\n", instruction, register, operand).as_str()
+    }
+
+    fn extend(&self, register: &str, value_type: &ValueType) -> String {
+        if value_type.is_full_width(self) {
+            return String::new();
+        }
+
+        let (instruction, destination) = self.load_instruction(register, value_type);
+        format!("{}\t%{}, %{}\n", instruction, Self::part(register, value_type.bytes(self)), destination)
+    }
+
+    fn stack_address(&self, destination: &str, offset: &RegisterOrImmediate) -> String {
+        match offset {
+            RegisterOrImmediate::Register(register) => format!("leaq\t(%rsp,%{}), %{}\n", register, destination),
+            RegisterOrImmediate::Immediate(value) if i32::try_from(*value).is_ok() => format!("leaq\t{}(%rsp), %{}\n", value, destination),
+            RegisterOrImmediate::Immediate(value) => format!("movabsq\t${}, %{}\nleaq\t(%rsp,%{}), %{}\n", value, Self::SCRATCH_REGISTER, Self::SCRATCH_REGISTER, destination),
+        }
+    }
+
+    fn load_immediate(&self, register: &str, value: i64) -> String {
+        // movq only takes 32-bit immediates (which are sign-extended)
+        match i32::try_from(value) {
+            Ok(_) => format!("movq\t${}, %{}\n", value, register),
+            Err(_) => format!("movabsq\t${}, %{}\n", value, register),
+        }
+    }
+
+    fn load_address(&self, register: &str, label: &str) -> String {
+        format!("leaq\t{}(%rip), %{}\n", label, register)
+    }
+
+    fn constant_section(&self, _is_c_string: bool) -> String {
+        ".section\t.rodata\n".to_string()
+    }
+
+    fn immediate_fits(&self, operation: Operation, value: i64) -> bool {
+        match operation {
+            // Division takes its divisor from a register
+            Operation::DivideSigned | Operation::DivideUnsigned | Operation::RemainderSigned | Operation::RemainderUnsigned => false,
+            _ => i32::try_from(value).is_ok(),
+        }
+    }
+
+    fn compute(&self, operation: Operation, destination: &str, left: &str, right: &RegisterOrImmediate) -> String {
+        let scratch = Self::SCRATCH_REGISTER;
+
+        let right = match right {
+            RegisterOrImmediate::Register(register) => format!("%{}", register),
+            RegisterOrImmediate::Immediate(value) => match operation {
+                Operation::ShiftLeft | Operation::ShiftRightArithmetic | Operation::ShiftRightLogical => format!("${}", value & 63),
+                _ => format!("${}", value),
+            },
+        };
+
+        // Most instructions only take two operands (and overwrite the first one),
+        // so the result is calculated in the scratch register and moved to the destination afterwards.
+        // If the right operand isn't the destination, the result can be calculated in the destination right away.
+        let mut code = String::new();
+        let in_destination = right != format!("%{}", destination);
+
+        match operation {
+            Operation::DivideSigned | Operation::DivideUnsigned | Operation::RemainderSigned | Operation::RemainderUnsigned => {
+                // Division always divides rdx:rax and puts the quotient into rax and the remainder into rdx,
+                // their values are kept on the stack in the meantime (nothing is called, so the alignment doesn't matter).
+                let is_signed = matches!(operation, Operation::DivideSigned | Operation::RemainderSigned);
+                let result = if matches!(operation, Operation::DivideSigned | Operation::DivideUnsigned) { "rax" } else { "rdx" };
+
+                code += "pushq\t%rax\npushq\t%rdx\n";
+                code += format!("movq\t{}, %{}\n", right, scratch).as_str();
+                code += format!("movq\t%{}, %rax\n", left).as_str();
+                code += if is_signed { "cqto\n" } else { "xorl\t%edx, %edx\n" };
+                code += format!("{}\t%{}\n", if is_signed { "idivq" } else { "divq" }, scratch).as_str();
+                code += format!("movq\t%{}, %{}\n", result, scratch).as_str();
+                code += "popq\t%rdx\npopq\t%rax\n";
+            }
+            Operation::ShiftLeft | Operation::ShiftRightArithmetic | Operation::ShiftRightLogical => {
+                let instruction = match operation {
+                    Operation::ShiftLeft => "shlq",
+                    Operation::ShiftRightArithmetic => "sarq",
+                    _ => "shrq",
+                };
+
+                if right.starts_with('$') && in_destination {
+                    if left != destination { code += format!("movq\t%{}, %{}\n", left, destination).as_str(); }
+                    code += format!("{}\t{}, %{}\n", instruction, right, destination).as_str();
+                    return code;
+                }
+
+                code += format!("movq\t%{}, %{}\n", left, scratch).as_str();
+
+                if right.starts_with('$') {
+                    code += format!("{}\t{}, %{}\n", instruction, right, scratch).as_str();
+                } else {
+                    // Shift amounts have to be in cl
+                    code += "pushq\t%rcx\n";
+                    code += format!("movq\t{}, %rcx\n", right).as_str();
+                    code += format!("{}\t%cl, %{}\n", instruction, scratch).as_str();
+                    code += "popq\t%rcx\n";
+                }
+            }
+            _ if operation.is_comparison() => {
+                let condition = match operation {
+                    Operation::Equal => "e",
+                    Operation::NotEqual => "ne",
+                    Operation::LessSigned => "l",
+                    Operation::LessOrEqualSigned => "le",
+                    Operation::GreaterSigned => "g",
+                    Operation::GreaterOrEqualSigned => "ge",
+                    Operation::LessUnsigned => "b",
+                    Operation::LessOrEqualUnsigned => "be",
+                    Operation::GreaterUnsigned => "a",
+                    _ => "ae",
+                };
+
+                code += format!("cmpq\t{}, %{}\n", right, left).as_str();
+                code += format!("set{}\t%{}b\n", condition, scratch).as_str();
+                code += format!("movzbq\t%{}b, %{}\n", scratch, scratch).as_str();
+            }
+            _ => {
+                let instruction = match operation {
+                    Operation::Add => "addq",
+                    Operation::Subtract => "subq",
+                    Operation::Multiply => "imulq",
+                    Operation::And => "andq",
+                    Operation::Or => "orq",
+                    _ => "xorq",
+                };
+
+                if in_destination {
+                    if left != destination { code += format!("movq\t%{}, %{}\n", left, destination).as_str(); }
+                    code += format!("{}\t{}, %{}\n", instruction, right, destination).as_str();
+                    return code;
+                }
+
+                code += format!("movq\t%{}, %{}\n", left, scratch).as_str();
+                code += format!("{}\t{}, %{}\n", instruction, right, scratch).as_str();
+            }
+        }
+
+        code += format!("movq\t%{}, %{}\n", scratch, destination).as_str();
+
+        code
+    }
+
+    fn compute_unary(&self, operation: UnaryOperation, destination: &str, source: &str) -> String {
+        let instruction = match operation {
+            UnaryOperation::Negate => "negq",
+            UnaryOperation::Not => "notq",
+        };
+
+        let mut code = format!("movq\t%{}, %{}\n", source, destination);
+        code += format!("{}\t%{}\n", instruction, destination).as_str();
+
+        code
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use std::path::PathBuf;
+    use crate::compiler::low_level::arch::arch::Arch;
+    use crate::compiler::low_level::arch::x86_64_linux::x86_64_linux::X86_64Linux;
+    use crate::compiler::low_level::ir_text::parse_ir;
+    use crate::compiler::low_level::macro_function::MacroFunction;
+    use crate::compiler::low_level::macro_instruction::MacroInstruction;
+    use crate::compiler::low_level::operation::{Operand, Operation};
+    use crate::compiler::low_level::variable::Variable;
+
+    /// Build the assembly and run it with two arguments (so argc is 3), returns the exit code.
+    /// Returns None if it can't run on this machine.
+    fn run(name: &str, assembly: String) -> Option<i32> {
+        if !cfg!(all(target_arch = "x86_64", target_os = "linux")) { return None; }
+
+        let directory = std::env::temp_dir().join(format!("rsl-x86_64-linux-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let assembly_path = directory.join("main.s");
+        let executable_path: PathBuf = directory.join("main");
+        std::fs::write(&assembly_path, assembly).unwrap();
+
+        let Ok(status) = std::process::Command::new("cc").arg("-o").arg(&executable_path).arg(&assembly_path).status() else {
+            println!("cc isn't available, not running the executable");
+            return None;
+        };
+        assert!(status.success());
+
+        let status = std::process::Command::new(&executable_path).arg("a").arg("b").status().unwrap();
+
+        _ = std::fs::remove_dir_all(&directory);
+
+        status.code()
+    }
+
+    #[test]
+    fn test_generate_assembly(){
+        let x86_64 = X86_64Linux::new();
+
+        let count = Variable::new("my_app:main.rsl:Main:count".to_string(), vec![]);
+
+        // Exit with the amount of arguments the program got
+        let instructions: Vec<MacroInstruction> = vec![
+            MacroInstruction::DeclareVariable(count.clone()),
+            MacroInstruction::GetArgument(count.clone(), 0),
+            MacroInstruction::UseVariableAsArgument(count.clone(), 0),
+            MacroInstruction::CallFunction("exit".to_string(), 1, None, None),
+            MacroInstruction::DestroyVariable(count.clone()),
+        ];
+
+        let assembly = x86_64.generate_assembly(vec![MacroFunction::new("main".to_string(), 1, false, instructions)]);
+        println!("{}", assembly);
+
+        assert!(assembly.contains("main:\npushq\t%rbp\nmovq\t%rsp, %rbp\n"));
+        assert!(assembly.contains("call\texit\n"));
+
+        if let Some(code) = run("arguments", assembly) {
+            assert_eq!(code, 3);
+        }
+    }
+
+    #[test]
+    fn test_branches(){
+        let x86_64 = X86_64Linux::new();
+
+        let variable = |name: &str| Variable::new(format!("my_app:main.rsl:main:{}", name), vec![]);
+        let (count, result, condition) = (variable("count"), variable("result"), variable("condition"));
+
+        let mut instructions: Vec<MacroInstruction> = vec![
+            MacroInstruction::DeclareVariable(count.clone()),
+            MacroInstruction::GetArgument(count.clone(), 0),
+            MacroInstruction::DeclareVariable(result.clone()),
+            MacroInstruction::Compute(result.clone(), Operation::Multiply, Operand::Variable(count.clone()), Operand::Immediate(5)),
+            MacroInstruction::DeclareVariable(condition.clone()),
+            // Not taken, the variables are in registers here
+            MacroInstruction::Compute(condition.clone(), Operation::GreaterSigned, Operand::Variable(count.clone()), Operand::Immediate(100)),
+            MacroInstruction::BranchIf(condition.clone(), "done".to_string()),
+        ];
+
+        // Use up all the registers, so the variables have to move to the stack
+        let pressure: Vec<Variable> = (0..20).map(|i| variable(&format!("pressure{}", i))).collect();
+        for (i, pressure) in pressure.iter().enumerate() {
+            instructions.push(MacroInstruction::DeclareVariable(pressure.clone()));
+            instructions.push(MacroInstruction::Compute(pressure.clone(), Operation::Add, Operand::Variable(count.clone()), Operand::Immediate(i as i64)));
+        }
+
+        // Nothing that's still needed can stay in a caller-saved register across a call
+        instructions.push(MacroInstruction::CallFunction("getpid".to_string(), 0, None, None));
+
+        // Taken, so the variables need to be moved back to where the label expects them
+        instructions.push(MacroInstruction::Compute(condition.clone(), Operation::GreaterSigned, Operand::Variable(count.clone()), Operand::Immediate(1)));
+        instructions.push(MacroInstruction::BranchIf(condition.clone(), "done".to_string()));
+
+        // Never runs, but it keeps the pressure variables alive until here
+        instructions.push(MacroInstruction::Compute(result.clone(), Operation::Add, Operand::Immediate(1), Operand::Immediate(1)));
+        instructions.extend(pressure.iter().map(|x| MacroInstruction::Compute(result.clone(), Operation::Add, Operand::Variable(result.clone()), Operand::Variable(x.clone()))));
+        instructions.extend(pressure.iter().map(|x| MacroInstruction::DestroyVariable(x.clone())));
+
+        instructions.extend([
+            MacroInstruction::Label("done".to_string()),
+            MacroInstruction::UseVariableAsArgument(result.clone(), 0),
+            MacroInstruction::CallFunction("exit".to_string(), 1, None, None),
+            MacroInstruction::DestroyVariable(condition.clone()),
+            MacroInstruction::DestroyVariable(result.clone()),
+            MacroInstruction::DestroyVariable(count.clone()),
+        ]);
+
+        let assembly = x86_64.generate_assembly(vec![MacroFunction::new("main".to_string(), 1, false, instructions)]);
+        println!("{}", assembly);
+
+        assert!(assembly.contains(".Lmain.done:\n"));
+        assert!(assembly.contains("jnz\t.Lmain.moves"));
+
+        if let Some(code) = run("branches", assembly) {
+            assert_eq!(code, 15);
+        }
+    }
+
+    #[test]
+    fn test_backward_jumps(){
+        let x86_64 = X86_64Linux::new();
+
+        let variable = |name: &str| Variable::new(format!("my_app:main.rsl:main:{}", name), vec![]);
+        let (count, sum, i, condition) = (variable("count"), variable("sum"), variable("i"), variable("condition"));
+
+        // The body of the loop comes first, it's only ever jumped to from the condition below it
+        let mut instructions: Vec<MacroInstruction> = vec![
+            MacroInstruction::DeclareVariable(count.clone()),
+            MacroInstruction::GetArgument(count.clone(), 0),
+            MacroInstruction::DeclareVariable(sum.clone()),
+            MacroInstruction::Compute(sum.clone(), Operation::Add, Operand::Variable(count.clone()), Operand::Immediate(0)),
+            MacroInstruction::DeclareVariable(i.clone()),
+            MacroInstruction::Compute(i.clone(), Operation::Add, Operand::Immediate(0), Operand::Immediate(0)),
+            MacroInstruction::DeclareVariable(condition.clone()),
+            MacroInstruction::Jump("condition".to_string()),
+            MacroInstruction::Label("body".to_string()),
+            MacroInstruction::Compute(sum.clone(), Operation::Add, Operand::Variable(sum.clone()), Operand::Variable(i.clone())),
+        ];
+
+        // Use up all the registers, so the variables are somewhere else at the end of the body
+        let pressure: Vec<Variable> = (0..20).map(|i| variable(&format!("pressure{}", i))).collect();
+        for (n, pressure) in pressure.iter().enumerate() {
+            instructions.push(MacroInstruction::DeclareVariable(pressure.clone()));
+            instructions.push(MacroInstruction::Compute(pressure.clone(), Operation::Add, Operand::Variable(i.clone()), Operand::Immediate(n as i64)));
+        }
+        instructions.extend(pressure.iter().map(|x| MacroInstruction::DestroyVariable(x.clone())));
+
+        instructions.extend([
+            MacroInstruction::Compute(i.clone(), Operation::Add, Operand::Variable(i.clone()), Operand::Immediate(1)),
+            MacroInstruction::Label("condition".to_string()),
+            MacroInstruction::Compute(condition.clone(), Operation::LessSigned, Operand::Variable(i.clone()), Operand::Immediate(10)),
+            MacroInstruction::BranchIf(condition.clone(), "body".to_string()),
+            MacroInstruction::UseVariableAsArgument(sum.clone(), 0),
+            MacroInstruction::CallFunction("exit".to_string(), 1, None, None),
+            MacroInstruction::DestroyVariable(condition.clone()),
+            MacroInstruction::DestroyVariable(i.clone()),
+            MacroInstruction::DestroyVariable(sum.clone()),
+            MacroInstruction::DestroyVariable(count.clone()),
+        ]);
+
+        let assembly = x86_64.generate_assembly(vec![MacroFunction::new("main".to_string(), 1, false, instructions)]);
+        println!("{}", assembly);
+
+        assert!(assembly.contains(".Lmain.body:\n"));
+
+        // 3 + 0 + 1 + ... + 9
+        if let Some(code) = run("backward_jumps", assembly) {
+            assert_eq!(code, 48);
+        }
+    }
+
+    #[test]
+    fn test_functions(){
+        let x86_64 = X86_64Linux::new();
+
+        let a = Variable::new("my_app:main.rsl:max:a".to_string(), vec![]);
+        let b = Variable::new("my_app:main.rsl:max:b".to_string(), vec![]);
+        let less = Variable::new("my_app:main.rsl:max:less".to_string(), vec![]);
+
+        // max(a, b)
+        let max = MacroFunction::new("max".to_string(), 2, true, vec![
+            MacroInstruction::DeclareVariable(a.clone()),
+            MacroInstruction::GetArgument(a.clone(), 0),
+            MacroInstruction::DeclareVariable(b.clone()),
+            MacroInstruction::GetArgument(b.clone(), 1),
+            MacroInstruction::DeclareVariable(less.clone()),
+            MacroInstruction::Compute(less.clone(), Operation::LessSigned, Operand::Variable(a.clone()), Operand::Variable(b.clone())),
+            MacroInstruction::BranchIf(less.clone(), "if1.end".to_string()),
+            MacroInstruction::Return(Some(a.clone())),
+            MacroInstruction::Label("if1.end".to_string()),
+            MacroInstruction::Return(Some(b.clone())),
+            MacroInstruction::DestroyVariable(less.clone()),
+            MacroInstruction::DestroyVariable(b.clone()),
+            MacroInstruction::DestroyVariable(a.clone()),
+        ]);
+
+        let count = Variable::new("my_app:main.rsl:main:count".to_string(), vec![]);
+        let ten = Variable::new("my_app:main.rsl:main:ten".to_string(), vec![]);
+        let result = Variable::new("my_app:main.rsl:main:result".to_string(), vec![]);
+
+        // Exit with max(argc, argc * 3 + 1), which is 10
+        let main = MacroFunction::new("main".to_string(), 1, true, vec![
+            MacroInstruction::DeclareVariable(count.clone()),
+            MacroInstruction::GetArgument(count.clone(), 0),
+            MacroInstruction::DeclareVariable(ten.clone()),
+            MacroInstruction::Compute(ten.clone(), Operation::Multiply, Operand::Variable(count.clone()), Operand::Immediate(3)),
+            MacroInstruction::Compute(ten.clone(), Operation::Add, Operand::Variable(ten.clone()), Operand::Immediate(1)),
+            MacroInstruction::DeclareVariable(result.clone()),
+            MacroInstruction::UseVariableAsArgument(count.clone(), 0),
+            MacroInstruction::UseVariableAsArgument(ten.clone(), 1),
+            MacroInstruction::CallFunction("max".to_string(), 2, None, Some(result.clone())),
+            MacroInstruction::Return(Some(result.clone())),
+        ]);
+
+        let assembly = x86_64.generate_assembly(vec![max, main]);
+        println!("{}", assembly);
+
+        // Only the first return needs to jump to the epilogue
+        assert!(assembly.contains("jmp\t.Lmax.return\n.Lmax.if1.end:\n"));
+        assert!(assembly.contains(".Lmain.return:\n"));
+        assert!(!assembly.contains("jmp\t.Lmain.return\n"));
+
+        if let Some(code) = run("functions", assembly) {
+            assert_eq!(code, 10);
+        }
+    }
+
+    #[test]
+    fn test_constants(){
+        let x86_64 = X86_64Linux::new();
+
+        let text = Variable::new("my_app:main.rsl:main:text".to_string(), vec![]);
+        let same_text = Variable::new("my_app:main.rsl:main:sameText".to_string(), vec![]);
+        let length = Variable::new("my_app:main.rsl:main:length".to_string(), vec![]);
+        let large = Variable::new("my_app:main.rsl:main:large".to_string(), vec![]);
+        let result = Variable::new("my_app:main.rsl:main:result".to_string(), vec![]);
+
+        // Exit with strlen("hello") + (2^40 >> 38), which is 9
+        let main = MacroFunction::new("main".to_string(), 0, true, vec![
+            MacroInstruction::DeclareVariable(text.clone()),
+            MacroInstruction::LoadConstantAddress(text.clone(), b"hello\0".to_vec()),
+            MacroInstruction::DeclareVariable(same_text.clone()),
+            MacroInstruction::LoadConstantAddress(same_text.clone(), b"hello\0".to_vec()),
+            MacroInstruction::DeclareVariable(length.clone()),
+            MacroInstruction::UseVariableAsArgument(text.clone(), 0),
+            MacroInstruction::CallFunction("strlen".to_string(), 1, None, Some(length.clone())),
+            MacroInstruction::DeclareVariable(large.clone()),
+            MacroInstruction::LoadImmediate(large.clone(), 1 << 40),
+            MacroInstruction::Compute(large.clone(), Operation::ShiftRightLogical, Operand::Variable(large.clone()), Operand::Immediate(38)),
+            MacroInstruction::DeclareVariable(result.clone()),
+            MacroInstruction::Copy(result.clone(), length.clone()),
+            MacroInstruction::Compute(result.clone(), Operation::Add, Operand::Variable(result.clone()), Operand::Variable(large.clone())),
+            MacroInstruction::Return(Some(result.clone())),
+        ]);
+
+        let assembly = x86_64.generate_assembly(vec![main]);
+        println!("{}", assembly);
+
+        // Both constants share their bytes
+        assert!(assembly.ends_with(".section\t.rodata\n.Lconstant.0:\n.byte\t104, 101, 108, 108, 111, 0\n.section\t.note.GNU-stack,\"\",@progbits\n"));
+        assert!(assembly.contains("leaq\t.Lconstant.0(%rip), %"));
+
+        if let Some(code) = run("constants", assembly) {
+            assert_eq!(code, 9);
+        }
+    }
+
+    #[test]
+    fn test_pointers(){
+        let x86_64 = X86_64Linux::new();
+
+        let size = Variable::new("my_app:main.rsl:main:size".to_string(), vec![]);
+        let buffer = Variable::new("my_app:main.rsl:main:buffer".to_string(), vec![]);
+        let a = Variable::new("my_app:main.rsl:main:a".to_string(), vec![]);
+        let pointer = Variable::new("my_app:main.rsl:main:pointer".to_string(), vec![]);
+        let value = Variable::new("my_app:main.rsl:main:value".to_string(), vec![]);
+        let result = Variable::new("my_app:main.rsl:main:result".to_string(), vec![]);
+
+        // Set a (which starts out as 5) to 7 through its address, which is kept in a heap object for a while.
+        // a has to be read from memory again afterwards, so the result is 14.
+        let main = MacroFunction::new("main".to_string(), 0, true, vec![
+            MacroInstruction::DeclareVariable(size.clone()),
+            MacroInstruction::LoadImmediate(size.clone(), 4096),
+            MacroInstruction::DeclareVariable(buffer.clone()),
+            MacroInstruction::UseVariableAsArgument(size.clone(), 0),
+            MacroInstruction::CallFunction("malloc".to_string(), 1, None, Some(buffer.clone())),
+            MacroInstruction::DeclareVariable(a.clone()),
+            MacroInstruction::LoadImmediate(a.clone(), 5),
+            MacroInstruction::DeclareVariable(pointer.clone()),
+            MacroInstruction::AddressOf(pointer.clone(), a.clone()),
+            MacroInstruction::Store(buffer.clone(), 4000, pointer.clone()),
+            MacroInstruction::LoadImmediate(pointer.clone(), 0),
+            MacroInstruction::DeclareVariable(value.clone()),
+            MacroInstruction::LoadImmediate(value.clone(), 7),
+            MacroInstruction::Load(pointer.clone(), buffer.clone(), 4000),
+            MacroInstruction::Store(pointer.clone(), 0, value.clone()),
+            MacroInstruction::DeclareVariable(result.clone()),
+            MacroInstruction::Load(result.clone(), pointer.clone(), 0),
+            MacroInstruction::Compute(result.clone(), Operation::Add, Operand::Variable(result.clone()), Operand::Variable(a.clone())),
+            MacroInstruction::Return(Some(result.clone())),
+        ]);
+
+        let assembly = x86_64.generate_assembly(vec![main]);
+        println!("{}", assembly);
+
+        assert!(assembly.contains("leaq\t0(%rsp), %"));
+        assert!(assembly.contains(", 4000(%"));
+
+        if let Some(code) = run("pointers", assembly) {
+            assert_eq!(code, 14);
+        }
+    }
+
+    #[test]
+    fn test_repeated_arguments(){
+        let x86_64 = X86_64Linux::new();
+
+        let (functions, diagnostics) = parse_ir("repeated_arguments.rslir".to_string(), include_str!("../fixtures/repeated_arguments.rslir"));
+        assert_eq!(diagnostics, vec![]);
+
+        let assembly = x86_64.generate_assembly(functions);
+        println!("{}", assembly);
+
+        if let Some(code) = run("repeated_arguments", assembly) {
+            assert_eq!(code, 211);
+        }
+    }
+
+    #[test]
+    fn test_sized_values(){
+        let x86_64 = X86_64Linux::new();
+
+        let (functions, diagnostics) = parse_ir("sized_values.rslir".to_string(), include_str!("../fixtures/sized_values.rslir"));
+        assert_eq!(diagnostics, vec![]);
+
+        let assembly = x86_64.generate_assembly(functions);
+        println!("{}", assembly);
+
+        assert!(assembly.contains("movw\t%"));
+        assert!(assembly.contains("movzbl\t"));
+
+        if let Some(code) = run("sized_values", assembly) {
+            assert_eq!(code, 16);
+        }
+    }
+
+    #[test]
+    fn test_stack_arguments(){
+        let x86_64 = X86_64Linux::new();
+
+        let (functions, diagnostics) = parse_ir("stack_arguments.rslir".to_string(), include_str!("../fixtures/stack_arguments.rslir"));
+        assert_eq!(diagnostics, vec![]);
+
+        let assembly = x86_64.generate_assembly(functions);
+        println!("{}", assembly);
+
+        // Only six arguments fit into registers, the rest is above the return address and the saved rbp
+        assert!(assembly.contains("movq\t16(%rbp), %"));
+        assert!(assembly.contains("movzbl\t32(%rbp), %"));
+        assert!(assembly.contains("movslq\t40(%rbp), %"));
+        assert!(assembly.contains(", 24(%rsp)\n"));
+
+        if let Some(code) = run("stack_arguments", assembly) {
+            assert_eq!(code, 201);
+        }
+    }
+
+    #[test]
+    fn test_shuffled_arguments(){
+        let x86_64 = X86_64Linux::new();
+
+        // Every order of the first four arguments (cycles of up to four registers, also two cycles at once),
+        // and random orders of all eight, which mixes the registers with the arguments on the stack
+        let mut orders: Vec<Vec<usize>> = Vec::new();
+        for first in 0..4 { for second in 0..4 { for third in 0..4 { for fourth in 0..4 {
+            let order = vec![first, second, third, fourth, 4, 5, 6, 7];
+            if (0..4).all(|x| order.contains(&x)) { orders.push(order); }
+        } } } }
+
+        let mut state: u64 = 0x9E3779B97F4A7C15;
+        for _ in 0..40 {
+            let mut order: Vec<usize> = (0..8).collect();
+            for i in (1..8).rev() {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                order.swap(i, (state % (i as u64 + 1)) as usize);
+            }
+            orders.push(order);
+        }
+
+        // weigh puts its arguments together as the digits of a number, the shuffles pass their parameters on in another order
+        let parameters = |count: usize| (0..count).map(|i| format!("    declare %a{}\n    %a{} = get_argument {}\n", i, i, i)).collect::<String>();
+        let mut ir = format!("function @weigh(8) -> value {{\n{}    declare %sum\n    %sum = immediate 0\n", parameters(8));
+        ir += (0..8).map(|i| format!("    %sum = multiply %sum, 10\n    %sum = add %sum, %a{}\n", i)).collect::<String>().as_str();
+        ir += "    return %sum\n}\n";
+
+        for (k, order) in orders.iter().enumerate() {
+            ir += format!("function @shuffle{}(8) -> value {{\n{}    declare %r\n", k, parameters(8)).as_str();
+            ir += order.iter().enumerate().map(|(i, x)| format!("    argument %a{}, {}\n", x, i)).collect::<String>().as_str();
+            ir += "    %r = call @weigh, 8\n    return %r\n}\n";
+        }
+
+        // main calls all of them with 1 to 8 and returns the number of the first one that's wrong (0 if they're all right)
+        ir += "function @main(0) -> value {\n    declare %r\n    declare %wrong\n";
+        ir += (0..8).map(|i| format!("    declare %v{}\n    %v{} = immediate {}\n", i, i, i + 1)).collect::<String>().as_str();
+
+        for (k, order) in orders.iter().enumerate() {
+            let expected = order.iter().fold(0, |sum, x| sum * 10 + x + 1);
+            ir += (0..8).map(|i| format!("    argument %v{}, {}\n", i, i)).collect::<String>().as_str();
+            ir += format!("    %r = call @shuffle{}, 8\n    %wrong = not_equal %r, {}\n    branch_if %wrong, wrong{}\n", k, expected, k).as_str();
+        }
+
+        ir += "    %r = immediate 0\n    return %r\n";
+        ir += (0..orders.len()).map(|k| format!("    label wrong{}\n    %r = immediate {}\n    return %r\n", k, k + 1)).collect::<String>().as_str();
+        ir += "}\n";
+
+        let (functions, diagnostics) = parse_ir("shuffled_arguments.rslir".to_string(), &ir);
+        assert_eq!(diagnostics, vec![]);
+
+        let assembly = x86_64.generate_assembly(functions);
+
+        if let Some(code) = run("shuffled_arguments", assembly) {
+            assert_eq!(code, 0, "{:?} is passed in the wrong order", orders.get(code as usize - 1));
+        }
+    }
+
+    #[test]
+    fn test_call_survivors(){
+        let x86_64 = X86_64Linux::new();
+
+        let (functions, diagnostics) = parse_ir("call_survivors.rslir".to_string(), include_str!("../fixtures/call_survivors.rslir"));
+        assert_eq!(diagnostics, vec![]);
+
+        let assembly = x86_64.generate_assembly(functions);
+        println!("{}", assembly);
+
+        if let Some(code) = run("call_survivors", assembly) {
+            assert_eq!(code, 130);
+        }
+    }
+}
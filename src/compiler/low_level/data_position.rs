@@ -1,14 +1,16 @@
 use crate::compiler::low_level::data_position::DataPosition::Register;
 
 /// Holds all the possible locations data could be at during the runtime of the compiled program
+#[allow(dead_code)] // Stack offsets aren't calculated at runtime yet
 #[derive(Clone, Debug, PartialEq)]
 pub enum DataPosition{
     Register(/*name: */String),
     StackOffset(/*offset:*/usize),
-    StackOffsetAt(/*offset location*/Box<DataPosition>),
-    Heap(/*address at: */Box<DataPosition>, /*size: */usize),
+    StackOffsetAt(/*offset location*/Box<DataPosition>),                  // At the stack pointer + the value at the offset location
+    Heap(/*address at: */Box<DataPosition>, /*offset: */i64),           // At the address (the value at the inner position) + the offset
 }
 
+#[allow(dead_code)]
 impl DataPosition {
     const GENERAL_PURPOSE_REGISTER_NAME: &'static str = "generalPurposeRegister";
 
@@ -0,0 +1,668 @@
+use std::fmt::{Display, Formatter};
+use crate::compiler::diagnostic::Diagnostic;
+use crate::compiler::lexer::span::Span;
+use crate::compiler::low_level::data_position::DataPosition;
+use crate::compiler::low_level::macro_function::MacroFunction;
+use crate::compiler::low_level::macro_instruction::MacroInstruction;
+use crate::compiler::low_level::operation::{Operand, Operation, UnaryOperation};
+use crate::compiler::low_level::variable::{ValueType, Variable};
+
+// The text format of the macro instructions (.rslir files), one instruction per line:
+//
+//     function @main(1) -> value {
+//         declare %my_app:main.rsl:main:count
+//         %my_app:main.rsl:main:count = get_argument 0
+//         %my_app:main.rsl:main:count = add %my_app:main.rsl:main:count, 1
+//         return %my_app:main.rsl:main:count
+//     }
+//
+// Variables start with %, functions with @. Names with other characters than letters, digits and _.:$- are quoted ("...").
+// The positions of a variable (if it has any) follow it in braces (e.g. %a{reg(x0), stack(8)}).
+// Declarations may name the type of the variable (i8 to i64, u8 to u64, f32, f64 and isize, usize for the size of a register),
+// e.g. declare u8 %a. Variables are signed and as large as a register otherwise.
+// Calls of variadic functions name the amount of fixed arguments (e.g. call @printf, 3, variadic 1).
+// Everything after // is a comment.
+
+/// Names that can be written without quotes, they also must not look like a number
+fn is_plain_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|x| x.is_ascii_alphanumeric() || "_.:$-".contains(x)) && name.parse::<i64>().is_err()
+}
+
+/// Write the bytes in quotes, bytes that aren't printable (and " and \) are escaped as \ followed by two hex digits.
+/// Other characters of names are kept as they are, so only the bytes of constants escape everything outside of ASCII.
+fn quote(bytes: &[u8], escape_non_ascii: bool) -> String {
+    let mut text = "\"".to_string();
+
+    let text_part = match escape_non_ascii {
+        true => None,
+        false => std::str::from_utf8(bytes).ok(),
+    };
+
+    match text_part {
+        Some(name) => {
+            for character in name.chars() {
+                match character {
+                    '"' | '\\' => text += format!("\\{:02x}", character as u32).as_str(),
+                    _ if character.is_control() && character.is_ascii() => text += format!("\\{:02x}", character as u32).as_str(),
+                    _ => text.push(character),
+                }
+            }
+        }
+        None => {
+            for byte in bytes {
+                match byte {
+                    b' '..=b'~' if *byte != b'"' && *byte != b'\\' => text.push(*byte as char),
+                    _ => text += format!("\\{:02x}", byte).as_str(),
+                }
+            }
+        }
+    }
+
+    text + "\""
+}
+
+fn name(name: &str) -> String {
+    match is_plain_name(name) {
+        true => name.to_string(),
+        false => quote(name.as_bytes(), false),
+    }
+}
+
+impl Display for DataPosition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DataPosition::Register(register) => write!(f, "reg({})", name(register)),
+            DataPosition::StackOffset(offset) => write!(f, "stack({})", offset),
+            DataPosition::StackOffsetAt(offset) => write!(f, "stack_at({})", offset),
+            DataPosition::Heap(address, offset) => write!(f, "heap({}, {})", address, offset),
+        }
+    }
+}
+
+impl Display for Variable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "%{}", name(&self.full_name))?;
+
+        if !self.positions.is_empty() {
+            let positions = self.positions.iter().map(|x| x.to_string()).collect::<Vec<String>>();
+            write!(f, "{{{}}}", positions.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Operand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Operand::Variable(variable) => write!(f, "{}", variable),
+            Operand::Immediate(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl Display for MacroInstruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MacroInstruction::DeclareVariable(variable) if variable.value_type == ValueType::default() => write!(f, "declare {}", variable),
+            MacroInstruction::DeclareVariable(variable) => write!(f, "declare {} {}", variable.value_type.name(), variable),
+            MacroInstruction::DestroyVariable(variable) => write!(f, "destroy {}", variable),
+            MacroInstruction::UseVariableAsArgument(variable, n) => write!(f, "argument {}, {}", variable, n),
+            MacroInstruction::CallFunction(function, argument_count, fixed_argument_count, result) => {
+                if let Some(result) = result {
+                    write!(f, "{} = ", result)?;
+                }
+                write!(f, "call @{}, {}", name(function), argument_count)?;
+                match fixed_argument_count {
+                    Some(count) => write!(f, ", variadic {}", count),
+                    None => Ok(()),
+                }
+            }
+            MacroInstruction::GetArgument(target, n) => write!(f, "{} = get_argument {}", target, n),
+            MacroInstruction::Return(None) => write!(f, "return"),
+            MacroInstruction::Return(Some(value)) => write!(f, "return {}", value),
+            MacroInstruction::Compute(target, operation, left, right) => write!(f, "{} = {} {}, {}", target, operation.name(), left, right),
+            MacroInstruction::ComputeUnary(target, operation, operand) => write!(f, "{} = {} {}", target, operation.name(), operand),
+            MacroInstruction::LoadImmediate(target, value) => write!(f, "{} = immediate {}", target, value),
+            MacroInstruction::LoadConstantAddress(target, bytes) => write!(f, "{} = constant {}", target, quote(bytes, true)),
+            MacroInstruction::Copy(target, source) => write!(f, "{} = copy {}", target, source),
+            MacroInstruction::Load(target, address, offset) => write!(f, "{} = load {}, {}", target, address, offset),
+            MacroInstruction::Store(address, offset, value) => write!(f, "store {}, {}, {}", address, offset, value),
+            MacroInstruction::AddressOf(target, variable) => write!(f, "{} = address_of {}", target, variable),
+            MacroInstruction::Label(label) => write!(f, "label {}", name(label)),
+            MacroInstruction::Jump(label) => write!(f, "jump {}", name(label)),
+            MacroInstruction::BranchIf(condition, label) => write!(f, "branch_if {}, {}", condition, name(label)),
+        }
+    }
+}
+
+impl Display for MacroFunction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "function @{}({})", name(&self.name), self.parameter_count)?;
+        if self.returns_value {
+            write!(f, " -> value")?;
+        }
+        writeln!(f, " {{")?;
+
+        for instruction in &self.instructions {
+            writeln!(f, "    {}", instruction)?;
+        }
+
+        writeln!(f, "}}")
+    }
+}
+
+/// Write the functions in the text format, separated by empty lines
+pub fn print_functions(functions: &[MacroFunction]) -> String {
+    functions.iter().map(|x| x.to_string()).collect::<Vec<String>>().join("\n")
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum IrTokenKind {
+    Variable(String),       // %name
+    Function(String),       // @name
+    Word(String),           // Names of labels, registers and instructions
+    Number(i64),
+    Text(Vec<u8>),          // Anything in quotes that doesn't belong to a variable or function
+    Symbol(char),           // = , ( ) { }
+    Arrow,                  // ->
+}
+
+#[derive(Clone, Debug)]
+struct IrToken {
+    kind: IrTokenKind,
+    column: usize,
+}
+
+/// Split a line into tokens, returns the message and the column if that's not possible
+fn tokenize_line(line: &str) -> Result<Vec<IrToken>, (String, usize)> {
+    let characters: Vec<char> = line.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    let is_name_character = |x: char| x.is_ascii_alphanumeric() || "_.:$-".contains(x);
+
+    while i < characters.len() {
+        let character = characters[i];
+        let column = i + 1;
+
+        if character.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        if character == '/' && characters.get(i + 1) == Some(&'/') { break; }
+
+        if character == '-' && characters.get(i + 1) == Some(&'>') {
+            tokens.push(IrToken { kind: IrTokenKind::Arrow, column });
+            i += 2;
+            continue;
+        }
+
+        if "=,(){}".contains(character) {
+            tokens.push(IrToken { kind: IrTokenKind::Symbol(character), column });
+            i += 1;
+            continue;
+        }
+
+        // Variables and functions are followed by their name, which might be quoted
+        let prefix = match character {
+            '%' | '@' => { i += 1; Some(character) }
+            _ => None,
+        };
+
+        let (text, is_quoted) = if characters.get(i) == Some(&'"') {
+            let mut bytes: Vec<u8> = Vec::new();
+            i += 1;
+
+            loop {
+                match characters.get(i) {
+                    None => return Err(("The quotes aren't closed.".to_string(), column)),
+                    Some('"') => { i += 1; break; }
+                    Some('\\') => {
+                        let digits: String = characters.iter().skip(i + 1).take(2).collect();
+                        let Ok(byte) = u8::from_str_radix(&digits, 16) else {
+                            return Err(("\\ must be followed by two hex digits.".to_string(), i + 1));
+                        };
+                        bytes.push(byte);
+                        i += 3;
+                    }
+                    Some(x) => {
+                        bytes.extend(x.to_string().as_bytes());
+                        i += 1;
+                    }
+                }
+            }
+
+            (bytes, true)
+        } else {
+            let start = i;
+            while i < characters.len() && is_name_character(characters[i]) { i += 1; }
+
+            if start == i {
+                return Err((format!("Unexpected character '{}'.", characters[i.min(characters.len() - 1)]), i + 1));
+            }
+
+            (characters[start..i].iter().collect::<String>().into_bytes(), false)
+        };
+
+        let as_name = |bytes: Vec<u8>| String::from_utf8(bytes).map_err(|_| ("Names must be valid UTF-8.".to_string(), column));
+
+        let kind = match prefix {
+            Some('%') => IrTokenKind::Variable(as_name(text)?),
+            Some(_) => IrTokenKind::Function(as_name(text)?),
+            None if is_quoted => IrTokenKind::Text(text),
+            None => {
+                let word = as_name(text)?;
+                match word.parse::<i64>() {
+                    Ok(number) => IrTokenKind::Number(number),
+                    Err(_) => IrTokenKind::Word(word),
+                }
+            }
+        };
+
+        tokens.push(IrToken { kind, column });
+    }
+
+    Ok(tokens)
+}
+
+/// Reads the tokens of a single line
+struct LineParser {
+    tokens: Vec<IrToken>,
+    position: usize,
+    end_column: usize,      // Where errors at the end of the line are reported
+}
+
+type LineResult<T> = Result<T, (String, usize)>;
+
+impl LineParser {
+    fn peek(&self) -> Option<&IrTokenKind> {
+        self.tokens.get(self.position).map(|x| &x.kind)
+    }
+
+    fn error<T>(&self, message: &str) -> LineResult<T> {
+        let column = self.tokens.get(self.position).map(|x| x.column).unwrap_or(self.end_column);
+        Err((message.to_string(), column))
+    }
+
+    fn next(&mut self) -> Option<IrTokenKind> {
+        let token = self.tokens.get(self.position).map(|x| x.kind.clone());
+        self.position += 1;
+        token
+    }
+
+    fn symbol(&mut self, symbol: char) -> LineResult<()> {
+        match self.peek() {
+            Some(IrTokenKind::Symbol(x)) if *x == symbol => { self.position += 1; Ok(()) }
+            _ => self.error(&format!("Expected '{}'.", symbol)),
+        }
+    }
+
+    fn end(&self) -> LineResult<()> {
+        match self.peek() {
+            None => Ok(()),
+            Some(_) => self.error("Expected the end of the line."),
+        }
+    }
+
+    fn number(&mut self) -> LineResult<i64> {
+        match self.peek() {
+            Some(IrTokenKind::Number(number)) => { let number = *number; self.position += 1; Ok(number) }
+            _ => self.error("Expected a number."),
+        }
+    }
+
+    fn unsigned(&mut self) -> LineResult<usize> {
+        let number = self.number()?;
+        match usize::try_from(number) {
+            Ok(number) => Ok(number),
+            Err(_) => { self.position -= 1; self.error("Expected a number that isn't negative.") }
+        }
+    }
+
+    /// A label or register, which can be a plain word or quoted
+    fn name(&mut self) -> LineResult<String> {
+        match self.peek().cloned() {
+            Some(IrTokenKind::Word(word)) => { self.position += 1; Ok(word) }
+            Some(IrTokenKind::Text(bytes)) => match String::from_utf8(bytes) {
+                Ok(name) => { self.position += 1; Ok(name) }
+                Err(_) => self.error("Names must be valid UTF-8."),
+            },
+            _ => self.error("Expected a name."),
+        }
+    }
+
+    fn function(&mut self) -> LineResult<String> {
+        match self.peek().cloned() {
+            Some(IrTokenKind::Function(name)) => { self.position += 1; Ok(name) }
+            _ => self.error("Expected a function (@name)."),
+        }
+    }
+
+    /// The function, argument count and (for variadic functions) fixed argument count of a call, e.g. @printf, 3, variadic 1
+    fn call(&mut self) -> LineResult<(String, usize, Option<usize>)> {
+        let function = self.function()?;
+        self.symbol(',')?;
+        let argument_count = self.unsigned()?;
+
+        if self.peek() != Some(&IrTokenKind::Symbol(',')) {
+            return Ok((function, argument_count, None));
+        }
+
+        self.position += 1;
+        if self.name()? != "variadic" {
+            self.position -= 1;
+            return self.error("Expected \"variadic\".");
+        }
+
+        Ok((function, argument_count, Some(self.unsigned()?)))
+    }
+
+    fn position(&mut self) -> LineResult<DataPosition> {
+        let kind = self.name()?;
+        self.symbol('(')?;
+
+        let position = match kind.as_str() {
+            "reg" => DataPosition::Register(self.name()?),
+            "stack" => DataPosition::StackOffset(self.unsigned()?),
+            "stack_at" => DataPosition::StackOffsetAt(Box::new(self.position()?)),
+            "heap" => {
+                let address = self.position()?;
+                self.symbol(',')?;
+                DataPosition::Heap(Box::new(address), self.number()?)
+            }
+            _ => {
+                self.position -= 2;
+                return self.error("Expected a position (reg, stack, stack_at or heap).");
+            }
+        };
+
+        self.symbol(')')?;
+        Ok(position)
+    }
+
+    fn variable(&mut self) -> LineResult<Variable> {
+        let Some(IrTokenKind::Variable(name)) = self.peek().cloned() else {
+            return self.error("Expected a variable (%name).");
+        };
+        self.position += 1;
+
+        let mut positions = Vec::new();
+
+        if self.peek() == Some(&IrTokenKind::Symbol('{')) {
+            self.position += 1;
+
+            loop {
+                positions.push(self.position()?);
+
+                match self.next() {
+                    Some(IrTokenKind::Symbol(',')) => continue,
+                    Some(IrTokenKind::Symbol('}')) => break,
+                    _ => { self.position -= 1; return self.error("Expected ',' or '}'."); }
+                }
+            }
+        }
+
+        Ok(Variable::new(name, positions))
+    }
+
+    fn operand(&mut self) -> LineResult<Operand> {
+        match self.peek() {
+            Some(IrTokenKind::Number(_)) => Ok(Operand::Immediate(self.number()?)),
+            _ => Ok(Operand::Variable(self.variable()?)),
+        }
+    }
+
+    fn instruction(&mut self) -> LineResult<MacroInstruction> {
+        // Instructions that set a variable start with it
+        if let Some(IrTokenKind::Variable(_)) = self.peek() {
+            let target = self.variable()?;
+            self.symbol('=')?;
+
+            let Some(IrTokenKind::Word(word)) = self.peek().cloned() else { return self.error("Expected an instruction."); };
+            self.position += 1;
+
+            let instruction = match word.as_str() {
+                "call" => {
+                    let (function, argument_count, fixed_argument_count) = self.call()?;
+                    MacroInstruction::CallFunction(function, argument_count, fixed_argument_count, Some(target))
+                }
+                "get_argument" => MacroInstruction::GetArgument(target, self.unsigned()?),
+                "immediate" => MacroInstruction::LoadImmediate(target, self.number()?),
+                "constant" => match self.next() {
+                    Some(IrTokenKind::Text(bytes)) => MacroInstruction::LoadConstantAddress(target, bytes),
+                    _ => { self.position -= 1; return self.error("Expected the bytes of the constant in quotes."); }
+                },
+                "copy" => MacroInstruction::Copy(target, self.variable()?),
+                "load" => {
+                    let address = self.variable()?;
+                    self.symbol(',')?;
+                    MacroInstruction::Load(target, address, self.number()?)
+                }
+                "address_of" => MacroInstruction::AddressOf(target, self.variable()?),
+                _ => {
+                    if let Some(operation) = UnaryOperation::ALL.iter().find(|x| x.name() == word) {
+                        MacroInstruction::ComputeUnary(target, *operation, self.operand()?)
+                    } else if let Some(operation) = Operation::ALL.iter().find(|x| x.name() == word) {
+                        let left = self.operand()?;
+                        self.symbol(',')?;
+                        MacroInstruction::Compute(target, *operation, left, self.operand()?)
+                    } else {
+                        self.position -= 1;
+                        return self.error(&format!("Unknown instruction \"{}\".", word));
+                    }
+                }
+            };
+
+            return Ok(instruction);
+        }
+
+        let Some(IrTokenKind::Word(word)) = self.peek().cloned() else { return self.error("Expected an instruction."); };
+        self.position += 1;
+
+        let instruction = match word.as_str() {
+            "declare" => {
+                // The type is optional, it's a signed value as large as a register otherwise
+                let value_type = match self.peek().cloned() {
+                    Some(IrTokenKind::Word(word)) => match ValueType::named(&word) {
+                        Some(value_type) => { self.position += 1; value_type }
+                        None => return self.error(&format!("Unknown type \"{}\".", word)),
+                    },
+                    _ => ValueType::default(),
+                };
+
+                MacroInstruction::DeclareVariable(self.variable()?.with_type(value_type))
+            }
+            "destroy" => MacroInstruction::DestroyVariable(self.variable()?),
+            "argument" => {
+                let variable = self.variable()?;
+                self.symbol(',')?;
+                MacroInstruction::UseVariableAsArgument(variable, self.unsigned()?)
+            }
+            "call" => {
+                let (function, argument_count, fixed_argument_count) = self.call()?;
+                MacroInstruction::CallFunction(function, argument_count, fixed_argument_count, None)
+            }
+            "return" => match self.peek() {
+                None => MacroInstruction::Return(None),
+                _ => MacroInstruction::Return(Some(self.variable()?)),
+            },
+            "store" => {
+                let address = self.variable()?;
+                self.symbol(',')?;
+                let offset = self.number()?;
+                self.symbol(',')?;
+                MacroInstruction::Store(address, offset, self.variable()?)
+            }
+            "label" => MacroInstruction::Label(self.name()?),
+            "jump" => MacroInstruction::Jump(self.name()?),
+            "branch_if" => {
+                let condition = self.variable()?;
+                self.symbol(',')?;
+                MacroInstruction::BranchIf(condition, self.name()?)
+            }
+            _ => {
+                self.position -= 1;
+                return self.error(&format!("Unknown instruction \"{}\".", word));
+            }
+        };
+
+        Ok(instruction)
+    }
+
+    /// function @name(parameter count) -> value {
+    fn function_header(&mut self) -> LineResult<(String, usize, bool)> {
+        let name = self.function()?;
+        self.symbol('(')?;
+        let parameter_count = self.unsigned()?;
+        self.symbol(')')?;
+
+        let returns_value = self.peek() == Some(&IrTokenKind::Arrow);
+        if returns_value {
+            self.position += 1;
+            if self.name()? != "value" {
+                self.position -= 1;
+                return self.error("Expected \"value\".");
+            }
+        }
+
+        self.symbol('{')?;
+        Ok((name, parameter_count, returns_value))
+    }
+}
+
+/// Parse the functions in the text format (see print_functions).
+/// Lines that can't be parsed are skipped and reported, so all the errors of a file are found at once.
+pub fn parse_ir(file: String, text: &str) -> (Vec<MacroFunction>, Vec<Diagnostic>) {
+    let mut functions: Vec<MacroFunction> = Vec::new();
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+
+    // The function whose body is being parsed
+    let mut current: Option<MacroFunction> = None;
+
+    for (i, line) in text.lines().enumerate() {
+        let line_number = i + 1;
+        let mut error = |(message, column): (String, usize)| diagnostics.push(Diagnostic::new(message, Span::new(file.clone(), line_number, column)));
+
+        let tokens = match tokenize_line(line) {
+            Ok(tokens) => tokens,
+            Err(message) => { error(message); continue; }
+        };
+
+        if tokens.is_empty() { continue; }
+
+        let mut parser = LineParser { tokens, position: 0, end_column: line.chars().count() + 1 };
+
+        match (&mut current, parser.peek()) {
+            (Some(_), Some(IrTokenKind::Symbol('}'))) => {
+                parser.position += 1;
+                match parser.end() {
+                    Ok(()) => functions.push(current.take().unwrap()),
+                    Err(message) => error(message),
+                }
+            }
+            (Some(function), _) => {
+                match parser.instruction().and_then(|x| parser.end().map(|_| x)) {
+                    Ok(instruction) => function.instructions.push(instruction),
+                    Err(message) => error(message),
+                }
+            }
+            (None, Some(IrTokenKind::Word(word))) if word == "function" => {
+                parser.position += 1;
+                match parser.function_header().and_then(|x| parser.end().map(|_| x)) {
+                    Ok((name, parameter_count, returns_value)) => current = Some(MacroFunction::new(name, parameter_count, returns_value, Vec::new())),
+                    Err(message) => error(message),
+                }
+            }
+            (None, _) => error(("Expected a function (function @name(parameter count) {).".to_string(), parser.tokens[0].column)),
+        }
+    }
+
+    if let Some(function) = current {
+        let line = text.lines().count().max(1);
+        diagnostics.push(Diagnostic::new(format!("Function \"{}\" isn't closed with '}}'.", function.name), Span::new(file, line, 1)));
+    }
+
+    (functions, diagnostics)
+}
+
+#[cfg(test)]
+mod tests{
+    use crate::compiler::low_level::ir_text::{parse_ir, print_functions};
+    use crate::compiler::lowering::lowering::Lowering;
+    use crate::compiler::parser::parser::parse_source;
+
+    #[test]
+    fn test_round_trip(){
+        let source = "
+            extern fun puts(text: *Int8) -> Int32;
+            extern fun printf(format: *Int8, ...) -> Int32;
+
+            fun main(count: Int) -> Int {
+                var a = -count;
+                let p = &a;
+                *p += 2;
+                while a < 10 && count != 0 {
+                    puts(\"a \\\"quote\\\"\\n\");
+                    a = ~a ^ 3;
+                }
+                printf(\"%d\", a);
+                return *p;
+            }
+        ";
+
+        let (module, diagnostics) = parse_source("main.rsl".to_string(), source);
+        assert_eq!(diagnostics, vec![]);
+
+        let (functions, diagnostics) = Lowering::new(&[module], "my app".to_string()).lower_program();
+        assert_eq!(diagnostics, vec![]);
+
+        let text = print_functions(&functions);
+        println!("{}", text);
+
+        assert!(text.starts_with("function @main(1) -> value {\n    declare %\"my app:main.rsl:main:count\"\n"));
+        assert!(text.contains(" = constant \"a \\22quote\\22\\0a\\00\"\n"));
+        assert!(text.contains("\n    call @printf, 2, variadic 1\n"));
+
+        let (parsed, diagnostics) = parse_ir("main.rslir".to_string(), &text);
+        assert_eq!(diagnostics, vec![]);
+        assert_eq!(print_functions(&parsed), text);
+    }
+
+    #[test]
+    fn test_positions_and_errors(){
+        let text = "
+            // The positions of variables are kept
+            function @\"Main.helper\"(0) {
+                declare %a{reg(x0), stack(8), heap(stack_at(reg(x1)), -16)}
+                declare u8 %b
+                declare x7 %c
+                %a = less_or_equal_unsigned %a, -3
+                call @puts 1
+                %a = frobnicate %a
+                label 12
+                store %a, 8, %a
+            }
+
+            function @main(0) {
+        ";
+
+        let (functions, diagnostics) = parse_ir("test.rslir".to_string(), text);
+
+        assert_eq!(functions.len(), 1);
+        assert_eq!(functions[0].name, "Main.helper");
+        assert_eq!(functions[0].instructions[0].to_string(), "declare %a{reg(x0), stack(8), heap(stack_at(reg(x1)), -16)}");
+        assert_eq!(functions[0].instructions[1].to_string(), "declare u8 %b");
+        assert_eq!(functions[0].instructions.len(), 4);
+
+        let messages: Vec<String> = diagnostics.iter().map(|x| x.to_string()).collect();
+        assert_eq!(messages, vec![
+            "test.rslir:6:25: Unknown type \"x7\".",
+            "test.rslir:8:28: Expected ','.",
+            "test.rslir:9:22: Unknown instruction \"frobnicate\".",
+            "test.rslir:10:23: Expected a name.",
+            "test.rslir:15:1: Function \"main\" isn't closed with '}'.",
+        ]);
+    }
+}
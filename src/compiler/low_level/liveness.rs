@@ -0,0 +1,180 @@
+use std::collections::{HashMap, HashSet};
+use crate::compiler::low_level::macro_instruction::MacroInstruction;
+
+/// Which variables of a function are live where and when they're read next.
+/// A variable is live before an instruction if its current value might still be read on some path from there on,
+/// so a variable that's only written (or not used at all) anymore is dead.
+pub struct Liveness {
+    // The instructions that may run after each instruction
+    successors: Vec<Vec<usize>>,
+    // The variables that are live before each instruction and the least amount of instructions until they're read (0 if the instruction reads them)
+    next_uses: Vec<HashMap<String, usize>>,
+    // Every variable the instructions mention, the others (e.g. pseudo-variables of the code generator) aren't known here
+    variables: HashSet<String>,
+}
+
+impl Liveness {
+    /// Analyze the instructions of a function (backwards, until nothing changes anymore, as loops feed uses back up)
+    pub fn analyze(instructions: &[MacroInstruction]) -> Liveness {
+        let labels: HashMap<&str, usize> = instructions.iter().enumerate().filter_map(|(i, instruction)| match instruction {
+            MacroInstruction::Label(label) => Some((label.as_str(), i)),
+            _ => None,
+        }).collect();
+
+        // Labels that don't exist are the verifier's business, they're just not followed here
+        let successors: Vec<Vec<usize>> = instructions.iter().enumerate().map(|(i, instruction)| {
+            let mut successors = match instruction {
+                MacroInstruction::Jump(label) => labels.get(label.as_str()).copied().into_iter().collect(),
+                MacroInstruction::BranchIf(_, label) => labels.get(label.as_str()).copied().into_iter().chain([i + 1]).collect(),
+                MacroInstruction::Return(_) => vec![],
+                _ => vec![i + 1],
+            };
+
+            successors.retain(|&x| x < instructions.len());
+            successors
+        }).collect();
+
+        let mut variables: HashSet<String> = HashSet::new();
+
+        for instruction in instructions {
+            match instruction {
+                MacroInstruction::DeclareVariable(variable) | MacroInstruction::DestroyVariable(variable) => { variables.insert(variable.full_name.clone()); }
+                _ => {}
+            }
+
+            variables.extend(instruction.read_variables().into_iter().chain(instruction.written_variable()).map(|x| x.full_name.clone()));
+        }
+
+        let mut next_uses: Vec<HashMap<String, usize>> = vec![HashMap::new(); instructions.len()];
+
+        // The distances only ever shrink, so this ends
+        let mut changed = true;
+
+        while changed {
+            changed = false;
+
+            for (i, instruction) in instructions.iter().enumerate().rev() {
+                let mut uses: HashMap<String, usize> = HashMap::new();
+
+                for &successor in &successors[i] {
+                    for (name, distance) in &next_uses[successor] {
+                        let entry = uses.entry(name.clone()).or_insert(usize::MAX);
+                        *entry = (*entry).min(distance + 1);
+                    }
+                }
+
+                // The value that's written here is a new one, the old one is dead unless it's read first
+                if let Some(written) = instruction.written_variable() {
+                    uses.remove(&written.full_name);
+                }
+
+                for read in instruction.read_variables() {
+                    uses.insert(read.full_name.clone(), 0);
+                }
+
+                if uses != next_uses[i] {
+                    next_uses[i] = uses;
+                    changed = true;
+                }
+            }
+        }
+
+        Liveness { successors, next_uses, variables }
+    }
+
+    /// The variables that are live after the instruction
+    pub fn live_out(&self, index: usize) -> HashSet<&String> {
+        self.successors[index].iter().flat_map(|&x| self.next_uses[x].keys()).collect()
+    }
+
+    /// How many instructions it takes (at least) from the instruction on until the variable is read, None if it's dead there
+    pub fn next_use(&self, index: usize, name: &str) -> Option<usize> {
+        self.next_uses.get(index)?.get(name).copied()
+    }
+
+    /// Whether the value of the variable can't be read anymore from the instruction on.
+    /// Names the instructions don't mention are never dead, as nothing is known about them.
+    pub fn is_dead(&self, index: usize, name: &str) -> bool {
+        self.variables.contains(name) && self.next_use(index, name).is_none()
+    }
+
+    /// Whether the value of the variable can't be read anymore after the instruction (see is_dead)
+    pub fn is_dead_after(&self, index: usize, name: &str) -> bool {
+        self.variables.contains(name) && !self.live_out(index).iter().any(|x| *x == name)
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use std::collections::HashSet;
+    use crate::compiler::low_level::ir_text::parse_ir;
+    use crate::compiler::low_level::liveness::Liveness;
+
+    fn names(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|x| x.to_string()).collect()
+    }
+
+    #[test]
+    fn test_straight_line(){
+        let (functions, diagnostics) = parse_ir("test.rslir".to_string(), "
+            function @f(0) -> value {
+                declare %a
+                declare %b
+                %a = immediate 1
+                %b = add %a, 2
+                %a = immediate 3
+                return %b
+            }
+        ");
+        assert_eq!(diagnostics, vec![]);
+
+        let liveness = Liveness::analyze(&functions[0].instructions);
+        let live_out = |i: usize| liveness.live_out(i).into_iter().cloned().collect::<HashSet<String>>();
+
+        assert_eq!(live_out(1), names(&[]));
+        assert_eq!(live_out(2), names(&["a"]));
+        assert_eq!(live_out(3), names(&["b"]));
+        assert_eq!(liveness.next_use(4, "b"), Some(1));
+        assert_eq!(liveness.next_use(3, "a"), Some(0));
+
+        // a is overwritten without being read again
+        assert!(liveness.is_dead(4, "a"));
+        assert!(!liveness.is_dead(4, "b"));
+        assert!(!liveness.is_dead(4, "argument-0"));
+    }
+
+    #[test]
+    fn test_loop(){
+        let (functions, diagnostics) = parse_ir("test.rslir".to_string(), "
+            function @f(0) -> value {
+                declare %i
+                declare %sum
+                declare %condition
+                %i = immediate 10
+                %sum = immediate 0
+                label loop
+                %sum = add %sum, %i
+                %i = subtract %i, 1
+                %condition = not_equal %i, 0
+                branch_if %condition, loop
+                return %sum
+            }
+        ");
+        assert_eq!(diagnostics, vec![]);
+
+        let liveness = Liveness::analyze(&functions[0].instructions);
+        let live_out = |i: usize| liveness.live_out(i).into_iter().cloned().collect::<HashSet<String>>();
+
+        // The branch back keeps both alive, even though i isn't read after the loop
+        assert_eq!(live_out(9), names(&["i", "sum"]));
+        assert_eq!(live_out(8), names(&["i", "sum", "condition"]));
+        assert_eq!(live_out(10), names(&[]));
+
+        // i is only read again in the loop, sum is read sooner by the return than by the next iteration
+        assert_eq!(liveness.next_use(9, "i"), Some(2));
+        assert_eq!(liveness.next_use(7, "sum"), Some(3));
+        assert!(liveness.is_dead(10, "i"));
+        assert!(liveness.is_dead_after(9, "condition"));
+        assert!(!liveness.is_dead_after(9, "i"));
+    }
+}
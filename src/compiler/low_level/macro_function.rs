@@ -0,0 +1,16 @@
+use crate::compiler::low_level::macro_instruction::MacroInstruction;
+
+/// A function of the program, its body is made up of macro instructions
+#[derive(Clone, Debug)]
+pub struct MacroFunction {
+    pub name: String,                       // The symbol of the function (e.g. main or Main.helper)
+    pub parameter_count: usize,
+    pub returns_value: bool,                // Whether every Return of the function has a value
+    pub instructions: Vec<MacroInstruction>,
+}
+
+impl MacroFunction {
+    pub fn new(name: String, parameter_count: usize, returns_value: bool, instructions: Vec<MacroInstruction>) -> MacroFunction {
+        MacroFunction { name, parameter_count, returns_value, instructions }
+    }
+}
@@ -1,11 +1,57 @@
+use crate::compiler::low_level::operation::{Operand, Operation, UnaryOperation};
 use crate::compiler::low_level::variable::Variable;
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub enum MacroInstruction {
     DeclareVariable(Variable),                      // Declare a variable exists and potentially reserve space on the stack/heap
     DestroyVariable(Variable),
 
     UseVariableAsArgument(Variable, usize),
-    CallFunction(/*name: */String, /*argument_count: */usize),
-    GetArgument(/*n-th argument n=*/usize)
-}
\ No newline at end of file
+    CallFunction(/*name: */String, /*argument_count: */usize, /*fixed_argument_count: */Option<usize>, /*result: */Option<Variable>),  // The result (if there is one) is stored in a (declared) variable
+                                                    // Variadic functions (e.g. printf) have a fixed argument count, the arguments after those are the variadic ones
+    GetArgument(/*target: */Variable, /*n-th argument n=*/usize),  // Store the n-th argument of the current function in a (declared) variable
+    Return(/*value: */Option<Variable>),            // Leave the current function, all variables are dead afterwards
+
+    Compute(/*target: */Variable, Operation, /*left: */Operand, /*right: */Operand),  // target = left <operation> right
+    ComputeUnary(/*target: */Variable, UnaryOperation, Operand),
+    LoadImmediate(/*target: */Variable, /*value: */i64),
+    LoadConstantAddress(/*target: */Variable, /*bytes: */Vec<u8>),    // target = the address of the bytes, which are put into read-only memory
+    Copy(/*target: */Variable, /*source: */Variable),
+
+    // Memory is accessed with the size of the loaded (or stored) variable's type, loads extend the value to the whole register.
+    // Variables whose address is taken always live in memory, so writes through the address are seen by later reads.
+    Load(/*target: */Variable, /*address: */Variable, /*offset: */i64),    // target = *(address + offset)
+    Store(/*address: */Variable, /*offset: */i64, /*value: */Variable),    // *(address + offset) = value
+    AddressOf(/*target: */Variable, /*variable: */Variable),
+
+    // Labels are local to their function (e.g. loop1.start), every jump to a label must find the same variables alive (except for the ones that aren't needed there anymore)
+    Label(String),
+    Jump(/*label: */String),
+    BranchIf(/*condition: */Variable, /*label: */String),   // Jump if the condition isn't 0
+}
+
+impl MacroInstruction {
+    /// The variables whose values the instruction reads
+    pub fn read_variables(&self) -> Vec<&Variable> {
+        match self {
+            MacroInstruction::UseVariableAsArgument(variable, _) | MacroInstruction::Copy(_, variable) |
+            MacroInstruction::Load(_, variable, _) | MacroInstruction::AddressOf(_, variable) | MacroInstruction::BranchIf(variable, _) => vec![variable],
+            MacroInstruction::Return(value) => value.iter().collect(),
+            MacroInstruction::Compute(_, _, left, right) => left.variable().into_iter().chain(right.variable()).collect(),
+            MacroInstruction::ComputeUnary(_, _, operand) => operand.variable().into_iter().collect(),
+            MacroInstruction::Store(address, _, value) => vec![address, value],
+            _ => vec![],
+        }
+    }
+
+    /// The variable the instruction sets (if there is one)
+    pub fn written_variable(&self) -> Option<&Variable> {
+        match self {
+            MacroInstruction::CallFunction(_, _, _, result) => result.as_ref(),
+            MacroInstruction::GetArgument(target, _) | MacroInstruction::Compute(target, _, _, _) | MacroInstruction::ComputeUnary(target, _, _) |
+            MacroInstruction::LoadImmediate(target, _) | MacroInstruction::LoadConstantAddress(target, _) | MacroInstruction::Copy(target, _) |
+            MacroInstruction::Load(target, _, _) | MacroInstruction::AddressOf(target, _) => Some(target),
+            _ => None,
+        }
+    }
+}
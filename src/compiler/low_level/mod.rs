@@ -1,4 +1,9 @@
 pub mod arch;
+pub mod macro_function;
 pub mod macro_instruction;
+pub mod operation;
 pub mod variable;
-pub mod data_position;
\ No newline at end of file
+pub mod data_position;
+pub mod ir_text;
+pub mod verifier;
+pub mod liveness;
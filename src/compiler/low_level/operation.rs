@@ -0,0 +1,224 @@
+use crate::compiler::low_level::variable::Variable;
+
+/// A value an operation works with
+#[derive(Clone, Debug)]
+pub enum Operand {
+    Variable(Variable),
+    Immediate(i64),
+}
+
+impl Operand {
+    /// The variable the operand reads (if it isn't an immediate)
+    pub fn variable(&self) -> Option<&Variable> {
+        match self {
+            Operand::Variable(variable) => Some(variable),
+            Operand::Immediate(_) => None,
+        }
+    }
+}
+
+/// Operations with two operands, comparisons result in 1 (true) or 0 (false)
+#[allow(dead_code)] // Values don't have types yet, so nothing is unsigned
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operation {
+    Add,
+    Subtract,
+    Multiply,
+    DivideSigned,
+    DivideUnsigned,
+    RemainderSigned,
+    RemainderUnsigned,
+    And,
+    Or,
+    Xor,
+    ShiftLeft,
+    ShiftRightArithmetic,       // Fills up with the sign bit
+    ShiftRightLogical,          // Fills up with zeros
+    Equal,
+    NotEqual,
+    LessSigned,
+    LessOrEqualSigned,
+    GreaterSigned,
+    GreaterOrEqualSigned,
+    LessUnsigned,
+    LessOrEqualUnsigned,
+    GreaterUnsigned,
+    GreaterOrEqualUnsigned,
+}
+
+impl Operation {
+    pub const ALL: [Operation; 23] = [
+        Operation::Add, Operation::Subtract, Operation::Multiply,
+        Operation::DivideSigned, Operation::DivideUnsigned, Operation::RemainderSigned, Operation::RemainderUnsigned,
+        Operation::And, Operation::Or, Operation::Xor,
+        Operation::ShiftLeft, Operation::ShiftRightArithmetic, Operation::ShiftRightLogical,
+        Operation::Equal, Operation::NotEqual,
+        Operation::LessSigned, Operation::LessOrEqualSigned, Operation::GreaterSigned, Operation::GreaterOrEqualSigned,
+        Operation::LessUnsigned, Operation::LessOrEqualUnsigned, Operation::GreaterUnsigned, Operation::GreaterOrEqualUnsigned,
+    ];
+
+    /// The name of the operation in the text format of the macro instructions
+    pub fn name(&self) -> &'static str {
+        match self {
+            Operation::Add => "add",
+            Operation::Subtract => "subtract",
+            Operation::Multiply => "multiply",
+            Operation::DivideSigned => "divide_signed",
+            Operation::DivideUnsigned => "divide_unsigned",
+            Operation::RemainderSigned => "remainder_signed",
+            Operation::RemainderUnsigned => "remainder_unsigned",
+            Operation::And => "and",
+            Operation::Or => "or",
+            Operation::Xor => "xor",
+            Operation::ShiftLeft => "shift_left",
+            Operation::ShiftRightArithmetic => "shift_right_arithmetic",
+            Operation::ShiftRightLogical => "shift_right_logical",
+            Operation::Equal => "equal",
+            Operation::NotEqual => "not_equal",
+            Operation::LessSigned => "less_signed",
+            Operation::LessOrEqualSigned => "less_or_equal_signed",
+            Operation::GreaterSigned => "greater_signed",
+            Operation::GreaterOrEqualSigned => "greater_or_equal_signed",
+            Operation::LessUnsigned => "less_unsigned",
+            Operation::LessOrEqualUnsigned => "less_or_equal_unsigned",
+            Operation::GreaterUnsigned => "greater_unsigned",
+            Operation::GreaterOrEqualUnsigned => "greater_or_equal_unsigned",
+        }
+    }
+
+    pub fn is_comparison(&self) -> bool {
+        matches!(self,
+            Operation::Equal | Operation::NotEqual |
+            Operation::LessSigned | Operation::LessOrEqualSigned | Operation::GreaterSigned | Operation::GreaterOrEqualSigned |
+            Operation::LessUnsigned | Operation::LessOrEqualUnsigned | Operation::GreaterUnsigned | Operation::GreaterOrEqualUnsigned
+        )
+    }
+
+    /// The operation that gives the same result when the operands are swapped (if there is one).
+    /// e.g. a < b is the same as b > a
+    pub fn swapped(&self) -> Option<Operation> {
+        match self {
+            Operation::Add | Operation::Multiply | Operation::And | Operation::Or | Operation::Xor |
+            Operation::Equal | Operation::NotEqual => Some(*self),
+            Operation::LessSigned => Some(Operation::GreaterSigned),
+            Operation::LessOrEqualSigned => Some(Operation::GreaterOrEqualSigned),
+            Operation::GreaterSigned => Some(Operation::LessSigned),
+            Operation::GreaterOrEqualSigned => Some(Operation::LessOrEqualSigned),
+            Operation::LessUnsigned => Some(Operation::GreaterUnsigned),
+            Operation::LessOrEqualUnsigned => Some(Operation::GreaterOrEqualUnsigned),
+            Operation::GreaterUnsigned => Some(Operation::LessUnsigned),
+            Operation::GreaterOrEqualUnsigned => Some(Operation::LessOrEqualUnsigned),
+            _ => None,
+        }
+    }
+
+    /// The comparison that has the opposite result (e.g. a >= b for a < b)
+    pub fn inverted(&self) -> Option<Operation> {
+        match self {
+            Operation::Equal => Some(Operation::NotEqual),
+            Operation::NotEqual => Some(Operation::Equal),
+            Operation::LessSigned => Some(Operation::GreaterOrEqualSigned),
+            Operation::LessOrEqualSigned => Some(Operation::GreaterSigned),
+            Operation::GreaterSigned => Some(Operation::LessOrEqualSigned),
+            Operation::GreaterOrEqualSigned => Some(Operation::LessSigned),
+            Operation::LessUnsigned => Some(Operation::GreaterOrEqualUnsigned),
+            Operation::LessOrEqualUnsigned => Some(Operation::GreaterUnsigned),
+            Operation::GreaterUnsigned => Some(Operation::LessOrEqualUnsigned),
+            Operation::GreaterOrEqualUnsigned => Some(Operation::LessUnsigned),
+            _ => None,
+        }
+    }
+
+    /// Calculate the result for two 64-bit values (like the native backends would at runtime).
+    /// Shift amounts only use their lowest 6 bits.
+    /// Returns None if the result isn't defined (dividing by zero).
+    pub fn evaluate(&self, left: i64, right: i64) -> Option<i64> {
+        let (left_unsigned, right_unsigned) = (left as u64, right as u64);
+
+        let result = match self {
+            Operation::Add => left.wrapping_add(right),
+            Operation::Subtract => left.wrapping_sub(right),
+            Operation::Multiply => left.wrapping_mul(right),
+            Operation::DivideSigned => left.checked_div(right).or(if right == -1 { Some(left.wrapping_neg()) } else { None })?,
+            Operation::DivideUnsigned => left_unsigned.checked_div(right_unsigned)? as i64,
+            Operation::RemainderSigned => left.checked_rem(right).or(if right == -1 { Some(0) } else { None })?,
+            Operation::RemainderUnsigned => left_unsigned.checked_rem(right_unsigned)? as i64,
+            Operation::And => left & right,
+            Operation::Or => left | right,
+            Operation::Xor => left ^ right,
+            Operation::ShiftLeft => left.wrapping_shl(right as u32 & 63),
+            Operation::ShiftRightArithmetic => left.wrapping_shr(right as u32 & 63),
+            Operation::ShiftRightLogical => left_unsigned.wrapping_shr(right as u32 & 63) as i64,
+            Operation::Equal => (left == right) as i64,
+            Operation::NotEqual => (left != right) as i64,
+            Operation::LessSigned => (left < right) as i64,
+            Operation::LessOrEqualSigned => (left <= right) as i64,
+            Operation::GreaterSigned => (left > right) as i64,
+            Operation::GreaterOrEqualSigned => (left >= right) as i64,
+            Operation::LessUnsigned => (left_unsigned < right_unsigned) as i64,
+            Operation::LessOrEqualUnsigned => (left_unsigned <= right_unsigned) as i64,
+            Operation::GreaterUnsigned => (left_unsigned > right_unsigned) as i64,
+            Operation::GreaterOrEqualUnsigned => (left_unsigned >= right_unsigned) as i64,
+        };
+
+        Some(result)
+    }
+}
+
+/// Operations with a single operand
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnaryOperation {
+    Negate,     // Two's complement (-x)
+    Not,        // Flip all the bits (~x)
+}
+
+impl UnaryOperation {
+    pub const ALL: [UnaryOperation; 2] = [UnaryOperation::Negate, UnaryOperation::Not];
+
+    /// The name of the operation in the text format of the macro instructions
+    pub fn name(&self) -> &'static str {
+        match self {
+            UnaryOperation::Negate => "negate",
+            UnaryOperation::Not => "not",
+        }
+    }
+
+    pub fn evaluate(&self, value: i64) -> i64 {
+        match self {
+            UnaryOperation::Negate => value.wrapping_neg(),
+            UnaryOperation::Not => !value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use crate::compiler::low_level::operation::{Operation, UnaryOperation};
+
+    #[test]
+    fn test_evaluate(){
+        assert_eq!(Operation::Subtract.evaluate(3, 5), Some(-2));
+        assert_eq!(Operation::DivideSigned.evaluate(-7, 2), Some(-3));
+        assert_eq!(Operation::DivideUnsigned.evaluate(-1, 2), Some(i64::MAX));
+        assert_eq!(Operation::RemainderSigned.evaluate(-7, 2), Some(-1));
+        assert_eq!(Operation::DivideSigned.evaluate(1, 0), None);
+        assert_eq!(Operation::DivideSigned.evaluate(i64::MIN, -1), Some(i64::MIN));
+        assert_eq!(Operation::ShiftRightArithmetic.evaluate(-8, 1), Some(-4));
+        assert_eq!(Operation::ShiftRightLogical.evaluate(-8, 60), Some(15));
+        assert_eq!(Operation::ShiftLeft.evaluate(1, 65), Some(2));
+        assert_eq!(Operation::LessUnsigned.evaluate(-1, 1), Some(0));
+        assert_eq!(Operation::LessSigned.evaluate(-1, 1), Some(1));
+        assert_eq!(UnaryOperation::Not.evaluate(0), -1);
+
+        // Swapping the operands of the swapped operation must not change the result
+        for operation in [Operation::LessSigned, Operation::GreaterOrEqualUnsigned, Operation::Xor] {
+            assert_eq!(operation.evaluate(-3, 4), operation.swapped().unwrap().evaluate(4, -3));
+        }
+        assert_eq!(Operation::Subtract.swapped(), None);
+
+        for operation in [Operation::Equal, Operation::LessOrEqualSigned, Operation::GreaterUnsigned] {
+            assert_eq!(operation.evaluate(-3, 4), operation.inverted().unwrap().evaluate(-3, 4).map(|x| 1 - x));
+        }
+        assert_eq!(Operation::Add.inverted(), None);
+    }
+}
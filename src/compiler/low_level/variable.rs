@@ -1,17 +1,23 @@
-use std::ops::Deref;
 use crate::compiler::low_level::arch::arch::Arch;
 use crate::compiler::low_level::data_position::DataPosition;
 use crate::util::exit::{exit, ExitCode};
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct Variable {
     pub full_name: String,              // The full name of the variable (e.g. my_app:main.rsl:Main:loop1:myVar)
-    pub positions: Vec<DataPosition>    // All the positions the data position is currently stored in (might be in a register and on the stack at the same time)
+    pub positions: Vec<DataPosition>,   // All the positions the data position is currently stored in (might be in a register and on the stack at the same time)
+    pub value_type: ValueType,          // The size and the kind of the value, only the declaration's counts
 }
 
 impl Variable {
+    /// A variable that's as large as the architecture's registers and signed
     pub fn new(full_name: String, positions: Vec<DataPosition>) -> Variable {
-        Variable { full_name, positions }
+        Variable { full_name, positions, value_type: ValueType::default() }
+    }
+
+    pub fn with_type(mut self, value_type: ValueType) -> Variable {
+        self.value_type = value_type;
+        self
     }
 
     pub fn get_cheapest_position(&self) -> Option<DataPosition> {
@@ -63,8 +69,8 @@ impl Variable {
 
         // Finally, replace all the positions with the stack position (if it exists)
         // or nothing if it's non-existent.
-        if stack_position.is_some() {
-            self.positions = vec![stack_position.unwrap()];
+        if let Some(stack_position) = stack_position {
+            self.positions = vec![stack_position];
         }else{
             self.positions = vec![];
         }
@@ -91,14 +97,12 @@ impl Variable {
             }
         }
 
-        if stack_position.is_none() { return None }
-
-        stack_position.unwrap().immediate_stack_offset()
+        stack_position?.immediate_stack_offset()
     }
 }
 
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum BitUnit {
     Byte,
     Word,
@@ -108,16 +112,13 @@ pub enum BitUnit {
 }
 
 impl BitUnit {
-    fn resolve(&self, arch: Box<dyn Arch>) -> BitUnit {
+    pub fn resolve(&self, arch: &dyn Arch) -> BitUnit {
         if !matches!(self, BitUnit::ArchitectureMax){
             // Not architecture dependent, just return the unit itself
             return (*self).clone();
         }
 
         // ArchitectureMax option, just use the max of the architecture
-
-        let arch = arch.deref();
-
         let arch_max_bits = arch.architecture_bits();
 
         match arch_max_bits {
@@ -130,4 +131,110 @@ impl BitUnit {
             }
         }
     }
-}
\ No newline at end of file
+
+    /// The amount of bits in the unit, None for ArchitectureMax (which has to be resolved first)
+    pub fn bits(&self) -> Option<u8> {
+        match self {
+            BitUnit::Byte => Some(8),
+            BitUnit::Word => Some(16),
+            BitUnit::DoubleWord => Some(32),
+            BitUnit::QuadWord => Some(64),
+            BitUnit::ArchitectureMax => None,
+        }
+    }
+}
+
+/// How the bits of a value are interpreted
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ValueKind {
+    Signed,
+    Unsigned,
+    Float,
+}
+
+/// The size and the kind of a value.
+/// Values that are smaller than a register are always kept sign- or zero-extended to the whole register
+/// (floats count as unsigned, their bits are just moved around).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValueType {
+    pub unit: BitUnit,
+    pub kind: ValueKind,
+}
+
+impl Default for ValueType {
+    fn default() -> Self {
+        ValueType::new(BitUnit::ArchitectureMax, ValueKind::Signed)
+    }
+}
+
+impl ValueType {
+    pub fn new(unit: BitUnit, kind: ValueKind) -> ValueType {
+        ValueType { unit, kind }
+    }
+
+    /// The amount of bits of the value on the architecture
+    pub fn bits(&self, arch: &dyn Arch) -> u8 {
+        self.unit.resolve(arch).bits().unwrap()
+    }
+
+    /// The amount of bytes the value takes up in memory (which is also its alignment)
+    pub fn bytes(&self, arch: &dyn Arch) -> usize {
+        self.bits(arch) as usize / 8
+    }
+
+    /// Whether the value is sign-extended to the whole register (instead of zero-extended)
+    pub fn is_signed(&self) -> bool {
+        self.kind == ValueKind::Signed
+    }
+
+    /// Whether the value fills a whole register of the architecture
+    pub fn is_full_width(&self, arch: &dyn Arch) -> bool {
+        self.bits(arch) == arch.architecture_bits()
+    }
+
+    /// Cut the value off to the size of the type and extend it again (as it would be in a register)
+    pub fn normalize(&self, arch: &dyn Arch, value: i64) -> i64 {
+        let unused_bits = 64 - self.bits(arch) as u32;
+        if unused_bits == 0 { return value; }
+
+        match self.is_signed() {
+            true => (value << unused_bits) >> unused_bits,
+            false => ((value as u64) << unused_bits >> unused_bits) as i64,
+        }
+    }
+
+    /// The name of the type in the text format of macro instructions (e.g. i8, u32, f64, isize)
+    pub fn name(&self) -> String {
+        let kind = match self.kind {
+            ValueKind::Signed => "i",
+            ValueKind::Unsigned => "u",
+            ValueKind::Float => "f",
+        };
+
+        match self.unit.bits() {
+            Some(bits) => format!("{}{}", kind, bits),
+            None => format!("{}size", kind),
+        }
+    }
+
+    /// The type with the given name (see name)
+    pub fn named(name: &str) -> Option<ValueType> {
+        let kind = match name.chars().next()? {
+            'i' => ValueKind::Signed,
+            'u' => ValueKind::Unsigned,
+            'f' => ValueKind::Float,
+            _ => return None,
+        };
+
+        let unit = match &name[1..] {
+            "8" => BitUnit::Byte,
+            "16" => BitUnit::Word,
+            "32" => BitUnit::DoubleWord,
+            "64" => BitUnit::QuadWord,
+            "size" => BitUnit::ArchitectureMax,
+            _ => return None,
+        };
+
+        Some(ValueType::new(unit, kind))
+    }
+}
@@ -0,0 +1,386 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use crate::compiler::low_level::macro_function::MacroFunction;
+use crate::compiler::low_level::macro_instruction::MacroInstruction;
+use crate::compiler::low_level::operation::Operand;
+use crate::compiler::low_level::variable::Variable;
+
+/// Something that's wrong with the instructions of a function
+#[derive(Clone, Debug, PartialEq)]
+pub struct VerificationError {
+    pub function: String,
+    pub index: usize,           // The index of the instruction in the function
+    pub instruction: String,    // The instruction in the text format
+    pub message: String,
+}
+
+impl Display for VerificationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Function \"{}\", instruction {} ({}): {}", self.function, self.index, self.instruction, self.message)
+    }
+}
+
+/// What's known about a variable at some point of a function
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum VariableState {
+    Alive,
+    Destroyed,
+    Partial,        // Alive on some paths to here, but not on all of them (so it's dead, but may still be destroyed)
+}
+
+type States = HashMap<String, VariableState>;
+
+/// The states after a label that's reached from two places
+fn merge(first: States, second: States) -> States {
+    let mut merged = States::new();
+
+    for name in first.keys().chain(second.keys()) {
+        let state = match (first.get(name), second.get(name)) {
+            (Some(first), Some(second)) if first == second => *first,
+            (Some(VariableState::Destroyed), None) | (None, Some(VariableState::Destroyed)) => VariableState::Destroyed,
+            _ => VariableState::Partial,
+        };
+
+        merged.insert(name.clone(), state);
+    }
+
+    merged
+}
+
+/// Checks the instructions of a single function
+struct Verifier<'a> {
+    function: &'a MacroFunction,
+    index: usize,
+    errors: Vec<VerificationError>,
+}
+
+impl Verifier<'_> {
+    fn error(&mut self, message: String) {
+        let instruction = self.function.instructions[self.index].to_string();
+        self.errors.push(VerificationError { function: self.function.name.clone(), index: self.index, instruction, message });
+    }
+
+    /// The variable is read or written, so it has to be alive
+    fn use_variable(&mut self, states: &States, variable: &Variable) {
+        let name = &variable.full_name;
+
+        match states.get(name) {
+            Some(VariableState::Alive) => {}
+            Some(VariableState::Destroyed) => self.error(format!("Variable \"{}\" is used after it has been destroyed.", name)),
+            Some(VariableState::Partial) => self.error(format!("Variable \"{}\" is used, but it isn't alive on every path to here.", name)),
+            None => self.error(format!("Variable \"{}\" is used before it has been declared.", name)),
+        }
+    }
+
+    fn use_operand(&mut self, states: &States, operand: &Operand) {
+        if let Operand::Variable(variable) = operand {
+            self.use_variable(states, variable);
+        }
+    }
+}
+
+/// Check that the instructions of the function are well formed:
+/// variables are only used while they're alive (on every path), the arguments of calls match the calls,
+/// arguments that are read exist and labels exist and are reached with the variables they expect.
+/// Code that can't be reached isn't checked.
+pub fn verify_function(function: &MacroFunction) -> Vec<VerificationError> {
+    let mut verifier = Verifier { function, index: 0, errors: Vec::new() };
+
+    let labels: Vec<&String> = function.instructions.iter().filter_map(|x| match x {
+        MacroInstruction::Label(label) => Some(label),
+        _ => None,
+    }).collect();
+
+    // None while the code can't be reached
+    let mut states: Option<States> = Some(States::new());
+
+    // The states at labels that haven't been placed yet, merged from every jump to them
+    let mut forward_jumps: HashMap<String, States> = HashMap::new();
+
+    // The variables that are alive at the labels that have been placed already
+    let mut placed_labels: HashMap<String, HashSet<String>> = HashMap::new();
+
+    // The argument slots that have been filled for the next call
+    let mut arguments: Vec<usize> = Vec::new();
+
+    let mut read_arguments: Vec<usize> = Vec::new();
+
+    for (index, instruction) in function.instructions.iter().enumerate() {
+        verifier.index = index;
+
+        let is_argument_or_call = matches!(instruction, MacroInstruction::UseVariableAsArgument(_, _) | MacroInstruction::CallFunction(_, _, _, _));
+        if !arguments.is_empty() && !is_argument_or_call && states.is_some() {
+            verifier.error(format!("The arguments {:?} aren't followed by a call.", arguments));
+            arguments.clear();
+        }
+
+        if let MacroInstruction::Label(label) = instruction {
+            if placed_labels.contains_key(label) {
+                verifier.error(format!("Label \"{}\" exists twice.", label));
+            }
+
+            states = match (states, forward_jumps.remove(label)) {
+                (Some(current), Some(jumped)) => Some(merge(current, jumped)),
+                (current, jumped) => current.or(jumped),
+            };
+
+            let alive = states.iter().flatten().filter(|(_, state)| **state == VariableState::Alive).map(|(name, _)| name.clone()).collect();
+            placed_labels.insert(label.clone(), alive);
+            continue;
+        }
+
+        let Some(current) = &mut states else { continue; };
+
+        // The label has to exist and get the variables it expects
+        let mut jump_to = |verifier: &mut Verifier, label: &String, current: &States| {
+            if !labels.contains(&label) {
+                verifier.error(format!("Label \"{}\" doesn't exist.", label));
+                return;
+            }
+
+            match placed_labels.get(label) {
+                // Jumping back only works if every variable the code after the label uses is still there
+                Some(alive) => {
+                    let mut missing: Vec<&String> = alive.iter().filter(|x| current.get(*x) != Some(&VariableState::Alive)).collect();
+                    missing.sort();
+
+                    for name in missing {
+                        verifier.error(format!("Variable \"{}\" is alive at label \"{}\", but not when jumping back to it.", name, label));
+                    }
+                }
+                None => {
+                    let merged = match forward_jumps.remove(label) {
+                        Some(jumped) => merge(jumped, current.clone()),
+                        None => current.clone(),
+                    };
+                    forward_jumps.insert(label.clone(), merged);
+                }
+            }
+        };
+
+        match instruction {
+            MacroInstruction::DeclareVariable(variable) => {
+                if current.get(&variable.full_name) == Some(&VariableState::Alive) {
+                    verifier.error(format!("Variable \"{}\" is declared while it's alive already.", variable.full_name));
+                }
+
+                current.insert(variable.full_name.clone(), VariableState::Alive);
+            }
+            MacroInstruction::DestroyVariable(variable) => {
+                match current.get(&variable.full_name) {
+                    // Variables that are only alive on some paths are dead already, destroying them is still fine
+                    Some(VariableState::Alive) | Some(VariableState::Partial) => {}
+                    Some(VariableState::Destroyed) => verifier.error(format!("Variable \"{}\" is destroyed twice.", variable.full_name)),
+                    None => verifier.error(format!("Variable \"{}\" is destroyed before it has been declared.", variable.full_name)),
+                }
+
+                current.insert(variable.full_name.clone(), VariableState::Destroyed);
+            }
+            MacroInstruction::UseVariableAsArgument(variable, n) => {
+                verifier.use_variable(current, variable);
+
+                if arguments.contains(n) {
+                    verifier.error(format!("Argument {} is passed twice.", n));
+                }
+                arguments.push(*n);
+            }
+            MacroInstruction::CallFunction(name, argument_count, fixed_argument_count, result) => {
+                arguments.sort();
+                if arguments != (0..*argument_count).collect::<Vec<usize>>() {
+                    verifier.error(format!("Function \"{}\" is called with {} argument(s), but the arguments {:?} have been passed.", name, argument_count, arguments));
+                }
+                arguments.clear();
+
+                if let Some(fixed_argument_count) = fixed_argument_count && fixed_argument_count > argument_count {
+                    verifier.error(format!("Function \"{}\" has {} fixed argument(s), but is only called with {}.", name, fixed_argument_count, argument_count));
+                }
+
+                if let Some(result) = result {
+                    verifier.use_variable(current, result);
+                }
+            }
+            MacroInstruction::GetArgument(variable, n) => {
+                verifier.use_variable(current, variable);
+
+                if *n >= function.parameter_count {
+                    verifier.error(format!("Argument {} is read, but the function only has {} parameter(s).", n, function.parameter_count));
+                } else if read_arguments.contains(n) {
+                    verifier.error(format!("Argument {} is read twice.", n));
+                }
+                read_arguments.push(*n);
+            }
+            MacroInstruction::Return(value) => {
+                if let Some(value) = value {
+                    verifier.use_variable(current, value);
+                }
+
+                if value.is_some() != function.returns_value {
+                    verifier.error(match function.returns_value {
+                        true => "The function returns a value, but nothing is returned.".to_string(),
+                        false => "The function doesn't return a value, but one is returned.".to_string(),
+                    });
+                }
+
+                states = None;
+            }
+            MacroInstruction::Compute(target, _, left, right) => {
+                verifier.use_operand(current, left);
+                verifier.use_operand(current, right);
+                verifier.use_variable(current, target);
+            }
+            MacroInstruction::ComputeUnary(target, _, operand) => {
+                verifier.use_operand(current, operand);
+                verifier.use_variable(current, target);
+            }
+            MacroInstruction::LoadImmediate(target, _) | MacroInstruction::LoadConstantAddress(target, _) => {
+                verifier.use_variable(current, target);
+            }
+            MacroInstruction::Copy(target, source) | MacroInstruction::Load(target, source, _) | MacroInstruction::AddressOf(target, source) => {
+                verifier.use_variable(current, source);
+                verifier.use_variable(current, target);
+            }
+            MacroInstruction::Store(address, _, value) => {
+                verifier.use_variable(current, address);
+                verifier.use_variable(current, value);
+            }
+            MacroInstruction::Jump(label) => {
+                jump_to(&mut verifier, label, current);
+                states = None;
+            }
+            MacroInstruction::BranchIf(condition, label) => {
+                verifier.use_variable(current, condition);
+                jump_to(&mut verifier, label, current);
+            }
+            MacroInstruction::Label(_) => {}
+        }
+    }
+
+    if !arguments.is_empty() && let Some(last) = function.instructions.len().checked_sub(1) {
+        verifier.index = last;
+        verifier.error(format!("The arguments {:?} aren't followed by a call.", arguments));
+    }
+
+    verifier.errors
+}
+
+/// Verify all the functions (see verify_function)
+pub fn verify_functions(functions: &[MacroFunction]) -> Vec<VerificationError> {
+    functions.iter().flat_map(verify_function).collect()
+}
+
+#[cfg(test)]
+mod tests{
+    use crate::compiler::low_level::ir_text::parse_ir;
+    use crate::compiler::low_level::verifier::{verify_function, verify_functions};
+    use crate::compiler::lowering::lowering::Lowering;
+    use crate::compiler::parser::parser::parse_source;
+
+    fn errors(text: &str) -> Vec<String> {
+        let (functions, diagnostics) = parse_ir("test.rslir".to_string(), text);
+        assert_eq!(diagnostics, vec![]);
+
+        verify_function(&functions[0]).iter().map(|x| format!("{}: {}", x.index, x.message)).collect()
+    }
+
+    #[test]
+    fn test_lowered_code_is_valid(){
+        let source = "
+            extern fun printf(format: *Int8, ...) -> Int32;
+
+            fun main(count: Int) -> Int {
+                var i = 0;
+                while i < count && count != 100 {
+                    let square = i * i;
+                    if square > 50 || i == 3 {
+                        break;
+                    } else {
+                        printf(\"%d\\n\", square);
+                    }
+                    i += 1;
+                    if i == 2 { continue; }
+                }
+                return i;
+            }
+        ";
+
+        let (module, diagnostics) = parse_source("main.rsl".to_string(), source);
+        assert_eq!(diagnostics, vec![]);
+
+        let (functions, diagnostics) = Lowering::new(&[module], "my_app".to_string()).lower_program();
+        assert_eq!(diagnostics, vec![]);
+
+        assert_eq!(verify_functions(&functions), vec![]);
+    }
+
+    #[test]
+    fn test_variables(){
+        assert_eq!(errors("
+            function @main(0) {
+                %a = immediate 1
+                declare %a
+                destroy %a
+                %a = immediate 2
+                destroy %a
+                destroy %b
+                declare %c
+                declare %c
+            }
+        "), vec![
+            "0: Variable \"a\" is used before it has been declared.",
+            "3: Variable \"a\" is used after it has been destroyed.",
+            "4: Variable \"a\" is destroyed twice.",
+            "5: Variable \"b\" is destroyed before it has been declared.",
+            "7: Variable \"c\" is declared while it's alive already.",
+        ]);
+    }
+
+    #[test]
+    fn test_calls_and_arguments(){
+        assert_eq!(errors("
+            function @helper(1) -> value {
+                declare %a
+                %a = get_argument 0
+                %a = get_argument 1
+                argument %a, 0
+                argument %a, 2
+                call @puts, 2
+                call @printf, 0, variadic 1
+                argument %a, 0
+                destroy %a
+                return %a
+            }
+        "), vec![
+            "2: Argument 1 is read, but the function only has 1 parameter(s).",
+            "5: Function \"puts\" is called with 2 argument(s), but the arguments [0, 2] have been passed.",
+            "6: Function \"printf\" has 1 fixed argument(s), but is only called with 0.",
+            "8: The arguments [0] aren't followed by a call.",
+            "9: Variable \"a\" is used after it has been destroyed.",
+        ]);
+    }
+
+    #[test]
+    fn test_labels(){
+        // b is only alive on one path to if1.end, so it's dead there (but may still be destroyed)
+        assert_eq!(errors("
+            function @main(0) {
+                declare %a
+                %a = immediate 1
+                branch_if %a, nowhere
+                label loop1.start
+                declare %b
+                branch_if %a, if1.end
+                destroy %b
+                label if1.end
+                %a = copy %b
+                destroy %b
+                destroy %a
+                jump loop1.start
+                destroy %a
+            }
+        "), vec![
+            "2: Label \"nowhere\" doesn't exist.",
+            "8: Variable \"b\" is used, but it isn't alive on every path to here.",
+            "11: Variable \"a\" is alive at label \"loop1.start\", but not when jumping back to it.",
+        ]);
+    }
+}
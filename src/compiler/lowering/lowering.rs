@@ -0,0 +1,1135 @@
+use std::collections::HashMap;
+use crate::compiler::diagnostic::Diagnostic;
+use crate::compiler::lexer::span::Span;
+use crate::compiler::low_level::macro_function::MacroFunction;
+use crate::compiler::low_level::macro_instruction::MacroInstruction;
+use crate::compiler::low_level::operation::{Operand, Operation, UnaryOperation};
+use crate::compiler::low_level::variable::{ValueKind, ValueType, Variable};
+use crate::compiler::lowering::scope::ScopeStack;
+use crate::compiler::parser::ast::*;
+
+/// What the lowering needs to know about a function to call it
+#[derive(Clone)]
+struct FunctionInfo {
+    symbol: String,
+    parameter_count: usize,
+    is_variadic: bool,
+    returns_value: bool,
+}
+
+/// What's calculated for an expression (before it's known where the result goes)
+enum Computation {
+    Binary(Operation, Operand, Operand),
+    Unary(UnaryOperation, Operand),
+    Call(FunctionInfo, /*arguments: */Vec<Variable>),
+    Value(Operand),                 // Nothing to calculate, just a copy
+    Constant(/*bytes: */Vec<u8>),   // The address of read-only data (e.g. of a string)
+    Load(/*address: */Variable),    // The value the address points to (*p)
+    AddressOf(Variable),            // &a
+}
+
+impl Computation {
+    fn into_instructions(self, target: Variable) -> Vec<MacroInstruction> {
+        match self {
+            Computation::Binary(operation, left, right) => vec![MacroInstruction::Compute(target, operation, left, right)],
+            Computation::Unary(operation, operand) => vec![MacroInstruction::ComputeUnary(target, operation, operand)],
+            Computation::Call(function, arguments) => call_instructions(function, arguments, Some(target)),
+            Computation::Value(Operand::Immediate(value)) => vec![MacroInstruction::LoadImmediate(target, value)],
+            Computation::Value(Operand::Variable(source)) => vec![MacroInstruction::Copy(target, source)],
+            Computation::Constant(bytes) => vec![MacroInstruction::LoadConstantAddress(target, bytes)],
+            Computation::Load(address) => vec![MacroInstruction::Load(target, address, 0)],
+            Computation::AddressOf(variable) => vec![MacroInstruction::AddressOf(target, variable)],
+        }
+    }
+}
+
+/// Pass the arguments (which have been calculated already) and call the function
+fn call_instructions(function: FunctionInfo, arguments: Vec<Variable>, result: Option<Variable>) -> Vec<MacroInstruction> {
+    let argument_count = arguments.len();
+    // Some targets pass the variadic arguments differently, so they have to know where they start
+    let fixed_argument_count = function.is_variadic.then_some(function.parameter_count);
+
+    let mut instructions: Vec<MacroInstruction> = arguments.into_iter().enumerate().map(|(i, x)| MacroInstruction::UseVariableAsArgument(x, i)).collect();
+    instructions.push(MacroInstruction::CallFunction(function.symbol, argument_count, fixed_argument_count, result));
+
+    instructions
+}
+
+/// A loop that's currently being lowered (break and continue need to know where it starts and ends)
+struct Loop {
+    start: String,                  // The label before the condition
+    end: String,                    // The label after the loop
+    scope_depth: usize,             // The amount of scopes that were open outside the loop's body
+}
+
+/// Turns the functions of a program (made up of one module per file) into streams of macro instructions
+pub struct Lowering<'a> {
+    modules: &'a [Module],
+    default_app_name: String,
+
+    // The module that's currently being lowered
+    app_name: String,
+    file_name: String,
+
+    functions: HashMap<String, FunctionInfo>,   // Functions by the path they're called by (e.g. puts or Main.helper)
+    diagnostics: Vec<Diagnostic>,
+
+    // State of the function that's currently being lowered
+    current_type: Option<String>,
+    returns_value: bool,                        // Whether the current function has a return type
+    scopes: ScopeStack,
+    scope_counters: HashMap<&'static str, usize>,
+    instructions: Vec<MacroInstruction>,
+    temporaries: Vec<Variable>,                 // The variables holding intermediate results of the current statement
+    loops: Vec<Loop>,                           // The loops around the current statement (the innermost one is the last one)
+}
+
+impl<'a> Lowering<'a> {
+    /// The name of the function the program starts at (either on its own or in the type Main)
+    pub const ENTRY_POINT_NAME: &'static str = "main";
+    pub const ENTRY_POINT_TYPE_NAME: &'static str = "Main";
+
+    /// The app name is used as the first part of all the variable names if a module doesn't declare its own name.
+    pub fn new(modules: &'a [Module], default_app_name: String) -> Lowering<'a> {
+        Lowering {
+            modules, default_app_name,
+            app_name: String::new(),
+            file_name: String::new(),
+            functions: HashMap::new(),
+            diagnostics: Vec::new(),
+            current_type: None,
+            returns_value: false,
+            scopes: ScopeStack::new(String::new()),
+            scope_counters: HashMap::new(),
+            instructions: Vec::new(),
+            temporaries: Vec::new(),
+            loops: Vec::new(),
+        }
+    }
+
+    pub fn lower_program(mut self) -> (Vec<MacroFunction>, Vec<Diagnostic>) {
+        self.collect_functions();
+
+        let mut lowered_functions = Vec::new();
+
+        for module in self.modules {
+            lowered_functions.extend(self.lower_module(module));
+        }
+
+        (lowered_functions, self.diagnostics)
+    }
+
+    fn lower_module(&mut self, module: &Module) -> Vec<MacroFunction> {
+        self.app_name = module.name.clone().unwrap_or(self.default_app_name.clone());
+
+        // Only the last part of the path is part of the variable names
+        self.file_name = module.file.rsplit(['/', '\\']).next().unwrap_or(&module.file).to_string();
+
+        let mut lowered_functions = Vec::new();
+
+        for item in &module.items {
+            match item {
+                Item::Function(function) => lowered_functions.push(self.lower_function(function, None)),
+                Item::Type(type_definition) => {
+                    for function in &type_definition.functions {
+                        lowered_functions.push(self.lower_function(function, Some(type_definition.name.clone())));
+                    }
+                }
+                Item::ExternFunction(_) => {}
+            }
+        }
+
+        lowered_functions
+    }
+
+    /// Make all the functions of the program known, so they can be called before they've been defined (and from other files)
+    fn collect_functions(&mut self) {
+        for item in self.modules.iter().flat_map(|x| &x.items) {
+            match item {
+                Item::Function(function) => self.add_function(function.signature.name.clone(), &function.signature, None),
+                Item::ExternFunction(signature) => {
+                    // Several files may declare the same external function
+                    if self.functions.get(&signature.name).is_some_and(|x| x.parameter_count == signature.parameters.len() && x.is_variadic == signature.is_variadic) { continue; }
+                    self.add_function(signature.name.clone(), signature, None)
+                }
+                Item::Type(type_definition) => {
+                    for function in &type_definition.functions {
+                        let path = format!("{}.{}", type_definition.name, function.signature.name);
+                        self.add_function(path, &function.signature, Some(&type_definition.name));
+                    }
+                }
+            }
+        }
+    }
+
+    fn add_function(&mut self, path: String, signature: &FunctionSignature, type_name: Option<&String>) {
+        if self.functions.contains_key(&path) {
+            self.error(format!("Function \"{}\" has been defined twice.", path), signature.span.clone());
+            return;
+        }
+
+        let info = FunctionInfo {
+            symbol: Self::symbol(signature, type_name),
+            parameter_count: signature.parameters.len(),
+            is_variadic: signature.is_variadic,
+            returns_value: signature.return_type.is_some(),
+        };
+        self.functions.insert(path, info);
+    }
+
+    /// The name a function gets in the generated code
+    fn symbol(signature: &FunctionSignature, type_name: Option<&String>) -> String {
+        match type_name {
+            Some(type_name) if type_name == Self::ENTRY_POINT_TYPE_NAME && signature.name == Self::ENTRY_POINT_NAME => signature.name.clone(),
+            Some(type_name) => format!("{}.{}", type_name, signature.name),
+            None => signature.name.clone(),
+        }
+    }
+
+    fn lower_function(&mut self, function: &Function, type_name: Option<String>) -> MacroFunction {
+        // e.g. my_app:main.rsl:Main
+        let mut prefix = format!("{}:{}", self.app_name, self.file_name);
+        if let Some(type_name) = &type_name {
+            prefix += format!(":{}", type_name).as_str();
+        }
+
+        self.current_type = type_name.clone();
+        self.returns_value = function.signature.return_type.is_some();
+        self.scopes = ScopeStack::new(prefix);
+        self.scope_counters.clear();
+        self.instructions = Vec::new();
+        self.loops.clear();
+
+        // The parameters live in the function's own scope
+        self.scopes.push(function.signature.name.clone());
+
+        for (i, parameter) in function.signature.parameters.iter().enumerate() {
+            let Some(variable) = self.declare_variable(&parameter.name, Some(&parameter.type_name), parameter.span.clone()) else { continue; };
+            self.instructions.push(MacroInstruction::GetArgument(variable, i));
+        }
+
+        for statement in &function.body.statements {
+            self.lower_statement(statement);
+        }
+
+        self.close_scope();
+
+        let name = Self::symbol(&function.signature, type_name.as_ref());
+        MacroFunction::new(name, function.signature.parameters.len(), self.returns_value, std::mem::take(&mut self.instructions))
+    }
+
+    // Scopes
+
+    /// A name for a scope (or the labels of a statement), which is made up of its kind
+    /// and how many of that kind the function has had so far (e.g. loop1).
+    fn unique_name(&mut self, kind: &'static str) -> String {
+        let counter = self.scope_counters.entry(kind).or_insert(0);
+        *counter += 1;
+
+        format!("{}{}", kind, counter)
+    }
+
+    /// Close the innermost scope and destroy its variables (the last declared one first).
+    fn close_scope(&mut self) {
+        let Some(scope) = self.scopes.pop() else { return; };
+
+        for (_, variable) in scope.variables.iter().rev() {
+            self.instructions.push(MacroInstruction::DestroyVariable(variable.clone()));
+        }
+    }
+
+    fn declare_variable(&mut self, name: &str, type_name: Option<&TypeName>, span: Span) -> Option<Variable> {
+        let value_type = type_name.map(Self::value_type).unwrap_or_default();
+
+        let Some(variable) = self.scopes.declare(name, value_type) else {
+            self.error(format!("Variable \"{}\" has already been declared in this scope.", name), span);
+            return None;
+        };
+
+        self.instructions.push(MacroInstruction::DeclareVariable(variable.clone()));
+        Some(variable)
+    }
+
+    /// The size and kind the values of an RSL type have. Only the integer types have a size of their own so far,
+    /// everything else (e.g. pointers, and floats, as there's no float arithmetic yet) is as large as a register.
+    fn value_type(type_name: &TypeName) -> ValueType {
+        let TypeName::Named(name) = type_name else { return ValueType::default(); };
+
+        let name = match name.as_str() {
+            "Int8" => "i8",
+            "Int16" => "i16",
+            "Int32" => "i32",
+            "UInt8" => "u8",
+            "UInt16" => "u16",
+            "UInt32" => "u32",
+            "UInt64" | "UInt" => "u64",
+            _ => return ValueType::default(),
+        };
+
+        ValueType::named(name).unwrap_or_default()
+    }
+
+    // Statements
+
+    fn lower_block(&mut self, block: &Block, scope_name: String) {
+        self.scopes.push(scope_name);
+
+        for statement in &block.statements {
+            self.lower_statement(statement);
+        }
+
+        self.close_scope();
+    }
+
+    fn lower_statement(&mut self, statement: &Statement) {
+        let span = statement.span.clone();
+
+        match &statement.kind {
+            StatementKind::VariableDeclaration { name, type_name, value, .. } => {
+                // The value is calculated before the variable exists, so it can still refer to a variable it shadows
+                let computation = value.as_ref().map(|x| self.lower_computation(x));
+
+                let Some(variable) = self.declare_variable(name, type_name.as_ref(), span) else { return; };
+
+                if let Some(Some(computation)) = computation {
+                    self.instructions.extend(computation.into_instructions(variable));
+                }
+            }
+            StatementKind::Expression(expression) => {
+                match &expression.kind {
+                    ExpressionKind::Call { callee, arguments } => {
+                        // The result (if there is one) isn't needed
+                        if let Some((function, arguments)) = self.lower_call(callee, arguments, expression.span.clone()) {
+                            self.instructions.extend(call_instructions(function, arguments, None));
+                        }
+                    }
+                    _ => self.error("Only calls can be used as statements.".to_string(), span),
+                }
+            }
+            StatementKind::Block(block) => {
+                let scope_name = self.unique_name("block");
+                self.lower_block(block, scope_name);
+            }
+            StatementKind::Assignment { target, operator, value } => self.lower_assignment(target, *operator, value),
+            StatementKind::If { condition, then_block, else_block } => self.lower_if(condition, then_block, else_block.as_ref()),
+            StatementKind::Loop(body) => self.lower_loop(None, body),
+            StatementKind::While { condition, body } => self.lower_loop(Some(condition), body),
+            StatementKind::Break => self.lower_loop_exit(true, span),
+            StatementKind::Continue => self.lower_loop_exit(false, span),
+            StatementKind::Return(value) => self.lower_return(value.as_ref(), span),
+        }
+
+        // The intermediate results aren't needed after the statement
+        let temporaries = std::mem::take(&mut self.temporaries);
+        self.destroy_variables(&temporaries);
+    }
+
+    /// The then block comes right after the condition, if the condition doesn't hold, the code branches to the else block (or the end)
+    fn lower_if(&mut self, condition: &Expression, then_block: &Block, else_block: Option<&Block>) {
+        let name = self.unique_name("if");
+        let else_label = format!("{}.else", name);
+        let end_label = format!("{}.end", name);
+
+        let branch_label = if else_block.is_some() { &else_label } else { &end_label };
+        let condition_temporaries = self.lower_branch_unless(condition, branch_label);
+
+        self.lower_block(then_block, name);
+
+        if let Some(else_block) = else_block {
+            self.instructions.push(MacroInstruction::Jump(end_label.clone()));
+            self.instructions.push(MacroInstruction::Label(else_label));
+            self.destroy_variables(&condition_temporaries);
+
+            let scope_name = self.unique_name("else");
+            self.lower_block(else_block, scope_name);
+        }
+
+        self.instructions.push(MacroInstruction::Label(end_label));
+
+        if else_block.is_none() {
+            self.destroy_variables(&condition_temporaries);
+        }
+    }
+
+    /// loop and while loops (which check their condition before every iteration)
+    fn lower_loop(&mut self, condition: Option<&Expression>, body: &Block) {
+        let name = self.unique_name("loop");
+        let start = format!("{}.start", name);
+        let end = format!("{}.end", name);
+
+        self.instructions.push(MacroInstruction::Label(start.clone()));
+
+        let condition_temporaries = match condition {
+            Some(condition) => self.lower_branch_unless(condition, &end),
+            None => Vec::new(),
+        };
+
+        self.loops.push(Loop { start: start.clone(), end: end.clone(), scope_depth: self.scopes.depth() });
+        self.lower_block(body, name);
+        self.loops.pop();
+
+        self.instructions.push(MacroInstruction::Jump(start));
+        self.instructions.push(MacroInstruction::Label(end));
+        self.destroy_variables(&condition_temporaries);
+    }
+
+    /// break (to the end of the innermost loop) and continue (to its start).
+    /// The variables of the loop's body don't survive that.
+    fn lower_loop_exit(&mut self, is_break: bool, span: Span) {
+        let Some(innermost_loop) = self.loops.last() else {
+            self.error(format!("\"{}\" can only be used inside of loops.", if is_break { "break" } else { "continue" }), span);
+            return;
+        };
+
+        let label = if is_break { innermost_loop.end.clone() } else { innermost_loop.start.clone() };
+        let variables = self.scopes.variables_since(innermost_loop.scope_depth);
+
+        self.destroy_variables(&variables);
+        self.instructions.push(MacroInstruction::Jump(label));
+    }
+
+    /// Branch to the label if the condition doesn't hold.
+    /// The temporaries of the condition are destroyed if the code doesn't branch,
+    /// they're returned as they still need to be destroyed after the label.
+    fn lower_branch_unless(&mut self, condition: &Expression, label: &str) -> Vec<Variable> {
+        if let Some(inverted_condition) = self.lower_inverted_condition(condition) {
+            self.instructions.push(MacroInstruction::BranchIf(inverted_condition, label.to_string()));
+        }
+
+        let temporaries = std::mem::take(&mut self.temporaries);
+        self.destroy_variables(&temporaries);
+
+        temporaries
+    }
+
+    /// Destroy the variables (the last declared one first)
+    fn destroy_variables(&mut self, variables: &[Variable]) {
+        for variable in variables.iter().rev() {
+            self.instructions.push(MacroInstruction::DestroyVariable(variable.clone()));
+        }
+    }
+
+    /// Return from the function, the variables that are still alive don't need to be destroyed
+    fn lower_return(&mut self, value: Option<&Expression>, span: Span) {
+        let value = match (value, self.returns_value) {
+            (Some(value), true) => {
+                let Some(variable) = self.lower_variable(value) else { return; };
+                Some(variable)
+            }
+            (None, false) => None,
+            (Some(_), false) => {
+                self.error("The function doesn't have a return type, so it can't return a value.".to_string(), span);
+                return;
+            }
+            (None, true) => {
+                self.error("The function has a return type, so it has to return a value.".to_string(), span);
+                return;
+            }
+        };
+
+        self.instructions.push(MacroInstruction::Return(value));
+    }
+
+    fn lower_assignment(&mut self, target: &Expression, operator: Option<BinaryOperator>, value: &Expression) {
+        if let ExpressionKind::Unary(UnaryOperator::Dereference, address) = &target.kind {
+            self.lower_store(address, operator, value, target.span.clone());
+            return;
+        }
+
+        let ExpressionKind::Identifier(name) = &target.kind else {
+            self.error("Only variables and dereferenced pointers can be assigned to for now.".to_string(), target.span.clone());
+            return;
+        };
+
+        let Some(variable) = self.resolve_variable(name, target.span.clone()) else { return; };
+
+        // a += b is a = a + b
+        let computation = match operator {
+            Some(operator) => {
+                let Some(value) = self.lower_operand(value) else { return; };
+                let Some(operation) = self.operation(operator, [&Operand::Variable(variable.clone()), &value], target.span.clone()) else { return; };
+                Some(Computation::Binary(operation, Operand::Variable(variable.clone()), value))
+            }
+            None => self.lower_computation(value),
+        };
+
+        if let Some(computation) = computation {
+            self.instructions.extend(computation.into_instructions(variable));
+        }
+    }
+
+    /// *p = value (or *p += value, which loads the current value first)
+    fn lower_store(&mut self, address: &Expression, operator: Option<BinaryOperator>, value: &Expression, span: Span) {
+        let Some(address) = self.lower_variable(address) else { return; };
+
+        let value = match operator {
+            Some(operator) => {
+                let current = self.declare_temporary();
+                self.instructions.push(MacroInstruction::Load(current.clone(), address.clone(), 0));
+
+                let Some(value) = self.lower_operand(value) else { return; };
+                let Some(operation) = self.operation(operator, [&Operand::Variable(current.clone()), &value], span) else { return; };
+                self.instructions.push(MacroInstruction::Compute(current.clone(), operation, Operand::Variable(current.clone()), value));
+                current
+            }
+            None => {
+                let Some(value) = self.lower_variable(value) else { return; };
+                value
+            }
+        };
+
+        self.instructions.push(MacroInstruction::Store(address, 0, value));
+    }
+
+    // Expressions
+
+    /// Find the function and calculate the arguments, which are returned (in order) with the function.
+    /// Arguments that are calls themselves are made before the call.
+    fn lower_call(&mut self, callee: &Expression, arguments: &[Expression], span: Span) -> Option<(FunctionInfo, Vec<Variable>)> {
+        let function = self.resolve_function(callee)?;
+
+        let argument_count_matches = if function.is_variadic { arguments.len() >= function.parameter_count } else { arguments.len() == function.parameter_count };
+        if !argument_count_matches {
+            self.error(format!("Function \"{}\" expects {} argument(s), but {} were given.", function.symbol, function.parameter_count, arguments.len()), span);
+            return None;
+        }
+
+        let mut argument_variables = Vec::new();
+
+        for argument in arguments {
+            argument_variables.push(self.lower_variable(argument)?);
+        }
+
+        Some((function, argument_variables))
+    }
+
+    /// Lower an expression to something an operation can work with.
+    /// The results of operations are stored in temporary variables.
+    fn lower_operand(&mut self, expression: &Expression) -> Option<Operand> {
+        let span = expression.span.clone();
+
+        match &expression.kind {
+            ExpressionKind::IntegerLiteral(value) => Some(Operand::Immediate(*value as i64)),
+            ExpressionKind::BoolLiteral(value) => Some(Operand::Immediate(*value as i64)),
+            ExpressionKind::CharLiteral(value) => Some(Operand::Immediate(*value as i64)),
+            ExpressionKind::Identifier(name) => self.resolve_variable(name, span).map(Operand::Variable),
+            // The value is converted when it's stored in a variable of the type (copies and results are extended to the target's type)
+            ExpressionKind::Cast(value, _) => self.lower_operand(value),
+            ExpressionKind::Binary(operator @ (BinaryOperator::LogicalAnd | BinaryOperator::LogicalOr), left, right) => {
+                self.lower_logical(*operator == BinaryOperator::LogicalAnd, left, right).map(Operand::Variable)
+            }
+            ExpressionKind::Unary(..) | ExpressionKind::Binary(..) | ExpressionKind::Call { .. } | ExpressionKind::StringLiteral(_) => {
+                let computation = self.lower_computation(expression)?;
+                let temporary = self.declare_temporary();
+
+                self.instructions.extend(computation.into_instructions(temporary.clone()));
+                Some(Operand::Variable(temporary))
+            }
+            ExpressionKind::FloatLiteral(_) => {
+                self.error("Float literals aren't supported yet.".to_string(), span);
+                None
+            }
+            ExpressionKind::MemberAccess(..) | ExpressionKind::Index(..) => {
+                self.error("Fields and indexing aren't supported yet.".to_string(), span);
+                None
+            }
+        }
+    }
+
+    /// Lower an expression to a variable, literals are loaded into a temporary
+    fn lower_variable(&mut self, expression: &Expression) -> Option<Variable> {
+        match self.lower_operand(expression)? {
+            Operand::Variable(variable) => Some(variable),
+            Operand::Immediate(value) => {
+                let temporary = self.declare_temporary();
+                self.instructions.push(MacroInstruction::LoadImmediate(temporary.clone(), value));
+                Some(temporary)
+            }
+        }
+    }
+
+    /// Lower a condition to a temporary that isn't 0 if the condition doesn't hold.
+    /// Comparisons are simply turned around (a < b becomes a >= b).
+    fn lower_inverted_condition(&mut self, condition: &Expression) -> Option<Variable> {
+        let inverted_condition = match &condition.kind {
+            ExpressionKind::Binary(..) | ExpressionKind::Unary(..) => match self.lower_computation(condition)? {
+                Computation::Binary(operation, left, right) if operation.is_comparison() => Computation::Binary(operation.inverted().unwrap(), left, right),
+                Computation::Value(operand) => Computation::Binary(Operation::Equal, operand, Operand::Immediate(0)),
+                computation => {
+                    let temporary = self.declare_temporary();
+                    self.instructions.extend(computation.into_instructions(temporary.clone()));
+                    Computation::Binary(Operation::Equal, Operand::Variable(temporary), Operand::Immediate(0))
+                }
+            },
+            _ => Computation::Binary(Operation::Equal, self.lower_operand(condition)?, Operand::Immediate(0)),
+        };
+
+        let temporary = self.declare_temporary();
+        self.instructions.extend(inverted_condition.into_instructions(temporary.clone()));
+
+        Some(temporary)
+    }
+
+    /// a && b and a || b, the right side is only calculated if the left one doesn't decide the result already.
+    /// The result (0 or 1) is stored in a temporary.
+    fn lower_logical(&mut self, is_and: bool, left: &Expression, right: &Expression) -> Option<Variable> {
+        let name = self.unique_name(if is_and { "and" } else { "or" });
+        let end_label = format!("{}.end", name);
+
+        let result = self.declare_temporary();
+
+        let left = self.lower_operand(left)?;
+        self.instructions.push(MacroInstruction::Compute(result.clone(), Operation::NotEqual, left, Operand::Immediate(0)));
+
+        // && doesn't need the right side if the left one is false, || if it's true
+        let decided = if is_and {
+            let is_false = self.declare_temporary();
+            self.instructions.push(MacroInstruction::Compute(is_false.clone(), Operation::Equal, Operand::Variable(result.clone()), Operand::Immediate(0)));
+            is_false
+        } else {
+            result.clone()
+        };
+        self.instructions.push(MacroInstruction::BranchIf(decided, end_label.clone()));
+
+        let right = self.lower_operand(right)?;
+        self.instructions.push(MacroInstruction::Compute(result.clone(), Operation::NotEqual, right, Operand::Immediate(0)));
+        self.instructions.push(MacroInstruction::Label(end_label));
+
+        Some(result)
+    }
+
+    /// Lower an expression that's an operation (the operands are lowered first)
+    fn lower_computation(&mut self, expression: &Expression) -> Option<Computation> {
+        let span = expression.span.clone();
+
+        match &expression.kind {
+            ExpressionKind::Binary(operator @ (BinaryOperator::LogicalAnd | BinaryOperator::LogicalOr), left, right) => {
+                let result = self.lower_logical(*operator == BinaryOperator::LogicalAnd, left, right)?;
+                Some(Computation::Value(Operand::Variable(result)))
+            }
+            ExpressionKind::Binary(operator, left, right) => {
+                let left = self.lower_operand(left)?;
+                let right = self.lower_operand(right)?;
+                let operation = self.operation(*operator, [&left, &right], span)?;
+
+                Some(Computation::Binary(operation, left, right))
+            }
+            ExpressionKind::Unary(operator, value) => {
+                let computation = match operator {
+                    UnaryOperator::Negate => Computation::Unary(UnaryOperation::Negate, self.lower_operand(value)?),
+                    UnaryOperator::BitwiseNot => Computation::Unary(UnaryOperation::Not, self.lower_operand(value)?),
+                    // !a is a == 0
+                    UnaryOperator::Not => Computation::Binary(Operation::Equal, self.lower_operand(value)?, Operand::Immediate(0)),
+                    UnaryOperator::Dereference => Computation::Load(self.lower_variable(value)?),
+                    // Only variables have an address (temporaries would be gone by the time it's used)
+                    UnaryOperator::AddressOf => {
+                        let ExpressionKind::Identifier(name) = &value.kind else {
+                            self.error("Only the address of a variable can be taken.".to_string(), span);
+                            return None;
+                        };
+
+                        Computation::AddressOf(self.resolve_variable(name, value.span.clone())?)
+                    }
+                };
+
+                Some(computation)
+            }
+            ExpressionKind::Call { callee, arguments } => {
+                let (function, arguments) = self.lower_call(callee, arguments, span.clone())?;
+
+                if !function.returns_value {
+                    self.error(format!("Function \"{}\" doesn't return a value.", function.symbol), span);
+                    return None;
+                }
+
+                Some(Computation::Call(function, arguments))
+            }
+            ExpressionKind::Cast(value, _) => self.lower_computation(value),
+            // Strings end with a 0 (like in C)
+            ExpressionKind::StringLiteral(value) => Some(Computation::Constant(value.bytes().chain([0]).collect())),
+            _ => Some(Computation::Value(self.lower_operand(expression)?)),
+        }
+    }
+
+    /// The operation a binary operator stands for. If one of the operands is an unsigned variable, the operation is unsigned as well
+    /// (immediates and intermediate results are signed).
+    fn operation(&mut self, operator: BinaryOperator, operands: [&Operand; 2], span: Span) -> Option<Operation> {
+        let is_unsigned = operands.iter().any(|x| x.variable().is_some_and(|x| x.value_type.kind == ValueKind::Unsigned));
+
+        let operation = match (operator, is_unsigned) {
+            (BinaryOperator::Add, _) => Operation::Add,
+            (BinaryOperator::Subtract, _) => Operation::Subtract,
+            (BinaryOperator::Multiply, _) => Operation::Multiply,
+            (BinaryOperator::Divide, false) => Operation::DivideSigned,
+            (BinaryOperator::Divide, true) => Operation::DivideUnsigned,
+            (BinaryOperator::Remainder, false) => Operation::RemainderSigned,
+            (BinaryOperator::Remainder, true) => Operation::RemainderUnsigned,
+            (BinaryOperator::BitwiseAnd, _) => Operation::And,
+            (BinaryOperator::BitwiseOr, _) => Operation::Or,
+            (BinaryOperator::BitwiseXor, _) => Operation::Xor,
+            (BinaryOperator::ShiftLeft, _) => Operation::ShiftLeft,
+            (BinaryOperator::ShiftRight, false) => Operation::ShiftRightArithmetic,
+            (BinaryOperator::ShiftRight, true) => Operation::ShiftRightLogical,
+            (BinaryOperator::Equal, _) => Operation::Equal,
+            (BinaryOperator::NotEqual, _) => Operation::NotEqual,
+            (BinaryOperator::Less, false) => Operation::LessSigned,
+            (BinaryOperator::Less, true) => Operation::LessUnsigned,
+            (BinaryOperator::LessEqual, false) => Operation::LessOrEqualSigned,
+            (BinaryOperator::LessEqual, true) => Operation::LessOrEqualUnsigned,
+            (BinaryOperator::Greater, false) => Operation::GreaterSigned,
+            (BinaryOperator::Greater, true) => Operation::GreaterUnsigned,
+            (BinaryOperator::GreaterEqual, false) => Operation::GreaterOrEqualSigned,
+            (BinaryOperator::GreaterEqual, true) => Operation::GreaterOrEqualUnsigned,
+            // They need branches (see lower_logical)
+            (BinaryOperator::LogicalAnd | BinaryOperator::LogicalOr, _) => {
+                self.error("&& and || can't be used here.".to_string(), span);
+                return None;
+            }
+        };
+
+        Some(operation)
+    }
+
+    /// Declare a variable for an intermediate result, which is destroyed at the end of the statement.
+    /// Its name can't clash with the names in the code, as those can't contain dots (e.g. my_app:main.rsl:main:temporary.1).
+    fn declare_temporary(&mut self) -> Variable {
+        let counter = self.scope_counters.entry("temporary").or_insert(0);
+        *counter += 1;
+
+        let variable = Variable::new(self.scopes.full_name(&format!("temporary.{}", counter)), vec![]);
+
+        self.instructions.push(MacroInstruction::DeclareVariable(variable.clone()));
+        self.temporaries.push(variable.clone());
+
+        variable
+    }
+
+    fn resolve_variable(&mut self, name: &str, span: Span) -> Option<Variable> {
+        let variable = self.scopes.resolve(name);
+
+        if variable.is_none() {
+            self.error(format!("Unknown variable \"{}\".", name), span);
+        }
+
+        variable
+    }
+
+    /// Find the function that's being called.
+    /// A plain name refers to a function of the current type first and to a global one otherwise.
+    fn resolve_function(&mut self, callee: &Expression) -> Option<FunctionInfo> {
+        let path = match &callee.kind {
+            ExpressionKind::Identifier(name) => {
+                let type_path = self.current_type.as_ref().map(|x| format!("{}.{}", x, name));
+
+                match type_path {
+                    Some(type_path) if self.functions.contains_key(&type_path) => type_path,
+                    _ => name.clone(),
+                }
+            }
+            ExpressionKind::MemberAccess(type_expression, name) => {
+                let ExpressionKind::Identifier(type_name) = &type_expression.kind else {
+                    self.error("Only functions and functions of types can be called.".to_string(), callee.span.clone());
+                    return None;
+                };
+                format!("{}.{}", type_name, name)
+            }
+            _ => {
+                self.error("Only functions and functions of types can be called.".to_string(), callee.span.clone());
+                return None;
+            }
+        };
+
+        let function = self.functions.get(&path).cloned();
+
+        if function.is_none() {
+            self.error(format!("Unknown function \"{}\".", path), callee.span.clone());
+        }
+
+        function
+    }
+
+    fn error(&mut self, message: String, span: Span) {
+        self.diagnostics.push(Diagnostic::new(message, span));
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use crate::compiler::low_level::macro_instruction::MacroInstruction;
+    use crate::compiler::low_level::operation::Operand;
+    use crate::compiler::low_level::variable::ValueType;
+    use crate::compiler::lowering::lowering::Lowering;
+    use crate::compiler::parser::parser::parse_source;
+
+    /// Describe the instructions briefly, so they can be compared
+    fn describe(instructions: &[MacroInstruction]) -> Vec<String> {
+        instructions.iter().map(|x| match x {
+            MacroInstruction::DeclareVariable(variable) => format!("declare {}", variable.full_name),
+            MacroInstruction::DestroyVariable(variable) => format!("destroy {}", variable.full_name),
+            MacroInstruction::UseVariableAsArgument(variable, n) => format!("argument {} {}", n, variable.full_name),
+            MacroInstruction::CallFunction(name, argument_count, _, None) => format!("call {} {}", name, argument_count),
+            MacroInstruction::CallFunction(name, argument_count, _, Some(result)) => format!("{} = call {} {}", result.full_name, name, argument_count),
+            MacroInstruction::GetArgument(variable, n) => format!("get-argument {} {}", n, variable.full_name),
+            MacroInstruction::Compute(variable, operation, left, right) => format!("{} = {:?} {} {}", variable.full_name, operation, describe_operand(left), describe_operand(right)),
+            MacroInstruction::ComputeUnary(variable, operation, operand) => format!("{} = {:?} {}", variable.full_name, operation, describe_operand(operand)),
+            MacroInstruction::LoadImmediate(variable, value) => format!("{} = {}", variable.full_name, value),
+            MacroInstruction::LoadConstantAddress(variable, bytes) => format!("{} = constant {:?}", variable.full_name, bytes),
+            MacroInstruction::Copy(variable, source) => format!("{} = copy {}", variable.full_name, source.full_name),
+            MacroInstruction::Load(variable, address, offset) => format!("{} = load {} {}", variable.full_name, address.full_name, offset),
+            MacroInstruction::Store(address, offset, value) => format!("store {} {} {}", address.full_name, offset, value.full_name),
+            MacroInstruction::AddressOf(variable, source) => format!("{} = address-of {}", variable.full_name, source.full_name),
+            MacroInstruction::Label(label) => format!("{}:", label),
+            MacroInstruction::Jump(label) => format!("jump {}", label),
+            MacroInstruction::BranchIf(variable, label) => format!("branch-if {} {}", variable.full_name, label),
+            MacroInstruction::Return(None) => "return".to_string(),
+            MacroInstruction::Return(Some(variable)) => format!("return {}", variable.full_name),
+        }).collect()
+    }
+
+    fn describe_operand(operand: &Operand) -> String {
+        match operand {
+            Operand::Variable(variable) => variable.full_name.rsplit(':').next().unwrap().to_string(),
+            Operand::Immediate(value) => value.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_lower_module(){
+        let source = "
+            extern fun free(pointer: *Int8);
+
+            type Main {
+                fun main(buffer: *Int8) {
+                    {
+                        var myVar: Int;
+                        helper(myVar, buffer);
+                    }
+                    free(buffer);
+                }
+
+                fun helper(a: Int, b: *Int8) {}
+            }
+        ";
+
+        let (module, diagnostics) = parse_source("src/main.rsl".to_string(), source);
+        assert_eq!(diagnostics, vec![]);
+
+        let (functions, diagnostics) = Lowering::new(&[module], "my_app".to_string()).lower_program();
+        assert_eq!(diagnostics, vec![]);
+
+        assert_eq!(functions[0].name, "main");
+        assert_eq!(describe(&functions[0].instructions), vec![
+            "declare my_app:main.rsl:Main:main:buffer",
+            "get-argument 0 my_app:main.rsl:Main:main:buffer",
+            "declare my_app:main.rsl:Main:main:block1:myVar",
+            "argument 0 my_app:main.rsl:Main:main:block1:myVar",
+            "argument 1 my_app:main.rsl:Main:main:buffer",
+            "call Main.helper 2",
+            "destroy my_app:main.rsl:Main:main:block1:myVar",
+            "argument 0 my_app:main.rsl:Main:main:buffer",
+            "call free 1",
+            "destroy my_app:main.rsl:Main:main:buffer",
+        ]);
+
+        assert_eq!(functions[1].name, "Main.helper");
+        assert_eq!(describe(&functions[1].instructions), vec![
+            "declare my_app:main.rsl:Main:helper:a",
+            "get-argument 0 my_app:main.rsl:Main:helper:a",
+            "declare my_app:main.rsl:Main:helper:b",
+            "get-argument 1 my_app:main.rsl:Main:helper:b",
+            "destroy my_app:main.rsl:Main:helper:b",
+            "destroy my_app:main.rsl:Main:helper:a",
+        ]);
+    }
+
+    #[test]
+    fn test_lower_arithmetic(){
+        let source = "
+            fun main(a: Int) {
+                var b = a + 2 * 3;
+                b -= -a;
+                let c = (a + b) * ~b;
+            }
+        ";
+
+        let (module, diagnostics) = parse_source("main.rsl".to_string(), source);
+        assert_eq!(diagnostics, vec![]);
+
+        let (functions, diagnostics) = Lowering::new(&[module], "my_app".to_string()).lower_program();
+        assert_eq!(diagnostics, vec![]);
+
+        assert_eq!(describe(&functions[0].instructions), vec![
+            "declare my_app:main.rsl:main:a",
+            "get-argument 0 my_app:main.rsl:main:a",
+            "declare my_app:main.rsl:main:temporary.1",
+            "my_app:main.rsl:main:temporary.1 = Multiply 2 3",
+            "declare my_app:main.rsl:main:b",
+            "my_app:main.rsl:main:b = Add a temporary.1",
+            "destroy my_app:main.rsl:main:temporary.1",
+            "declare my_app:main.rsl:main:temporary.2",
+            "my_app:main.rsl:main:temporary.2 = Negate a",
+            "my_app:main.rsl:main:b = Subtract b temporary.2",
+            "destroy my_app:main.rsl:main:temporary.2",
+            "declare my_app:main.rsl:main:temporary.3",
+            "my_app:main.rsl:main:temporary.3 = Add a b",
+            "declare my_app:main.rsl:main:temporary.4",
+            "my_app:main.rsl:main:temporary.4 = Not b",
+            "declare my_app:main.rsl:main:c",
+            "my_app:main.rsl:main:c = Multiply temporary.3 temporary.4",
+            "destroy my_app:main.rsl:main:temporary.4",
+            "destroy my_app:main.rsl:main:temporary.3",
+            "destroy my_app:main.rsl:main:c",
+            "destroy my_app:main.rsl:main:b",
+            "destroy my_app:main.rsl:main:a",
+        ]);
+    }
+
+    #[test]
+    fn test_lower_types(){
+        let source = "
+            fun main(a: UInt8, b: Int32, p: *Int8) {
+                var c: UInt32 = a / 2;
+                let d = b >> 1;
+                c >>= 3;
+            }
+        ";
+
+        let (module, diagnostics) = parse_source("main.rsl".to_string(), source);
+        assert_eq!(diagnostics, vec![]);
+
+        let (functions, diagnostics) = Lowering::new(&[module], "my_app".to_string()).lower_program();
+        assert_eq!(diagnostics, vec![]);
+
+        // The declarations carry the sizes of the integer types, everything else is as large as a register
+        let types: Vec<(String, ValueType)> = functions[0].instructions.iter().filter_map(|x| match x {
+            MacroInstruction::DeclareVariable(variable) => Some((variable.full_name.rsplit(':').next().unwrap().to_string(), variable.value_type.clone())),
+            _ => None,
+        }).collect();
+        assert_eq!(types, vec![
+            ("a".to_string(), ValueType::named("u8").unwrap()),
+            ("b".to_string(), ValueType::named("i32").unwrap()),
+            ("p".to_string(), ValueType::default()),
+            ("c".to_string(), ValueType::named("u32").unwrap()),
+            ("d".to_string(), ValueType::default()),
+        ]);
+
+        // Operations on unsigned variables are unsigned
+        let instructions = describe(&functions[0].instructions);
+        assert!(instructions.contains(&"my_app:main.rsl:main:c = DivideUnsigned a 2".to_string()));
+        assert!(instructions.contains(&"my_app:main.rsl:main:d = ShiftRightArithmetic b 1".to_string()));
+        assert!(instructions.contains(&"my_app:main.rsl:main:c = ShiftRightLogical c 3".to_string()));
+    }
+
+    #[test]
+    fn test_lower_control_flow(){
+        let source = "
+            fun main(a: Int) {
+                while a > 0 && a != 5 {
+                    var b = a - 1;
+                    if b == 3 { break; } else { continue; }
+                }
+            }
+        ";
+
+        let (module, diagnostics) = parse_source("main.rsl".to_string(), source);
+        assert_eq!(diagnostics, vec![]);
+
+        let (functions, diagnostics) = Lowering::new(&[module], "my_app".to_string()).lower_program();
+        assert_eq!(diagnostics, vec![]);
+
+        assert_eq!(describe(&functions[0].instructions), vec![
+            "declare my_app:main.rsl:main:a",
+            "get-argument 0 my_app:main.rsl:main:a",
+            "loop1.start:",
+            "declare my_app:main.rsl:main:temporary.1",
+            "declare my_app:main.rsl:main:temporary.2",
+            "my_app:main.rsl:main:temporary.2 = GreaterSigned a 0",
+            "my_app:main.rsl:main:temporary.1 = NotEqual temporary.2 0",
+            "declare my_app:main.rsl:main:temporary.3",
+            "my_app:main.rsl:main:temporary.3 = Equal temporary.1 0",
+            "branch-if my_app:main.rsl:main:temporary.3 and1.end",
+            "declare my_app:main.rsl:main:temporary.4",
+            "my_app:main.rsl:main:temporary.4 = NotEqual a 5",
+            "my_app:main.rsl:main:temporary.1 = NotEqual temporary.4 0",
+            "and1.end:",
+            "declare my_app:main.rsl:main:temporary.5",
+            "my_app:main.rsl:main:temporary.5 = Equal temporary.1 0",
+            "branch-if my_app:main.rsl:main:temporary.5 loop1.end",
+            "destroy my_app:main.rsl:main:temporary.5",
+            "destroy my_app:main.rsl:main:temporary.4",
+            "destroy my_app:main.rsl:main:temporary.3",
+            "destroy my_app:main.rsl:main:temporary.2",
+            "destroy my_app:main.rsl:main:temporary.1",
+            "declare my_app:main.rsl:main:loop1:b",
+            "my_app:main.rsl:main:loop1:b = Subtract a 1",
+            "declare my_app:main.rsl:main:loop1:temporary.6",
+            "my_app:main.rsl:main:loop1:temporary.6 = NotEqual b 3",
+            "branch-if my_app:main.rsl:main:loop1:temporary.6 if1.else",
+            "destroy my_app:main.rsl:main:loop1:temporary.6",
+            "destroy my_app:main.rsl:main:loop1:b",
+            "jump loop1.end",
+            "jump if1.end",
+            "if1.else:",
+            "destroy my_app:main.rsl:main:loop1:temporary.6",
+            "destroy my_app:main.rsl:main:loop1:b",
+            "jump loop1.start",
+            "if1.end:",
+            "destroy my_app:main.rsl:main:loop1:b",
+            "jump loop1.start",
+            "loop1.end:",
+            "destroy my_app:main.rsl:main:temporary.5",
+            "destroy my_app:main.rsl:main:temporary.4",
+            "destroy my_app:main.rsl:main:temporary.3",
+            "destroy my_app:main.rsl:main:temporary.2",
+            "destroy my_app:main.rsl:main:temporary.1",
+            "destroy my_app:main.rsl:main:a",
+        ]);
+    }
+
+    #[test]
+    fn test_lower_functions(){
+        let source = "
+            extern fun abs(value: Int) -> Int;
+
+            fun twice(a: Int) -> Int {
+                let b = a * 2;
+                return b;
+            }
+
+            fun main(a: Int) -> Int {
+                let b = abs(twice(a)) + 1;
+                return b;
+            }
+        ";
+
+        let (module, diagnostics) = parse_source("main.rsl".to_string(), source);
+        assert_eq!(diagnostics, vec![]);
+
+        let (functions, diagnostics) = Lowering::new(&[module], "my_app".to_string()).lower_program();
+        assert_eq!(diagnostics, vec![]);
+
+        assert_eq!((functions[0].name.as_str(), functions[0].parameter_count, functions[0].returns_value), ("twice", 1, true));
+        assert_eq!(describe(&functions[1].instructions), vec![
+            "declare my_app:main.rsl:main:a",
+            "get-argument 0 my_app:main.rsl:main:a",
+            "declare my_app:main.rsl:main:temporary.1",
+            "argument 0 my_app:main.rsl:main:a",
+            "my_app:main.rsl:main:temporary.1 = call twice 1",
+            "declare my_app:main.rsl:main:temporary.2",
+            "argument 0 my_app:main.rsl:main:temporary.1",
+            "my_app:main.rsl:main:temporary.2 = call abs 1",
+            "declare my_app:main.rsl:main:b",
+            "my_app:main.rsl:main:b = Add temporary.2 1",
+            "destroy my_app:main.rsl:main:temporary.2",
+            "destroy my_app:main.rsl:main:temporary.1",
+            "return my_app:main.rsl:main:b",
+            "destroy my_app:main.rsl:main:b",
+            "destroy my_app:main.rsl:main:a",
+        ]);
+    }
+
+    #[test]
+    fn test_lower_values(){
+        let source = "
+            extern fun puts(text: *Int8) -> Int32;
+
+            fun main() -> Int {
+                var a = 'a';
+                let b = a;
+                puts(\"hi\");
+                return 0;
+            }
+        ";
+
+        let (module, diagnostics) = parse_source("main.rsl".to_string(), source);
+        assert_eq!(diagnostics, vec![]);
+
+        let (functions, diagnostics) = Lowering::new(&[module], "my_app".to_string()).lower_program();
+        assert_eq!(diagnostics, vec![]);
+
+        assert_eq!(describe(&functions[0].instructions), vec![
+            "declare my_app:main.rsl:main:a",
+            "my_app:main.rsl:main:a = 97",
+            "declare my_app:main.rsl:main:b",
+            "my_app:main.rsl:main:b = copy my_app:main.rsl:main:a",
+            "declare my_app:main.rsl:main:temporary.1",
+            "my_app:main.rsl:main:temporary.1 = constant [104, 105, 0]",
+            "argument 0 my_app:main.rsl:main:temporary.1",
+            "call puts 1",
+            "destroy my_app:main.rsl:main:temporary.1",
+            "declare my_app:main.rsl:main:temporary.2",
+            "my_app:main.rsl:main:temporary.2 = 0",
+            "return my_app:main.rsl:main:temporary.2",
+            "destroy my_app:main.rsl:main:temporary.2",
+            "destroy my_app:main.rsl:main:b",
+            "destroy my_app:main.rsl:main:a",
+        ]);
+    }
+
+    #[test]
+    fn test_lower_pointers(){
+        let source = "
+            fun main() {
+                var a = 1;
+                let p = &a;
+                *p += 2;
+                a = *p;
+            }
+        ";
+
+        let (module, diagnostics) = parse_source("main.rsl".to_string(), source);
+        assert_eq!(diagnostics, vec![]);
+
+        let (functions, diagnostics) = Lowering::new(&[module], "my_app".to_string()).lower_program();
+        assert_eq!(diagnostics, vec![]);
+
+        assert_eq!(describe(&functions[0].instructions), vec![
+            "declare my_app:main.rsl:main:a",
+            "my_app:main.rsl:main:a = 1",
+            "declare my_app:main.rsl:main:p",
+            "my_app:main.rsl:main:p = address-of my_app:main.rsl:main:a",
+            "declare my_app:main.rsl:main:temporary.1",
+            "my_app:main.rsl:main:temporary.1 = load my_app:main.rsl:main:p 0",
+            "my_app:main.rsl:main:temporary.1 = Add temporary.1 2",
+            "store my_app:main.rsl:main:p 0 my_app:main.rsl:main:temporary.1",
+            "destroy my_app:main.rsl:main:temporary.1",
+            "my_app:main.rsl:main:a = load my_app:main.rsl:main:p 0",
+            "destroy my_app:main.rsl:main:p",
+            "destroy my_app:main.rsl:main:a",
+        ]);
+    }
+
+    #[test]
+    fn test_semantic_errors(){
+        let source = "
+            module other_app;
+            fun main() {
+                var a: Int;
+                var a: Int;
+                unknown(a);
+                main(b);
+                var c = nothing() + 1;
+                return a;
+            }
+
+            fun nothing() {}
+
+            fun value() -> Int {
+                return;
+            }
+        ";
+
+        let (module, _) = parse_source("main.rsl".to_string(), source);
+        let (_, diagnostics) = Lowering::new(&[module], "my_app".to_string()).lower_program();
+
+        let messages: Vec<String> = diagnostics.iter().map(|x| x.to_string()).collect();
+        assert_eq!(messages, vec![
+            "main.rsl:5:17: Variable \"a\" has already been declared in this scope.".to_string(),
+            "main.rsl:6:17: Unknown function \"unknown\".".to_string(),
+            "main.rsl:7:17: Function \"main\" expects 0 argument(s), but 1 were given.".to_string(),
+            "main.rsl:8:25: Function \"nothing\" doesn't return a value.".to_string(),
+            "main.rsl:9:17: The function doesn't have a return type, so it can't return a value.".to_string(),
+            "main.rsl:15:17: The function has a return type, so it has to return a value.".to_string(),
+        ]);
+    }
+}
@@ -0,0 +1,3 @@
+#[allow(clippy::module_inception)]
+pub mod lowering;
+pub mod scope;
\ No newline at end of file
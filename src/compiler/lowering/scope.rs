@@ -0,0 +1,70 @@
+use crate::compiler::low_level::variable::{ValueType, Variable};
+
+/// A lexical scope (function body, loop, block, ...) and the variables declared in it
+pub struct Scope {
+    pub name: String,                           // e.g. "main" or "loop1", becomes part of the full name of the variables
+    pub variables: Vec<(String, Variable)>,     // (name in the source, variable), in the order of declaration
+}
+
+/// All the scopes that are currently open, the innermost one is the last one
+pub struct ScopeStack {
+    prefix: String,                             // The part of the full name that's outside any scope (e.g. my_app:main.rsl:Main)
+    scopes: Vec<Scope>,
+}
+
+impl ScopeStack {
+    pub fn new(prefix: String) -> ScopeStack {
+        ScopeStack { prefix, scopes: Vec::new() }
+    }
+
+    pub fn push(&mut self, name: String) {
+        self.scopes.push(Scope { name, variables: Vec::new() });
+    }
+
+    pub fn pop(&mut self) -> Option<Scope> {
+        self.scopes.pop()
+    }
+
+    /// The full name a variable declared in the innermost scope would get (e.g. my_app:main.rsl:Main:main:loop1:myVar)
+    pub fn full_name(&self, name: &str) -> String {
+        let mut full_name = self.prefix.clone();
+
+        for scope in &self.scopes {
+            full_name += ":";
+            full_name += scope.name.as_str();
+        }
+
+        format!("{}:{}", full_name, name)
+    }
+
+    /// Declare a variable (of the type) in the innermost scope.
+    /// Returns None if a variable with the same name has already been declared in that scope.
+    pub fn declare(&mut self, name: &str, value_type: ValueType) -> Option<Variable> {
+        let full_name = self.full_name(name);
+        let scope = self.scopes.last_mut()?;
+
+        if scope.variables.iter().any(|(x, _)| x == name) { return None; }
+
+        let variable = Variable::new(full_name, vec![]).with_type(value_type);
+        scope.variables.push((name.to_string(), variable.clone()));
+
+        Some(variable)
+    }
+
+    /// The amount of scopes that are open
+    pub fn depth(&self) -> usize {
+        self.scopes.len()
+    }
+
+    /// The variables of the scopes that were opened once the given amount of scopes was open (in the order of declaration)
+    pub fn variables_since(&self, depth: usize) -> Vec<Variable> {
+        self.scopes.iter().skip(depth).flat_map(|x| x.variables.iter().map(|(_, variable)| variable.clone())).collect()
+    }
+
+    /// Find the variable a name refers to, inner scopes shadow outer ones
+    pub fn resolve(&self, name: &str) -> Option<Variable> {
+        self.scopes.iter().rev()
+            .find_map(|scope| scope.variables.iter().rev().find(|(x, _)| x == name))
+            .map(|(_, variable)| variable.clone())
+    }
+}
@@ -1 +1,5 @@
-pub mod low_level;
\ No newline at end of file
+pub mod low_level;
+pub mod lexer;
+pub mod parser;
+pub mod lowering;
+pub mod diagnostic;
\ No newline at end of file
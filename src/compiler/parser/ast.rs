@@ -0,0 +1,147 @@
+use crate::compiler::lexer::span::Span;
+
+/// Everything that's been parsed from a single .rsl file
+#[derive(Clone, Debug, PartialEq)]
+pub struct Module {
+    pub name: Option<String>,       // From the "module my_app;" declaration at the start of the file (if there is one)
+    pub file: String,
+    pub imports: Vec<Import>,
+    pub items: Vec<Item>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Import {
+    pub path: Vec<String>,          // import my_lib.io; -> ["my_lib", "io"]
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Item {
+    Type(TypeDefinition),
+    Function(Function),
+    ExternFunction(FunctionSignature),      // A function that's defined somewhere else (e.g. in libc)
+}
+
+/// type Main { ... }
+#[derive(Clone, Debug, PartialEq)]
+pub struct TypeDefinition {
+    pub name: String,
+    pub fields: Vec<Field>,
+    pub functions: Vec<Function>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Field {
+    pub name: String,
+    pub type_name: TypeName,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TypeName {
+    Named(String),                  // Int, Float64, Main, ...
+    Pointer(Box<TypeName>),         // *Int
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FunctionSignature {
+    pub name: String,
+    pub parameters: Vec<Parameter>,
+    pub is_variadic: bool,          // Whether the parameters end with "..."
+    pub return_type: Option<TypeName>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Parameter {
+    pub name: String,
+    pub type_name: TypeName,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Function {
+    pub signature: FunctionSignature,
+    pub body: Block,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Block {
+    pub statements: Vec<Statement>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Statement {
+    pub kind: StatementKind,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum StatementKind {
+    VariableDeclaration { name: String, is_mutable: bool, type_name: Option<TypeName>, value: Option<Expression> },
+    Assignment { target: Expression, operator: Option<BinaryOperator>, value: Expression },   // The operator is set for compound assignments (+= etc.)
+    Expression(Expression),
+    Block(Block),
+    If { condition: Expression, then_block: Block, else_block: Option<Block> },                // "else if" is stored as an else block containing only the if statement
+    Loop(Block),
+    While { condition: Expression, body: Block },
+    Break,
+    Continue,
+    Return(Option<Expression>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Expression {
+    pub kind: ExpressionKind,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExpressionKind {
+    IntegerLiteral(u64),
+    FloatLiteral(f64),
+    StringLiteral(String),
+    CharLiteral(char),
+    BoolLiteral(bool),
+    Identifier(String),
+
+    Unary(UnaryOperator, Box<Expression>),
+    Binary(BinaryOperator, Box<Expression>, Box<Expression>),
+    Cast(Box<Expression>, TypeName),                            // value as Int
+    Call { callee: Box<Expression>, arguments: Vec<Expression> },
+    MemberAccess(Box<Expression>, String),                      // Main.main or my_object.field
+    Index(Box<Expression>, Box<Expression>),                    // array[index]
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnaryOperator {
+    Negate,         // -
+    Not,            // !
+    BitwiseNot,     // ~
+    AddressOf,      // &
+    Dereference,    // *
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinaryOperator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+    BitwiseAnd,
+    BitwiseOr,
+    BitwiseXor,
+    ShiftLeft,
+    ShiftRight,
+    LogicalAnd,
+    LogicalOr,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
@@ -0,0 +1,3 @@
+#[allow(clippy::module_inception)]
+pub mod parser;
+pub mod ast;
\ No newline at end of file
@@ -0,0 +1,657 @@
+use crate::compiler::diagnostic::Diagnostic;
+use crate::compiler::lexer::lexer::Lexer;
+use crate::compiler::lexer::span::Span;
+use crate::compiler::lexer::token::{Delimiter, Keyword, Operator, Token, TokenKind};
+use crate::compiler::parser::ast::*;
+
+/// Lex and parse a whole file.
+/// The diagnostics of both phases are returned together, the module contains everything that could be parsed regardless.
+pub fn parse_source(file: String, source: &str) -> (Module, Vec<Diagnostic>) {
+    let (tokens, mut diagnostics) = Lexer::new(file.clone(), source).tokenize();
+    let (module, parser_diagnostics) = Parser::new(file, tokens).parse_module();
+
+    diagnostics.extend(parser_diagnostics);
+
+    (module, diagnostics)
+}
+
+/// A recursive-descent parser turning tokens into a Module.
+/// Every parse_* function returns None if it failed, in which case the error has already been reported.
+/// The caller then skips ahead to a place where parsing can continue (see synchronize).
+pub struct Parser {
+    file: String,
+    tokens: Vec<Token>,
+    position: usize,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Parser {
+    /// The tokens must end with an EndOfFile token (as the ones from the lexer do).
+    pub fn new(file: String, tokens: Vec<Token>) -> Parser {
+        Parser { file, tokens, position: 0, diagnostics: Vec::new() }
+    }
+
+    pub fn parse_module(mut self) -> (Module, Vec<Diagnostic>) {
+        let mut module = Module { name: None, file: self.file.clone(), imports: Vec::new(), items: Vec::new() };
+
+        if self.eat_keyword(Keyword::Module) {
+            if let Some(name) = self.expect_identifier() {
+                module.name = Some(name);
+            }
+            self.expect_delimiter(Delimiter::Semicolon);
+        }
+
+        while !self.is_at_end() {
+            let start = self.position;
+
+            let success = match self.peek().kind.clone() {
+                TokenKind::Keyword(Keyword::Import) => self.parse_import().map(|x| module.imports.push(x)),
+                TokenKind::Keyword(Keyword::Type) => self.parse_type_definition().map(|x| module.items.push(Item::Type(x))),
+                TokenKind::Keyword(Keyword::Fun) => self.parse_function().map(|x| module.items.push(Item::Function(x))),
+                TokenKind::Keyword(Keyword::Extern) => self.parse_extern_function().map(|x| module.items.push(Item::ExternFunction(x))),
+                TokenKind::Keyword(Keyword::Module) => {
+                    self.error_here("The module declaration must be at the start of the file.".to_string());
+                    None
+                }
+                _ => {
+                    self.error_here(format!("Expected \"type\", \"fun\", \"extern\" or \"import\", found {}.", self.peek().kind));
+                    None
+                }
+            };
+
+            if success.is_none() {
+                self.synchronize(start, &[Keyword::Import, Keyword::Type, Keyword::Fun, Keyword::Extern]);
+            }
+        }
+
+        (module, self.diagnostics)
+    }
+
+    // Items
+
+    fn parse_import(&mut self) -> Option<Import> {
+        let span = self.expect_keyword(Keyword::Import)?;
+
+        let mut path = vec![self.expect_identifier()?];
+        while self.eat_operator(Operator::Dot) {
+            path.push(self.expect_identifier()?);
+        }
+
+        self.expect_delimiter(Delimiter::Semicolon)?;
+
+        Some(Import { path, span })
+    }
+
+    fn parse_type_definition(&mut self) -> Option<TypeDefinition> {
+        let span = self.expect_keyword(Keyword::Type)?;
+        let name = self.expect_identifier()?;
+        self.expect_delimiter(Delimiter::OpenBrace)?;
+
+        let mut type_definition = TypeDefinition { name, fields: Vec::new(), functions: Vec::new(), span };
+
+        while !self.check_delimiter(Delimiter::CloseBrace) && !self.is_at_end() {
+            let start = self.position;
+
+            let success = match self.peek().kind {
+                TokenKind::Keyword(Keyword::Var) => self.parse_field().map(|x| type_definition.fields.push(x)),
+                TokenKind::Keyword(Keyword::Fun) => self.parse_function().map(|x| type_definition.functions.push(x)),
+                _ => {
+                    self.error_here(format!("Expected \"var\" or \"fun\" in type \"{}\", found {}.", type_definition.name, self.peek().kind));
+                    None
+                }
+            };
+
+            if success.is_none() {
+                self.synchronize(start, &[Keyword::Var, Keyword::Fun]);
+            }
+        }
+
+        self.expect_delimiter(Delimiter::CloseBrace)?;
+
+        Some(type_definition)
+    }
+
+    fn parse_field(&mut self) -> Option<Field> {
+        let span = self.expect_keyword(Keyword::Var)?;
+        let name = self.expect_identifier()?;
+        self.expect_delimiter(Delimiter::Colon)?;
+        let type_name = self.parse_type_name()?;
+        self.expect_delimiter(Delimiter::Semicolon)?;
+
+        Some(Field { name, type_name, span })
+    }
+
+    fn parse_function(&mut self) -> Option<Function> {
+        let signature = self.parse_function_signature()?;
+        let body = self.parse_block()?;
+
+        Some(Function { signature, body })
+    }
+
+    fn parse_extern_function(&mut self) -> Option<FunctionSignature> {
+        self.expect_keyword(Keyword::Extern)?;
+        let signature = self.parse_function_signature()?;
+        self.expect_delimiter(Delimiter::Semicolon)?;
+
+        Some(signature)
+    }
+
+    /// fun name(a: Int, b: *Char, ...) -> Int
+    fn parse_function_signature(&mut self) -> Option<FunctionSignature> {
+        let span = self.expect_keyword(Keyword::Fun)?;
+        let name = self.expect_identifier()?;
+        self.expect_delimiter(Delimiter::OpenParenthesis)?;
+
+        let mut parameters = Vec::new();
+        let mut is_variadic = false;
+
+        while !self.check_delimiter(Delimiter::CloseParenthesis) {
+            if self.eat_operator(Operator::Ellipsis) {
+                // Nothing may follow the ellipsis
+                is_variadic = true;
+                break;
+            }
+
+            let parameter_span = self.peek().span.clone();
+            let parameter_name = self.expect_identifier()?;
+            self.expect_delimiter(Delimiter::Colon)?;
+            let type_name = self.parse_type_name()?;
+
+            parameters.push(Parameter { name: parameter_name, type_name, span: parameter_span });
+
+            if !self.eat_delimiter(Delimiter::Comma) { break; }
+        }
+
+        self.expect_delimiter(Delimiter::CloseParenthesis)?;
+
+        let return_type = if self.eat_operator(Operator::Arrow) { Some(self.parse_type_name()?) } else { None };
+
+        Some(FunctionSignature { name, parameters, is_variadic, return_type, span })
+    }
+
+    fn parse_type_name(&mut self) -> Option<TypeName> {
+        if self.eat_operator(Operator::Star) {
+            return Some(TypeName::Pointer(Box::new(self.parse_type_name()?)));
+        }
+
+        Some(TypeName::Named(self.expect_identifier()?))
+    }
+
+    // Statements
+
+    fn parse_block(&mut self) -> Option<Block> {
+        let span = self.expect_delimiter(Delimiter::OpenBrace)?;
+
+        let mut statements = Vec::new();
+
+        while !self.check_delimiter(Delimiter::CloseBrace) && !self.is_at_end() {
+            let start = self.position;
+
+            match self.parse_statement() {
+                Some(statement) => statements.push(statement),
+                None => self.synchronize(start, &[Keyword::Var, Keyword::Let, Keyword::If, Keyword::Loop, Keyword::While, Keyword::Break, Keyword::Continue, Keyword::Return]),
+            }
+        }
+
+        self.expect_delimiter(Delimiter::CloseBrace)?;
+
+        Some(Block { statements, span })
+    }
+
+    fn parse_statement(&mut self) -> Option<Statement> {
+        let span = self.peek().span.clone();
+
+        let kind = match self.peek().kind {
+            TokenKind::Keyword(Keyword::Var) | TokenKind::Keyword(Keyword::Let) => self.parse_variable_declaration()?,
+            TokenKind::Keyword(Keyword::If) => self.parse_if()?,
+            TokenKind::Keyword(Keyword::Loop) => {
+                self.advance();
+                StatementKind::Loop(self.parse_block()?)
+            }
+            TokenKind::Keyword(Keyword::While) => {
+                self.advance();
+                let condition = self.parse_expression()?;
+                let body = self.parse_block()?;
+                StatementKind::While { condition, body }
+            }
+            TokenKind::Keyword(Keyword::Break) => {
+                self.advance();
+                self.expect_delimiter(Delimiter::Semicolon)?;
+                StatementKind::Break
+            }
+            TokenKind::Keyword(Keyword::Continue) => {
+                self.advance();
+                self.expect_delimiter(Delimiter::Semicolon)?;
+                StatementKind::Continue
+            }
+            TokenKind::Keyword(Keyword::Return) => {
+                self.advance();
+                let value = if self.check_delimiter(Delimiter::Semicolon) { None } else { Some(self.parse_expression()?) };
+                self.expect_delimiter(Delimiter::Semicolon)?;
+                StatementKind::Return(value)
+            }
+            TokenKind::Delimiter(Delimiter::OpenBrace) => StatementKind::Block(self.parse_block()?),
+            _ => self.parse_expression_statement()?,
+        };
+
+        Some(Statement { kind, span })
+    }
+
+    /// var name: Type = value; or let name = value;
+    fn parse_variable_declaration(&mut self) -> Option<StatementKind> {
+        let is_mutable = self.peek().kind == TokenKind::Keyword(Keyword::Var);
+        self.advance();
+
+        let name = self.expect_identifier()?;
+        let type_name = if self.eat_delimiter(Delimiter::Colon) { Some(self.parse_type_name()?) } else { None };
+        let value = if self.eat_operator(Operator::Assign) { Some(self.parse_expression()?) } else { None };
+
+        self.expect_delimiter(Delimiter::Semicolon)?;
+
+        Some(StatementKind::VariableDeclaration { name, is_mutable, type_name, value })
+    }
+
+    fn parse_if(&mut self) -> Option<StatementKind> {
+        self.expect_keyword(Keyword::If)?;
+
+        let condition = self.parse_expression()?;
+        let then_block = self.parse_block()?;
+
+        let mut else_block = None;
+
+        if self.eat_keyword(Keyword::Else) {
+            if self.check_keyword(Keyword::If) {
+                // Wrap the "else if" in a block of its own
+                let span = self.peek().span.clone();
+                let kind = self.parse_if()?;
+                else_block = Some(Block { statements: vec![Statement { kind, span: span.clone() }], span });
+            } else {
+                else_block = Some(self.parse_block()?);
+            }
+        }
+
+        Some(StatementKind::If { condition, then_block, else_block })
+    }
+
+    /// An expression on its own (like a call) or an assignment
+    fn parse_expression_statement(&mut self) -> Option<StatementKind> {
+        let expression = self.parse_expression()?;
+
+        let assignment_operator = match self.peek().kind {
+            TokenKind::Operator(Operator::Assign) => Some(None),
+            TokenKind::Operator(Operator::PlusAssign) => Some(Some(BinaryOperator::Add)),
+            TokenKind::Operator(Operator::MinusAssign) => Some(Some(BinaryOperator::Subtract)),
+            TokenKind::Operator(Operator::StarAssign) => Some(Some(BinaryOperator::Multiply)),
+            TokenKind::Operator(Operator::SlashAssign) => Some(Some(BinaryOperator::Divide)),
+            TokenKind::Operator(Operator::PercentAssign) => Some(Some(BinaryOperator::Remainder)),
+            TokenKind::Operator(Operator::AmpersandAssign) => Some(Some(BinaryOperator::BitwiseAnd)),
+            TokenKind::Operator(Operator::PipeAssign) => Some(Some(BinaryOperator::BitwiseOr)),
+            TokenKind::Operator(Operator::CaretAssign) => Some(Some(BinaryOperator::BitwiseXor)),
+            TokenKind::Operator(Operator::ShiftLeftAssign) => Some(Some(BinaryOperator::ShiftLeft)),
+            TokenKind::Operator(Operator::ShiftRightAssign) => Some(Some(BinaryOperator::ShiftRight)),
+            _ => None,
+        };
+
+        let kind = match assignment_operator {
+            Some(operator) => {
+                if !matches!(expression.kind, ExpressionKind::Identifier(_) | ExpressionKind::MemberAccess(_, _) | ExpressionKind::Index(_, _) | ExpressionKind::Unary(UnaryOperator::Dereference, _)) {
+                    self.diagnostics.push(Diagnostic::new("Only variables, fields, elements and dereferenced pointers can be assigned to.".to_string(), expression.span.clone()));
+                }
+
+                self.advance();
+                let value = self.parse_expression()?;
+                StatementKind::Assignment { target: expression, operator, value }
+            }
+            None => StatementKind::Expression(expression),
+        };
+
+        self.expect_delimiter(Delimiter::Semicolon)?;
+
+        Some(kind)
+    }
+
+    // Expressions
+
+    pub fn parse_expression(&mut self) -> Option<Expression> {
+        self.parse_binary(0)
+    }
+
+    /// The binary operator at the current position and how strongly it binds (higher binds stronger)
+    fn peek_binary_operator(&self) -> Option<(BinaryOperator, u8)> {
+        let TokenKind::Operator(operator) = self.peek().kind else { return None; };
+
+        let binary_operator = match operator {
+            Operator::OrOr => (BinaryOperator::LogicalOr, 1),
+            Operator::AndAnd => (BinaryOperator::LogicalAnd, 2),
+            Operator::Equal => (BinaryOperator::Equal, 3),
+            Operator::NotEqual => (BinaryOperator::NotEqual, 3),
+            Operator::Less => (BinaryOperator::Less, 3),
+            Operator::LessEqual => (BinaryOperator::LessEqual, 3),
+            Operator::Greater => (BinaryOperator::Greater, 3),
+            Operator::GreaterEqual => (BinaryOperator::GreaterEqual, 3),
+            Operator::Pipe => (BinaryOperator::BitwiseOr, 4),
+            Operator::Caret => (BinaryOperator::BitwiseXor, 5),
+            Operator::Ampersand => (BinaryOperator::BitwiseAnd, 6),
+            Operator::ShiftLeft => (BinaryOperator::ShiftLeft, 7),
+            Operator::ShiftRight => (BinaryOperator::ShiftRight, 7),
+            Operator::Plus => (BinaryOperator::Add, 8),
+            Operator::Minus => (BinaryOperator::Subtract, 8),
+            Operator::Star => (BinaryOperator::Multiply, 9),
+            Operator::Slash => (BinaryOperator::Divide, 9),
+            Operator::Percent => (BinaryOperator::Remainder, 9),
+            _ => return None,
+        };
+
+        Some(binary_operator)
+    }
+
+    /// Precedence climbing, all binary operators are left-associative
+    fn parse_binary(&mut self, minimum_precedence: u8) -> Option<Expression> {
+        let mut left = self.parse_cast()?;
+
+        while let Some((operator, precedence)) = self.peek_binary_operator() {
+            if precedence < minimum_precedence { break; }
+
+            self.advance();
+            let right = self.parse_binary(precedence + 1)?;
+
+            let span = left.span.clone();
+            left = Expression { kind: ExpressionKind::Binary(operator, Box::new(left), Box::new(right)), span };
+        }
+
+        Some(left)
+    }
+
+    fn parse_cast(&mut self) -> Option<Expression> {
+        let mut expression = self.parse_unary()?;
+
+        while self.eat_keyword(Keyword::As) {
+            let type_name = self.parse_type_name()?;
+            let span = expression.span.clone();
+            expression = Expression { kind: ExpressionKind::Cast(Box::new(expression), type_name), span };
+        }
+
+        Some(expression)
+    }
+
+    fn parse_unary(&mut self) -> Option<Expression> {
+        let span = self.peek().span.clone();
+
+        let operator = match self.peek().kind {
+            TokenKind::Operator(Operator::Minus) => UnaryOperator::Negate,
+            TokenKind::Operator(Operator::Exclamation) => UnaryOperator::Not,
+            TokenKind::Operator(Operator::Tilde) => UnaryOperator::BitwiseNot,
+            TokenKind::Operator(Operator::Ampersand) => UnaryOperator::AddressOf,
+            TokenKind::Operator(Operator::Star) => UnaryOperator::Dereference,
+            _ => return self.parse_postfix(),
+        };
+
+        self.advance();
+        let operand = self.parse_unary()?;
+
+        Some(Expression { kind: ExpressionKind::Unary(operator, Box::new(operand)), span })
+    }
+
+    /// Calls, member accesses and indexing
+    fn parse_postfix(&mut self) -> Option<Expression> {
+        let mut expression = self.parse_primary()?;
+
+        loop {
+            let span = expression.span.clone();
+
+            if self.eat_delimiter(Delimiter::OpenParenthesis) {
+                let mut arguments = Vec::new();
+
+                while !self.check_delimiter(Delimiter::CloseParenthesis) {
+                    arguments.push(self.parse_expression()?);
+                    if !self.eat_delimiter(Delimiter::Comma) { break; }
+                }
+                self.expect_delimiter(Delimiter::CloseParenthesis)?;
+
+                expression = Expression { kind: ExpressionKind::Call { callee: Box::new(expression), arguments }, span };
+            } else if self.eat_operator(Operator::Dot) {
+                let member = self.expect_identifier()?;
+                expression = Expression { kind: ExpressionKind::MemberAccess(Box::new(expression), member), span };
+            } else if self.eat_delimiter(Delimiter::OpenBracket) {
+                let index = self.parse_expression()?;
+                self.expect_delimiter(Delimiter::CloseBracket)?;
+                expression = Expression { kind: ExpressionKind::Index(Box::new(expression), Box::new(index)), span };
+            } else {
+                break;
+            }
+        }
+
+        Some(expression)
+    }
+
+    fn parse_primary(&mut self) -> Option<Expression> {
+        let token = self.peek().clone();
+
+        let kind = match token.kind {
+            TokenKind::IntegerLiteral(value) => ExpressionKind::IntegerLiteral(value),
+            TokenKind::FloatLiteral(value) => ExpressionKind::FloatLiteral(value),
+            TokenKind::StringLiteral(value) => ExpressionKind::StringLiteral(value),
+            TokenKind::CharLiteral(value) => ExpressionKind::CharLiteral(value),
+            TokenKind::Keyword(Keyword::True) => ExpressionKind::BoolLiteral(true),
+            TokenKind::Keyword(Keyword::False) => ExpressionKind::BoolLiteral(false),
+            TokenKind::Identifier(name) => ExpressionKind::Identifier(name),
+            TokenKind::Delimiter(Delimiter::OpenParenthesis) => {
+                self.advance();
+                let expression = self.parse_expression()?;
+                self.expect_delimiter(Delimiter::CloseParenthesis)?;
+                return Some(expression);
+            }
+            _ => {
+                self.error_here(format!("Expected an expression, found {}.", token.kind));
+                return None;
+            }
+        };
+
+        self.advance();
+
+        Some(Expression { kind, span: token.span })
+    }
+
+    // Error recovery
+
+    /// Skip tokens until parsing can continue after an error, which is
+    /// after a semicolon, before a closing brace (of the surrounding block) or before one of the given keywords.
+    /// Blocks that are opened while skipping are skipped entirely.
+    /// At least one token is skipped if the error happened at the start position, so the parser can't get stuck.
+    fn synchronize(&mut self, start: usize, keywords: &[Keyword]) {
+        if self.position == start && !self.is_at_end() {
+            self.skip_token_or_block();
+        }
+
+        while !self.is_at_end() {
+            match self.peek().kind {
+                TokenKind::Delimiter(Delimiter::Semicolon) => {
+                    self.advance();
+                    return;
+                }
+                TokenKind::Delimiter(Delimiter::CloseBrace) => return,
+                TokenKind::Keyword(keyword) if keywords.contains(&keyword) => return,
+                _ => self.skip_token_or_block(),
+            }
+        }
+    }
+
+    fn skip_token_or_block(&mut self) {
+        if !self.check_delimiter(Delimiter::OpenBrace) {
+            self.advance();
+            return;
+        }
+
+        let mut depth = 0;
+        while !self.is_at_end() {
+            match self.advance().kind {
+                TokenKind::Delimiter(Delimiter::OpenBrace) => depth += 1,
+                TokenKind::Delimiter(Delimiter::CloseBrace) => {
+                    depth -= 1;
+                    if depth == 0 { return; }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Helpers
+
+    fn peek(&self) -> &Token {
+        // The last token is always EndOfFile, so it's returned for everything past the end
+        &self.tokens[self.position.min(self.tokens.len() - 1)]
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.peek().clone();
+        if !self.is_at_end() { self.position += 1; }
+        token
+    }
+
+    fn is_at_end(&self) -> bool {
+        self.peek().kind == TokenKind::EndOfFile
+    }
+
+    fn error_here(&mut self, message: String) {
+        let span = self.peek().span.clone();
+        self.diagnostics.push(Diagnostic::new(message, span));
+    }
+
+    fn check_keyword(&self, keyword: Keyword) -> bool {
+        self.peek().kind == TokenKind::Keyword(keyword)
+    }
+
+    fn check_delimiter(&self, delimiter: Delimiter) -> bool {
+        self.peek().kind == TokenKind::Delimiter(delimiter)
+    }
+
+    fn eat_keyword(&mut self, keyword: Keyword) -> bool {
+        let matches = self.check_keyword(keyword);
+        if matches { self.advance(); }
+        matches
+    }
+
+    fn eat_delimiter(&mut self, delimiter: Delimiter) -> bool {
+        let matches = self.check_delimiter(delimiter);
+        if matches { self.advance(); }
+        matches
+    }
+
+    fn eat_operator(&mut self, operator: Operator) -> bool {
+        let matches = self.peek().kind == TokenKind::Operator(operator);
+        if matches { self.advance(); }
+        matches
+    }
+
+    fn expect_keyword(&mut self, keyword: Keyword) -> Option<Span> {
+        if !self.check_keyword(keyword) {
+            self.error_here(format!("Expected \"{}\", found {}.", keyword.text(), self.peek().kind));
+            return None;
+        }
+        Some(self.advance().span)
+    }
+
+    fn expect_delimiter(&mut self, delimiter: Delimiter) -> Option<Span> {
+        if !self.check_delimiter(delimiter) {
+            self.error_here(format!("Expected \"{}\", found {}.", delimiter.character(), self.peek().kind));
+            return None;
+        }
+        Some(self.advance().span)
+    }
+
+    fn expect_identifier(&mut self) -> Option<String> {
+        let TokenKind::Identifier(name) = self.peek().kind.clone() else {
+            self.error_here(format!("Expected an identifier, found {}.", self.peek().kind));
+            return None;
+        };
+        self.advance();
+        Some(name)
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use crate::compiler::parser::ast::*;
+    use crate::compiler::parser::parser::parse_source;
+
+    #[test]
+    fn test_parse_module(){
+        let source = "
+            module my_app;
+            import std.io;
+
+            extern fun printf(format: *Char, ...) -> Int32;
+
+            type Main {
+                var counter: Int;
+
+                fun main() -> Int {
+                    var myVar: Int = 1 + 2 * 3;
+                    loop {
+                        if myVar >= 10 { break; } else if myVar == 5 { myVar += 2; } else { continue; }
+                        printf(\"%d\\n\", myVar);
+                    }
+                    return myVar;
+                }
+            }
+        ";
+
+        let (module, diagnostics) = parse_source("main.rsl".to_string(), source);
+        assert_eq!(diagnostics, vec![]);
+
+        assert_eq!(module.name, Some("my_app".to_string()));
+        assert_eq!(module.imports[0].path, vec!["std".to_string(), "io".to_string()]);
+
+        let Item::ExternFunction(printf) = &module.items[0] else { panic!("Expected an extern function") };
+        assert!(printf.is_variadic);
+        assert_eq!(printf.parameters[0].type_name, TypeName::Pointer(Box::new(TypeName::Named("Char".to_string()))));
+
+        let Item::Type(main) = &module.items[1] else { panic!("Expected a type") };
+        assert_eq!(main.name, "Main");
+        assert_eq!(main.fields[0].name, "counter");
+
+        let statements = &main.functions[0].body.statements;
+        assert_eq!(statements.len(), 3);
+
+        // Multiplication binds stronger than addition
+        let StatementKind::VariableDeclaration { value: Some(value), .. } = &statements[0].kind else { panic!("Expected a declaration") };
+        let ExpressionKind::Binary(BinaryOperator::Add, _, right) = &value.kind else { panic!("Expected an addition") };
+        assert!(matches!(right.kind, ExpressionKind::Binary(BinaryOperator::Multiply, _, _)));
+
+        let StatementKind::Loop(body) = &statements[1].kind else { panic!("Expected a loop") };
+        let StatementKind::If { else_block: Some(else_block), .. } = &body.statements[0].kind else { panic!("Expected an if statement") };
+        assert!(matches!(else_block.statements[0].kind, StatementKind::If { else_block: Some(_), .. }));
+        assert!(matches!(body.statements[1].kind, StatementKind::Expression(Expression { kind: ExpressionKind::Call { .. }, .. })));
+    }
+
+    #[test]
+    fn test_error_recovery(){
+        let source = "
+            fun first() {
+                var a = ;
+                var b = 2;
+                b = (3;
+            }
+            fun second() {}
+            type { }
+            fun third() { 5 = 4; }
+        ";
+
+        let (module, diagnostics) = parse_source("main.rsl".to_string(), source);
+
+        let messages: Vec<String> = diagnostics.iter().map(|x| x.to_string()).collect();
+        assert_eq!(messages, vec![
+            "main.rsl:3:25: Expected an expression, found \";\".".to_string(),
+            "main.rsl:5:23: Expected \")\", found \";\".".to_string(),
+            "main.rsl:8:18: Expected an identifier, found \"{\".".to_string(),
+            "main.rsl:9:27: Only variables, fields, elements and dereferenced pointers can be assigned to.".to_string(),
+        ]);
+
+        // All the functions are still there
+        let names: Vec<String> = module.items.iter().filter_map(|x| match x {
+            Item::Function(function) => Some(function.signature.name.clone()),
+            _ => None,
+        }).collect();
+        assert_eq!(names, vec!["first".to_string(), "second".to_string(), "third".to_string()]);
+    }
+}
@@ -1,6 +1,18 @@
+mod cli;
 mod compiler;
 mod util;
 
+use crate::cli::arguments::parse_arguments;
+use crate::cli::driver::run;
+use crate::util::exit::{exit, ExitCode};
+
 fn main() {
-    println!("Hello, world!");
+    let arguments: Vec<String> = std::env::args().skip(1).collect();
+
+    let options = match parse_arguments(&arguments) {
+        Ok(options) => options,
+        Err(message) => exit(format!("{}\n\n{}", message, cli::arguments::USAGE), ExitCode::BadArgument),
+    };
+
+    run(options);
 }
@@ -1,10 +1,18 @@
 use colorize::AnsiColor;
 use std::process;
+use crate::compiler::diagnostic::Diagnostic;
 
 pub fn exit(message: String, exit_code: ExitCode) -> ! {
     exit_with_variant(message, exit_code, 1);
 }
 
+/// Report every diagnostic (pointing at its position in the source) and exit.
+pub fn exit_with_diagnostics(diagnostics: &[Diagnostic], exit_code: ExitCode) -> ! {
+    let message = diagnostics.iter().map(|x| x.to_string()).collect::<Vec<String>>().join("\n");
+
+    exit(message, exit_code);
+}
+
 pub fn exit_with_variant(message: String, exit_code: ExitCode, variant: u8) -> ! {
     let mut message = message.red();
 
@@ -12,7 +20,7 @@ pub fn exit_with_variant(message: String, exit_code: ExitCode, variant: u8) -> !
 
 
     // Check if the variant is acceptable, if not change it to one and attach a warning
-    if variant < 1 || variant > 9 {
+    if !(1..=9).contains(&variant) {
         let warning = format!("Program was supposed to exit with variant {} of the exit code, but this is out of range (1...9), choosing one instead.", variant).yellow();
         message += warning.as_str();
         variant = 1;
@@ -31,6 +39,9 @@ pub fn exit_with_variant(message: String, exit_code: ExitCode, variant: u8) -> !
 
 pub enum ExitCode {
     BadArgument,                // A CLI argument is not as expected
+    BadCode,                    // The code that's being compiled contains errors
+    FileAccess,                 // A file couldn't be read or written
+    ExternalTool,               // A tool the compiler relies on (like the assembler) failed
     Internal                    // Internal malfunction with no further explanation
 }
 
@@ -38,6 +49,9 @@ impl ExitCode {
     pub fn get_code(&self) -> u8 {
         match self {
             ExitCode::BadArgument => 0, // This will be formated as x00 where x is non-zero
+            ExitCode::BadCode => 5,
+            ExitCode::FileAccess => 10,
+            ExitCode::ExternalTool => 20,
             ExitCode::Internal => 99,
         }
     }